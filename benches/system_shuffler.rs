@@ -0,0 +1,97 @@
+cfg_select! {
+    all(target_family = "wasm", target_os = "unknown") => {
+        const fn main() {}
+    }
+    _ => {
+        use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+
+        use endless_sky_generator_web::generators::system_shuffler::{self, config::SystemShufflerConfig};
+
+        use std::{fs, hint::black_box, path::PathBuf};
+
+        // the fixture is the stable data fetched by `get_stable_es_data.sh`
+        const DATA_PATHS: &str = "es_stable_data_paths.txt";
+        const WEB_FOLDER: &str = "www";
+
+        fn fixture() -> Option<(Vec<String>, Vec<String>)> {
+            let Ok(paths_list) = fs::read_to_string(PathBuf::from(WEB_FOLDER).join(DATA_PATHS)) else {
+                eprintln!("Missing \"{WEB_FOLDER}/{DATA_PATHS}\", run `./get_stable_es_data.sh` first!");
+                return None;
+            };
+
+            let mut paths = vec![];
+            let mut sources = vec![];
+
+            for path in paths_list.lines().filter(|path| !path.is_empty()) {
+                match fs::read_to_string(PathBuf::from(WEB_FOLDER).join(path)) {
+                    Ok(source) => {
+                        paths.push(path.to_string());
+                        sources.push(source);
+                    }
+                    Err(error) => {
+                        eprintln!("{error}");
+                        eprintln!("Failed to read \"{path}\"!");
+                        return None;
+                    }
+                }
+            }
+
+            Some((paths, sources))
+        }
+
+        fn settings() -> SystemShufflerConfig {
+            SystemShufflerConfig::builder().max_presets(8).build()
+        }
+
+        fn system_shuffler(c: &mut Criterion) {
+            let Some((paths, sources)) = fixture() else {
+                return;
+            };
+
+            let Some((data_folder, _)) = endless_sky_rw::read_upload(paths.clone(), sources.clone()) else {
+                eprintln!("Failed to parse the fixture!");
+                return;
+            };
+
+            {
+                let detection = system_shuffler::detect(data_folder.data(), true, &[]);
+
+                // printed so the timings below can be compared against the size of the data they ran on
+                eprintln!(
+                    "fixture: {} files, {} systems, {} events that modify systems",
+                    paths.len(),
+                    detection.system_count(),
+                    detection.event_count(),
+                );
+            }
+
+            c.bench_function("read_upload", |b| {
+                b.iter_batched(
+                    || (paths.clone(), sources.clone()),
+                    |(paths, sources)| endless_sky_rw::read_upload(paths, sources),
+                    BatchSize::LargeInput,
+                );
+            });
+
+            c.bench_function("detect", |b| {
+                b.iter(|| system_shuffler::detect(black_box(data_folder.data()), true, &[]).system_count());
+            });
+
+            c.bench_function("process_data (8 presets)", |b| {
+                b.iter_batched(
+                    settings,
+                    |settings| system_shuffler::process_data(black_box(&data_folder), settings),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        criterion_group! {
+            name = benches;
+            config = Criterion::default().sample_size(10);
+            targets = system_shuffler
+        }
+
+        criterion_main!(benches);
+    }
+}
@@ -3,71 +3,14 @@ cfg_select! {
         const fn main() {}
     }
     _ => {
-        fn main() -> std::process::ExitCode {
-            use endless_sky_generator_web::generators::chaos;
-
-            const FILE_NAME: &str = "chaos.zip";
-            const OUTPUT_FOLDER: &str = "output";
-
-            use std::{env, fs, path::PathBuf, process::ExitCode};
-
-            let mut arguments = env::args();
-            arguments.next();
+        use endless_sky_generator_web::{cli, generators::chaos};
 
-            if let Some(file_path) = arguments.next() {
-                let file_path = file_path.as_str();
-                let path = PathBuf::from(file_path);
-
-                if !path.exists() {
-                    eprintln!("Config file \"{file_path}\" does not exist!");
-                    ExitCode::FAILURE
-                } else if !path.is_file() {
-                    eprintln!("Config file \"{file_path}\" is not a file!");
-                    ExitCode::FAILURE
-                } else {
-                    match fs::read_to_string(path) {
-                        Ok(source) => {
-                            let Some(settings) = chaos::config::from_file::parse(source.as_str()) else {
-                                return ExitCode::FAILURE;
-                            };
-
-                            let data_path = ["www", "es_stable_data"].iter().collect::<PathBuf>();
-                            let data_path = data_path.as_path();
-
-                            endless_sky_rw::read_path_and_ignore_if(data_path, |p| {
-                                p.starts_with(data_path.join("_deprecated"))
-                            })
-                            .map_or(ExitCode::FAILURE, |data_folder| {
-                                match chaos::process_data(&data_folder, &settings) {
-                                    Ok(bytes) => {
-                                        match fs::create_dir_all(OUTPUT_FOLDER).and_then(|()| {
-                                            fs::write(PathBuf::from(OUTPUT_FOLDER).join(FILE_NAME), bytes)
-                                        }) {
-                                            Ok(()) => ExitCode::SUCCESS,
-                                            Err(error) => {
-                                                eprintln!("{error}");
-                                                ExitCode::FAILURE
-                                            }
-                                        }
-                                    }
-                                    Err(error) => {
-                                        eprintln!("{error}");
-                                        ExitCode::FAILURE
-                                    }
-                                }
-                            })
-                        }
-                        Err(error) => {
-                            eprintln!("{error}");
-                            eprintln!("Failed to read config \"{file_path}\"!");
-                            ExitCode::FAILURE
-                        }
-                    }
-                }
-            } else {
-                eprintln!("Expected the path to the config!");
-                ExitCode::FAILURE
-            }
+        fn main() -> std::process::ExitCode {
+            cli::generate_from_config_file(
+                "chaos.zip",
+                chaos::config::from_file::parse,
+                chaos::process_data,
+            )
         }
     }
 }
@@ -1,19 +1,45 @@
 use std::{
+    env,
     ffi::OsString,
     fs,
     path::{Path, PathBuf},
     process::ExitCode,
 };
 
+/// Arguments, all optional and in order: the data directory, the output file, and the name of the
+/// directory inside the data that is left out, so a continuous checkout can be listed as well
 fn main() -> ExitCode {
-    let data_path = ["www", "es_stable_data"].iter().collect::<PathBuf>();
-
-    let deprecated_path = data_path.as_path().join("_deprecated");
+    let mut arguments = env::args().skip(1);
+
+    let data_path = arguments.next().map_or_else(
+        || ["www", "es_stable_data"].iter().collect::<PathBuf>(),
+        PathBuf::from,
+    );
+
+    let output = arguments.next().map_or_else(
+        || {
+            ["www", "es_stable_data_paths.txt"]
+                .iter()
+                .collect::<PathBuf>()
+        },
+        PathBuf::from,
+    );
+
+    let deprecated_path = data_path
+        .as_path()
+        .join(arguments.next().as_deref().unwrap_or("_deprecated"));
     let deprecated_path = deprecated_path.as_path();
 
-    let output = ["www", "es_stable_data_paths.txt"]
-        .iter()
-        .collect::<PathBuf>();
+    if !data_path.is_dir() {
+        eprintln!("Data directory \"{}\" does not exist!", data_path.display());
+        return ExitCode::FAILURE;
+    }
+
+    // the listed paths are relative to wherever the list is written
+    let output_folder = output
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .to_path_buf();
 
     let mut paths_list = vec![];
 
@@ -27,12 +53,7 @@ fn main() -> ExitCode {
             let list_as_text = paths_list
                 .into_iter()
                 .fold(OsString::new(), |mut accum, path| {
-                    accum.push(
-                        path.components()
-                            .skip(1)
-                            .collect::<PathBuf>()
-                            .as_mut_os_string(),
-                    );
+                    accum.push(path.strip_prefix(&output_folder).unwrap_or(&path));
 
                     accum.push("\n");
 
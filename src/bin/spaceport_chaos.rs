@@ -0,0 +1,16 @@
+cfg_select! {
+    all(target_family = "wasm", target_os = "unknown") => {
+        const fn main() {}
+    }
+    _ => {
+        use endless_sky_generator_web::{cli, generators::spaceport_chaos};
+
+        fn main() -> std::process::ExitCode {
+            cli::generate_from_config_file(
+                "spaceport_chaos.zip",
+                spaceport_chaos::config::from_file::parse,
+                spaceport_chaos::process_data,
+            )
+        }
+    }
+}
@@ -7,6 +7,7 @@ cfg_select! {
             use endless_sky_generator_web::generators::system_shuffler;
 
             const FILE_NAME: &str = "system_shuffler.zip";
+            const REPORT_FILE_NAME: &str = "system_shuffler_report.json";
             const OUTPUT_FOLDER: &str = "output";
 
             use std::{env, fs, path::PathBuf, process::ExitCode};
@@ -38,11 +39,12 @@ cfg_select! {
                                 p.starts_with(data_path.join("_deprecated"))
                             })
                             .map_or(ExitCode::FAILURE, |data_folder| {
-                                match system_shuffler::process_data(&data_folder, settings) {
-                                    Ok(bytes) => {
-                                        match fs::create_dir_all(OUTPUT_FOLDER).and_then(|()| {
-                                            fs::write(PathBuf::from(OUTPUT_FOLDER).join(FILE_NAME), bytes)
-                                        }) {
+                                match system_shuffler::process_parsed_with_report(data_folder.data(), settings) {
+                                    Ok((bytes, report)) => {
+                                        match fs::create_dir_all(OUTPUT_FOLDER)
+                                            .and_then(|()| fs::write(PathBuf::from(OUTPUT_FOLDER).join(FILE_NAME), bytes))
+                                            .and_then(|()| fs::write(PathBuf::from(OUTPUT_FOLDER).join(REPORT_FILE_NAME), report.to_json()))
+                                        {
                                             Ok(()) => ExitCode::SUCCESS,
                                             Err(error) => {
                                                 eprintln!("{error}");
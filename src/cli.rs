@@ -0,0 +1,70 @@
+//! Plumbing shared by the command line generators that only need a config file, which read the
+//! stable data from `www/es_stable_data` and write their plugin into `output/`
+
+use endless_sky_rw::DataFolder;
+
+use std::{env, error::Error, fs, path::PathBuf, process::ExitCode};
+
+const OUTPUT_FOLDER: &str = "output";
+
+/// Generates a plugin from the config file given as the first argument into `output/{file_name}`
+/// Every failure is printed, since there is no one else to tell
+pub fn generate_from_config_file<S>(
+    file_name: &str,
+    parse: impl Fn(&str) -> Option<S>,
+    process: impl Fn(&DataFolder, &S) -> Result<Vec<u8>, Box<dyn Error>>,
+) -> ExitCode {
+    let mut arguments = env::args();
+    arguments.next();
+
+    let Some(file_path) = arguments.next() else {
+        eprintln!("Expected the path to the config!");
+        return ExitCode::FAILURE;
+    };
+
+    let file_path = file_path.as_str();
+    let path = PathBuf::from(file_path);
+
+    if !path.exists() {
+        eprintln!("Config file \"{file_path}\" does not exist!");
+        return ExitCode::FAILURE;
+    }
+
+    if !path.is_file() {
+        eprintln!("Config file \"{file_path}\" is not a file!");
+        return ExitCode::FAILURE;
+    }
+
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!("Failed to read config \"{file_path}\"!");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let Some(settings) = parse(source.as_str()) else {
+        return ExitCode::FAILURE;
+    };
+
+    let data_path = ["www", "es_stable_data"].iter().collect::<PathBuf>();
+    let data_path = data_path.as_path();
+
+    endless_sky_rw::read_path_and_ignore_if(data_path, |p| {
+        p.starts_with(data_path.join("_deprecated"))
+    })
+    .map_or(ExitCode::FAILURE, |data_folder| {
+        match process(&data_folder, &settings).and_then(|bytes| {
+            fs::create_dir_all(OUTPUT_FOLDER)?;
+            fs::write(PathBuf::from(OUTPUT_FOLDER).join(file_name), bytes)?;
+            Ok(())
+        }) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("{error}");
+                ExitCode::FAILURE
+            }
+        }
+    })
+}
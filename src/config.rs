@@ -2,6 +2,31 @@ use std::collections::HashMap;
 
 #[macro_export]
 macro_rules! __parse_config {
+    (
+        $source:expr => $settings_ty:ty as builder;
+        $($option:ident => { $($t:tt)+ })+
+    ) => {
+        $crate::config::parse($source).and_then(|config| {
+            let builder = <$settings_ty>::builder();
+
+            $(
+                let builder = builder.$option(
+                    match config.options().get(stringify!($option)) {
+                        Some($option) => $crate::config::config_option!(
+                            $option as $($t)+
+                        ),
+                        None => {
+                            eprintln!("{} was never set and is required!", stringify!($option));
+                            None
+                        },
+                    }?,
+                );
+            )+
+
+            Some(builder.build())
+        })
+    };
+
     (
         $source:expr => $settings_ty:ty;
         $($option:ident => { $($t:tt)+ })+
@@ -194,6 +219,149 @@ pub fn key_value_list<'a, 'b>(list: &'b Value<'a>) -> Option<HashMap<&'a str, &'
     }
 }
 
+/// Trims a string option, treating an empty one as not set
+#[must_use]
+pub fn optional_string(text: &str) -> Option<String> {
+    let text = text.trim();
+
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[must_use]
+pub fn string_list(list: &[Value<'_>]) -> Option<Vec<String>> {
+    list.iter()
+        .map(|value| {
+            if let Value::String(value) = value
+                && !value.trim().is_empty()
+            {
+                Some((*value).to_string())
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()
+}
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Turns settings bytes into unpadded URL-safe base64, so a share code can go straight into a link
+#[must_use]
+pub fn encode_share_code(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .zip([16, 8, 0])
+            .fold(0_u32, |bits, (&byte, shift)| {
+                bits | (u32::from(byte) << shift)
+            });
+
+        for shift in [18, 12, 6, 0].into_iter().take(chunk.len() + 1) {
+            code.push(char::from(BASE64URL[((bits >> shift) & 63) as usize]));
+        }
+    }
+
+    code
+}
+
+#[must_use]
+pub fn decode_share_code(code: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+
+    for chunk in code.trim().as_bytes().chunks(4) {
+        // a lone character can't hold a whole byte
+        if chunk.len() < 2 {
+            return None;
+        }
+
+        let mut bits = 0_u32;
+
+        for (&character, shift) in chunk.iter().zip([18, 12, 6, 0]) {
+            let value = BASE64URL.iter().position(|&b| b == character)?;
+
+            bits |= u32::try_from(value).ok()? << shift;
+        }
+
+        for shift in [16, 8, 0].into_iter().take(chunk.len() - 1) {
+            bytes.push(bits.to_be_bytes()[3 - shift / 8]);
+        }
+    }
+
+    Some(bytes)
+}
+
+/// Appends an unsigned LEB128 number, so small values (the usual case) only take a byte
+pub fn push_share_code_number(bytes: &mut Vec<u8>, mut number: u64) {
+    while number >= 0x80 {
+        bytes.push(number.to_le_bytes()[0] | 0x80);
+        number >>= 7;
+    }
+
+    bytes.push(number.to_le_bytes()[0]);
+}
+
+pub fn push_share_code_string(bytes: &mut Vec<u8>, text: &str) {
+    push_share_code_number(bytes, text.len() as u64);
+
+    bytes.extend_from_slice(text.as_bytes());
+}
+
+/// Reads back what the `push_share_code_*` functions wrote, returning `None` on anything malformed
+pub struct ShareCodeReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ShareCodeReader<'a> {
+    #[must_use]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.bytes.split_first()?;
+
+        self.bytes = rest;
+
+        Some(byte)
+    }
+
+    pub fn number(&mut self) -> Option<u64> {
+        let mut number = 0_u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+
+            number |= u64::from(byte & 0x7f).checked_shl(shift)?;
+
+            if byte & 0x80 == 0 {
+                return Some(number);
+            }
+        }
+
+        None
+    }
+
+    pub fn string(&mut self) -> Option<String> {
+        let length = usize::try_from(self.number()?).ok()?;
+
+        if length > self.bytes.len() {
+            return None;
+        }
+
+        let (text, rest) = self.bytes.split_at(length);
+
+        self.bytes = rest;
+
+        String::from_utf8(text.to_vec()).ok()
+    }
+}
+
 fn value<'a>(tokens: &[Token<'a>], at: &mut usize) -> Option<Value<'a>> {
     if let Some(token) = tokens.get(*at) {
         *at += 1;
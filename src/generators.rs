@@ -1,13 +1,73 @@
+pub mod bundle;
 pub mod chaos;
 pub mod full_map;
+pub mod jump_chaos;
 pub mod random_galaxy;
+pub mod spaceport_chaos;
+pub mod star_chaos;
 pub mod system_shuffler;
 
 use crate::zippy::Zip;
 
-use endless_sky_rw::{self, Data, Node, NodeIndex, SourceIndex, Span, Spanned, Token};
+use endless_sky_rw::{
+    self, Data, Node, NodeIndex, SourceIndex, Span, Spanned, Token, tree_from_tokens,
+};
 
-use std::{error::Error, io, path::PathBuf};
+use flate2::read::GzDecoder;
+
+use std::{
+    error::Error,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+use std::cell::RefCell;
+
+/// How deep nested `object`s are followed before giving up on that branch
+/// Real systems rarely go past a moon orbiting a planet, so 64 is far beyond anything legitimate
+/// while still keeping the recursion well away from overflowing the stack
+const MAX_OBJECT_DEPTH: u64 = 64;
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+thread_local! {
+    /// Where `log` collects everything instead of printing it while `capture_log` runs
+    static CAPTURED_LOG: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Progress for whoever is watching, the browser console on the web and stderr everywhere else
+fn log(text: &str) {
+    cfg_select! {
+        all(target_family = "wasm", target_os = "unknown") => {
+            crate::web::import_from_javascript::log(text);
+        }
+        _ => {
+            let captured = CAPTURED_LOG.with_borrow_mut(|captured| {
+                captured
+                    .as_mut()
+                    .map(|captured| captured.push(text.to_string()))
+                    .is_some()
+            });
+
+            if !captured {
+                eprintln!("{text}");
+            }
+        }
+    }
+}
+
+/// Runs `f` with everything logged on this thread collected instead of printed, so tests can check
+/// what a generator logs
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn capture_log<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = CAPTURED_LOG.replace(Some(vec![]));
+
+    let value = f();
+
+    let captured = CAPTURED_LOG.replace(outer).unwrap_or_default();
+
+    (value, captured)
+}
 
 fn zip_root_nodes<P: Into<PathBuf>>(
     archive: &mut Zip,
@@ -17,6 +77,18 @@ fn zip_root_nodes<P: Into<PathBuf>>(
 ) -> Result<(), Box<dyn Error>> {
     let path = P::into(path);
 
+    let text = root_nodes_text(&path, data, root_nodes)?;
+
+    archive.write_file(path, text.as_bytes())?;
+
+    Ok(())
+}
+
+fn root_nodes_text(
+    path: &Path,
+    data: &Data,
+    root_nodes: &[(SourceIndex, NodeIndex)],
+) -> Result<String, Box<dyn Error>> {
     let mut text = String::new();
 
     if data.write_root_nodes(&mut text, root_nodes).is_err() {
@@ -26,11 +98,155 @@ fn zip_root_nodes<P: Into<PathBuf>>(
         ))));
     }
 
-    archive.write_file(path, text.trim().as_bytes())?;
+    Ok(text.trim().to_string())
+}
+
+/// Builds the text of a `plugin.txt`, which only depends on the settings and never on the game data
+/// Empty about lines are left out
+fn plugin_txt<S: AsRef<str>>(
+    name: &str,
+    about_lines: &[S],
+    version: &str,
+    requires: &[String],
+) -> Result<String, Box<dyn Error>> {
+    let mut output_data = Data::default();
+
+    let plugin_txt_source = output_data.insert_source(String::new());
+
+    let plugin_name = tree_from_tokens!(
+        &mut output_data; plugin_txt_source =>
+        : "name", name ;
+    );
+
+    output_data.push_root_node(plugin_txt_source, plugin_name);
+
+    for about in about_lines.iter().map(|about| about.as_ref().trim()) {
+        if !about.is_empty() {
+            let plugin_about = tree_from_tokens!(
+                &mut output_data; plugin_txt_source =>
+                : "about", about ;
+            );
+
+            output_data.push_root_node(plugin_txt_source, plugin_about);
+        }
+    }
+
+    let plugin_version = tree_from_tokens!(
+        &mut output_data; plugin_txt_source =>
+        : "version", version ;
+    );
+
+    output_data.push_root_node(plugin_txt_source, plugin_version);
+
+    let dependencies = tree_from_tokens!(
+        &mut output_data; plugin_txt_source =>
+        : "dependencies" ;
+        {
+            : "game version", crate::GAME_VERSION ;
+        }
+    );
+
+    push_required_plugins(&mut output_data, plugin_txt_source, dependencies, requires)?;
+
+    output_data.push_root_node(plugin_txt_source, dependencies);
+
+    root_nodes_text(
+        Path::new("plugin.txt"),
+        &output_data,
+        output_data.root_nodes(),
+    )
+}
+
+fn push_required_plugins(
+    output_data: &mut Data,
+    plugin_txt_source: SourceIndex,
+    dependencies: NodeIndex,
+    requires: &[String],
+) -> Result<(), Box<dyn Error>> {
+    for required_plugin in requires {
+        if required_plugin.trim().is_empty() {
+            return Err(Box::new(io::Error::other(
+                "Required plugin names must not be empty :(",
+            )));
+        }
+
+        let plugin_requires = tree_from_tokens!(
+            output_data; plugin_txt_source =>
+            : "requires", required_plugin ;
+        );
+
+        output_data.push_child(dependencies, plugin_requires);
+    }
 
     Ok(())
 }
 
+/// Turns the bytes of one uploaded file into its text, inflating it first if it's gzip-compressed
+#[allow(clippy::missing_errors_doc)]
+pub fn decode_source(bytes: Vec<u8>) -> Result<String, io::Error> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut source = String::new();
+
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut source)?;
+
+        Ok(source)
+    } else {
+        String::from_utf8(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+/// Every parsed root node and all of its children as JSON, for debugging
+#[must_use]
+pub fn data_to_json(data: &Data) -> String {
+    format!(
+        "[{}]",
+        data.root_nodes()
+            .iter()
+            .map(|&(source_index, node_index)| node_json(data, source_index, node_index))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn node_json(data: &Data, source_index: SourceIndex, node_index: NodeIndex) -> String {
+    format!(
+        "{{\"tokens\":[{}],\"children\":[{}]}}",
+        data.get_tokens(node_index)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|token| data.get_lexeme(source_index, token))
+            .map(json_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        data.get_children(node_index)
+            .unwrap_or_default()
+            .iter()
+            .map(|&child| node_json(data, source_index, child))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+/// Quotes and escapes text for hand-built JSON, which is all the JSON this crate needs
+pub(crate) fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+
+    json.push('"');
+
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            ch if ch.is_control() => json.push_str(format!("\\u{:04x}", u32::from(ch)).as_str()),
+            ch => json.push(ch),
+        }
+    }
+
+    json.push('"');
+
+    json
+}
+
 fn copy_node(
     data: &Data,
     (source_index, node_index): (SourceIndex, NodeIndex),
@@ -0,0 +1,100 @@
+use crate::{
+    generators::{self, chaos, full_map, system_shuffler},
+    zippy::Zip,
+};
+
+use endless_sky_rw::{Data, DataFolder};
+
+use std::error::Error;
+
+const PLUGIN_NAME: &str = "Generator Bundle";
+
+const PLUGIN_VERSION: &str = "0.1.0";
+
+#[allow(clippy::missing_errors_doc)]
+pub fn process_data(
+    data_folder: &DataFolder,
+    shuffler_settings: system_shuffler::config::SystemShufflerConfig,
+    chaos_settings: &chaos::config::ChaosConfig,
+    include_full_map: bool,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    process_parsed(
+        data_folder.data(),
+        shuffler_settings,
+        chaos_settings,
+        include_full_map,
+    )
+}
+
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(
+    data: &Data,
+    shuffler_settings: system_shuffler::config::SystemShufflerConfig,
+    chaos_settings: &chaos::config::ChaosConfig,
+    include_full_map: bool,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut output = vec![];
+
+    let mut archive = Zip::new(&mut output);
+
+    let mut requires = vec![];
+
+    for required_plugin in shuffler_settings
+        .requires()
+        .iter()
+        .chain(chaos_settings.requires())
+    {
+        if !requires.contains(required_plugin) {
+            requires.push(required_plugin.clone());
+        }
+    }
+
+    description(&mut archive, requires.as_slice(), include_full_map)?;
+
+    archive.write_dir("data/")?;
+
+    // every generator gets its own data directory, so none of their files collide
+    archive.set_data_dir("data/shuffler/");
+
+    system_shuffler::write_plugin(data, shuffler_settings, &mut archive, false)?;
+
+    archive.set_data_dir("data/chaos/");
+
+    chaos::write_plugin(data, chaos_settings, [].as_slice(), &mut archive, false)?;
+
+    if include_full_map {
+        archive.set_data_dir("data/full_map/");
+
+        full_map::write_plugin(data, &mut archive, false)?;
+    }
+
+    archive.finish()?;
+
+    Ok(output)
+}
+
+fn description(
+    archive: &mut Zip,
+    requires: &[String],
+    include_full_map: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut about_lines = vec![
+        "Every generator bundled into one plugin:",
+        "- System Shuffler",
+        "- Chaos",
+    ];
+
+    if include_full_map {
+        about_lines.push("- Full Map");
+    }
+
+    let plugin_txt = generators::plugin_txt(
+        PLUGIN_NAME,
+        about_lines.as_slice(),
+        PLUGIN_VERSION,
+        requires,
+    )?;
+
+    archive.write_file("plugin.txt", plugin_txt.as_bytes())
+}
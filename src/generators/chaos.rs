@@ -2,7 +2,7 @@ pub mod config;
 
 use crate::{
     generators,
-    wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex},
+    wandom::{self, XoShiRo256SS, shuffle_index::ShuffleIndex},
     zippy::Zip,
 };
 
@@ -11,7 +11,13 @@ use endless_sky_rw::{
     tree_from_tokens,
 };
 
-use std::{collections::HashMap, error::Error, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    hash::Hash,
+    io,
+    path::PathBuf,
+};
 
 const PLUGIN_NAME: &str = "Chaos";
 
@@ -22,63 +28,403 @@ pub fn process_data(
     data_folder: &DataFolder,
     settings: &config::ChaosConfig,
 ) -> Result<Vec<u8>, Box<dyn Error>> {
-    let data = data_folder.data();
+    process_parsed(data_folder.data(), settings)
+}
 
-    let mut rng = XoShiRo256SS::new(*settings.seed());
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(
+    data: &Data,
+    settings: &config::ChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    process_parsed_with_images(data, [].as_slice(), settings)
+}
+
+/// Like `process_parsed`, but also packs the uploaded images the plugin's sprites point to
+/// Each image is a path (anything up to and including `images/` is ignored) and its bytes
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed_with_images(
+    data: &Data,
+    images: &[(String, Vec<u8>)],
+    settings: &config::ChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
     let mut output = vec![];
 
+    let mut archive = Zip::new(&mut output);
+
+    write_plugin(data, settings, images, &mut archive, true)?;
+
+    archive.finish()?;
+
+    Ok(output)
+}
+
+/// The sprite name the game gives an image file, which leaves out the extension, any `@2x` or `@sw`
+/// variant, and the frame number of animations (like the `+0` of `ship/kestrel+0.png`)
+fn sprite_name(image_path: &str) -> &str {
+    let image_path = image_path
+        .rsplit_once("images/")
+        .map_or(image_path, |(_, image_path)| image_path);
+
+    let mut sprite_name = image_path
+        .rsplit_once('.')
+        .map_or(image_path, |(sprite_name, _)| sprite_name);
+
+    for variant in ["@2x", "@sw"] {
+        sprite_name = sprite_name.strip_suffix(variant).unwrap_or(sprite_name);
+    }
+
+    let without_frame = sprite_name.trim_end_matches(|ch: char| ch.is_ascii_digit());
+
+    if without_frame.len() < sprite_name.len()
+        && let Some(without_frame) = without_frame.strip_suffix(['-', '~', '+', '=', '^'])
+    {
+        without_frame
+    } else {
+        sprite_name
+    }
+}
+
+fn find_sprite_names<'a>(
+    data: &'a Data,
+    source_index: SourceIndex,
+    node_index: NodeIndex,
+    sprite_names: &mut BTreeSet<&'a str>,
+) {
+    let mut lexemes = data
+        .get_tokens(node_index)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|token| data.get_lexeme(source_index, token));
+
+    if matches!(lexemes.next(), Some("sprite" | "thumbnail"))
+        && let Some(sprite_name) = lexemes.next()
+    {
+        sprite_names.insert(sprite_name);
+    }
+
+    for &child in data.get_children(node_index).unwrap_or_default() {
+        find_sprite_names(data, source_index, child, sprite_names);
+    }
+}
+
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt(settings: &config::ChaosConfig) -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        PLUGIN_NAME,
+        about_lines(settings).as_slice(),
+        PLUGIN_VERSION,
+        settings.requires(),
+    )
+}
+
+/// The `about` lines of the `plugin.txt`, one for every enabled domain followed by the seed
+#[must_use]
+pub fn about_lines(settings: &config::ChaosConfig) -> Vec<String> {
+    let mut about_lines = vec![];
+
+    let swapped = match (*settings.swap_names(), *settings.swap_assets()) {
+        (true, true) => "name and image",
+        (false, true) => "image",
+        _ => "name",
+    };
+
+    if *settings.outfits() {
+        about_lines.push(format!("Shuffles every outfit {swapped}."));
+    }
+
+    if *settings.ships() {
+        about_lines.push(format!("Shuffles every ship {swapped}."));
+    }
+
+    if *settings.by_category() && (*settings.outfits() || *settings.ships()) {
+        about_lines.push("Ships and outfits only swap within their category.".to_string());
+    }
+
+    if *settings.weight_by_cost() && (*settings.outfits() || *settings.ships()) {
+        about_lines.push("Ships and outfits only swap with others of a similar cost.".to_string());
+    }
+
+    if *settings.preserve_outfit_space() && *settings.outfits() {
+        about_lines.push("Outfits keep their own mass and outfit space.".to_string());
+    }
+
+    if *settings.systems() {
+        about_lines.push("Shuffles every system name.".to_string());
+    }
+
+    if *settings.planets() {
+        about_lines.push("Shuffles every planet name.".to_string());
+    }
+
+    if *settings.landscapes() {
+        about_lines.push("Shuffles every planet landscape.".to_string());
+    }
+
+    about_lines.push(format!("PRNG seed: {}", settings.seed()));
+
+    if *settings.outfit_seed() != 0 {
+        about_lines.push(format!("Outfit PRNG seed: {}", settings.outfit_seed()));
+    }
+
+    if *settings.ship_seed() != 0 {
+        about_lines.push(format!("Ship PRNG seed: {}", settings.ship_seed()));
+    }
+
+    about_lines
+}
+
+/// Writes the plugin into an archive that may be shared with other generators
+/// Without `plugin_txt`, whoever owns the archive is expected to write the `plugin.txt`
+pub(crate) fn write_plugin(
+    data: &Data,
+    settings: &config::ChaosConfig,
+    images: &[(String, Vec<u8>)],
+    archive: &mut Zip,
+    plugin_txt: bool,
+) -> Result<(), Box<dyn Error>> {
+    if !(*settings.outfits()
+        || *settings.ships()
+        || *settings.systems()
+        || *settings.planets()
+        || *settings.landscapes())
+    {
+        return Err(Box::new(io::Error::other(
+            "Enable at least one of outfits, ships, systems, planets, or landscapes :(",
+        )));
+    }
+
+    if (*settings.outfits() || *settings.ships())
+        && !(*settings.swap_names() || *settings.swap_assets())
+    {
+        return Err(Box::new(io::Error::other(
+            "Outfits and ships need at least one of their names or images swapped :(",
+        )));
+    }
+
+    let mut rng = XoShiRo256SS::new(*settings.seed());
+
     let mut generator = Chaos {
-        archive: Zip::new(&mut output),
+        archive,
         output_data: Data::default(),
+        swaps: vec![],
     };
 
-    generator.description(settings)?;
+    if plugin_txt {
+        generator.description(settings)?;
+    }
 
     generator.archive.write_dir("data/")?;
 
-    if *settings.outfits() {
-        let mut outfit_rng = XoShiRo256SS::new(rng.step());
+    // every domain always takes its seed, so enabling or disabling one doesn't change the others
+    let outfit_seed = rng.step();
+    let ship_seed = rng.step();
+
+    // a seed of zero follows the main seed, so plugins generated with only `seed` stay the same
+    let outfit_seed = if *settings.outfit_seed() == 0 {
+        outfit_seed
+    } else {
+        *settings.outfit_seed()
+    };
+    let ship_seed = if *settings.ship_seed() == 0 {
+        ship_seed
+    } else {
+        *settings.ship_seed()
+    };
 
-        generator.outfits(data, &mut outfit_rng)?;
+    // the domain seeds can be picked by hand, and neighbours like 1 and 2 are the likely ones
+    let mut outfit_rng = XoShiRo256SS::with_warmup(outfit_seed, wandom::WARMUP_STEPS);
+    let mut ship_rng = XoShiRo256SS::with_warmup(ship_seed, wandom::WARMUP_STEPS);
+    let mut system_name_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
+    let mut planet_name_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
+    let mut landscape_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
+
+    // `exclude` applies to outfits and ships alike, so a name is only unknown if it's neither
+    if *settings.outfits() || *settings.ships() {
+        let known_names = node_path_iter!(data; "outfit" | "ship")
+            .filter_map(|(source_index, node_index)| {
+                data.get_tokens(node_index)
+                    .filter(|tokens| tokens.len() >= 2)
+                    .and_then(|tokens| tokens.last())
+                    .and_then(|token| data.get_lexeme(source_index, token))
+            })
+            .collect::<BTreeSet<_>>();
+
+        for name in settings
+            .exclude()
+            .iter()
+            .filter(|name| !known_names.contains(name.as_str()))
+        {
+            generators::log(
+                format!(
+                    "Excluded outfit or ship `{name}` does not exist in the data, so it was ignored"
+                )
+                .as_str(),
+            );
+        }
     }
 
-    if *settings.ships() {
-        let mut ship_rng = XoShiRo256SS::new(rng.step());
+    if *settings.outfits() {
+        generator.outfits(data, &mut outfit_rng, settings)?;
+    }
 
-        generator.ships(data, &mut ship_rng)?;
+    if *settings.ships() {
+        generator.ships(data, &mut ship_rng, settings)?;
     }
 
     if *settings.systems() {
-        let mut system_name_rng = XoShiRo256SS::new(rng.step());
-
         generator.systems(data, &mut system_name_rng)?;
     }
 
     if *settings.planets() {
-        let mut planet_name_rng = XoShiRo256SS::new(rng.step());
-
         generator.planets(data, &mut planet_name_rng)?;
     }
 
-    generator.archive.finish()?;
+    if *settings.landscapes() {
+        generator.landscapes(data, &mut landscape_rng)?;
+    }
 
-    Ok(output)
+    if !images.is_empty() {
+        generator.pack_images(images)?;
+    }
+
+    generator.swap_manifest(*settings.seed(), (outfit_seed, ship_seed))?;
+
+    Ok(())
 }
 
-struct Chaos<'a> {
-    archive: Zip<'a>,
+struct Chaos<'a, 'b> {
+    archive: &'a mut Zip<'b>,
     output_data: Data,
+    /// Every outfit and ship swap, as `kind "original" -> "swap"` lines for `swaps.txt`
+    swaps: Vec<String>,
+}
+
+/// Pairs every key with the one it's swapped with, but only ever with a key in the same group,
+/// shuffling the groups one after another in order
+/// With `no_self_swap` no key is paired with itself, unless it's alone in its group
+/// Excluded keys are shuffled too and then skipped over, so a key that would have become an
+/// excluded one becomes whatever that one would have, and every other pair stays the same
+fn shuffled_pairs<K: Copy + Eq + Hash, G: Ord>(
+    keys: &[K],
+    no_self_swap: bool,
+    (group, is_excluded): (impl Fn(K) -> G, impl Fn(K) -> bool),
+    rng: &mut XoShiRo256SS,
+) -> HashMap<K, K> {
+    let pairs = all_shuffled_pairs(keys, no_self_swap, group, rng);
+
+    pairs
+        .iter()
+        .filter(|&(&original, _)| !is_excluded(original))
+        .map(|(&original, &replacement)| {
+            let mut replacement = replacement;
+
+            // the pairs are a permutation, so this ends at the latest back at `original`
+            while is_excluded(replacement) {
+                replacement = pairs[&replacement];
+            }
+
+            (original, replacement)
+        })
+        .collect()
+}
+
+/// With every key in the same group, this is the same as shuffling all of them at once
+fn all_shuffled_pairs<K: Copy + Eq + Hash, G: Ord>(
+    keys: &[K],
+    no_self_swap: bool,
+    group: impl Fn(K) -> G,
+    rng: &mut XoShiRo256SS,
+) -> HashMap<K, K> {
+    let mut groups = BTreeMap::<G, Vec<K>>::new();
+
+    for &key in keys {
+        groups.entry(group(key)).or_default().push(key);
+    }
+
+    groups
+        .values()
+        .flat_map(|group_keys| {
+            if no_self_swap {
+                group_keys.deranged_pairs_with_rng(rng)
+            } else {
+                group_keys.shuffled_pairs_with_rng(rng)
+            }
+            .into_iter()
+            .map(|(&original, &replacement)| (original, replacement))
+            .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Costs with the same number of digits are in the same tier, counting a cost of 0 as one digit
+const fn cost_tier(cost: u64) -> u32 {
+    match cost.checked_ilog10() {
+        Some(tier) => tier,
+        None => 0,
+    }
+}
+
+/// The last whole number `cost` directly under a node
+fn last_cost(data: &Data, source_index: SourceIndex, node_index: NodeIndex) -> Option<u64> {
+    node_path_iter!(data => (source_index, node_index); "cost")
+        .filter_map(|(_, cost)| {
+            data.get_tokens(cost)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+                .and_then(|cost| cost.parse().ok())
+        })
+        .last()
+}
+
+/// A ship's cost is inside its `attributes`
+fn last_ship_cost(data: &Data, source_index: SourceIndex, node_index: NodeIndex) -> Option<u64> {
+    node_path_iter!(data => (source_index, node_index); "attributes")
+        .filter_map(|(_, attributes)| last_cost(data, source_index, attributes))
+        .last()
+}
+
+/// The last `category` directly under a node
+fn last_category(data: &Data, source_index: SourceIndex, node_index: NodeIndex) -> Option<&str> {
+    node_path_iter!(data => (source_index, node_index); "category")
+        .filter_map(|(_, category)| {
+            data.get_tokens(category)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+        })
+        .last()
+}
+
+/// A ship's category is inside its `attributes`
+fn last_ship_category(
+    data: &Data,
+    source_index: SourceIndex,
+    node_index: NodeIndex,
+) -> Option<&str> {
+    node_path_iter!(data => (source_index, node_index); "attributes")
+        .filter_map(|(_, attributes)| last_category(data, source_index, attributes))
+        .last()
 }
 
 struct OutfitData<'a> {
     name: &'a str,
+    category: Option<&'a str>,
+    cost: Option<u64>,
     thumbnail: NodeIndex,
     series: Option<NodeIndex>,
     index: Option<NodeIndex>,
+    /// The `sound` of the outfit's `weapon`, while effects are left out since the game adds them
+    /// to the ones a weapon already has instead of replacing them
+    sound: Option<NodeIndex>,
+    mass: Option<NodeIndex>,
+    outfit_space: Option<NodeIndex>,
 }
 
 struct ShipData<'a> {
     name: &'a str,
+    category: Option<&'a str>,
+    cost: Option<u64>,
     model: &'a str,
     noun: Option<NodeIndex>,
     plural: Option<NodeIndex>,
@@ -94,14 +440,14 @@ struct PlanetData<'a> {
     name: &'a str,
 }
 
-impl Chaos<'_> {
+impl Chaos<'_, '_> {
     fn zip_root_nodes<P: Into<PathBuf>>(
         &mut self,
         path: P,
         from: usize,
     ) -> Result<(), Box<dyn Error>> {
         generators::zip_root_nodes(
-            &mut self.archive,
+            self.archive,
             path,
             &self.output_data,
             &self.output_data.root_nodes()[from..],
@@ -158,123 +504,165 @@ impl Chaos<'_> {
         .last()
     }
 
-    fn description(&mut self, settings: &config::ChaosConfig) -> Result<(), Box<dyn Error>> {
-        let output_root_node_count = self.output_data.root_nodes().len();
-        let plugin_txt_source = self.output_data.insert_source(String::new());
-
-        let plugin_name = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "name", PLUGIN_NAME ;
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_name);
+    /// Only the images some `sprite` or `thumbnail` of the plugin refers to are packed, to keep it small
+    fn pack_images(&mut self, images: &[(String, Vec<u8>)]) -> Result<(), Box<dyn Error>> {
+        let mut sprite_names = BTreeSet::new();
 
-        if *settings.outfits() {
-            let plugin_about = tree_from_tokens!(
-                &mut self.output_data; plugin_txt_source =>
-                : "about", "Shuffles every outfit name and image." ;
+        for &(source_index, node_index) in self.output_data.root_nodes() {
+            find_sprite_names(
+                &self.output_data,
+                source_index,
+                node_index,
+                &mut sprite_names,
             );
-
-            self.output_data
-                .push_root_node(plugin_txt_source, plugin_about);
         }
 
-        if *settings.ships() {
-            let plugin_about = tree_from_tokens!(
-                &mut self.output_data; plugin_txt_source =>
-                : "about", "Shuffles every ship name and image." ;
-            );
-
-            self.output_data
-                .push_root_node(plugin_txt_source, plugin_about);
-        }
+        self.archive.write_dir("images/")?;
 
-        if *settings.systems() {
-            let plugin_about = tree_from_tokens!(
-                &mut self.output_data; plugin_txt_source =>
-                : "about", "Shuffles every system name." ;
-            );
+        for (image_path, bytes) in images {
+            if sprite_names.contains(sprite_name(image_path)) {
+                let image_path = image_path
+                    .rsplit_once("images/")
+                    .map_or(image_path.as_str(), |(_, image_path)| image_path);
 
-            self.output_data
-                .push_root_node(plugin_txt_source, plugin_about);
+                self.archive
+                    .write_file_stored(format!("images/{image_path}"), bytes.as_slice())?;
+            }
         }
 
-        if *settings.planets() {
-            let plugin_about = tree_from_tokens!(
-                &mut self.output_data; plugin_txt_source =>
-                : "about", "Shuffles every planet name." ;
-            );
-
-            self.output_data
-                .push_root_node(plugin_txt_source, plugin_about);
-        }
+        Ok(())
+    }
 
-        let plugin_version = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "version", PLUGIN_VERSION ;
-        );
+    /// Lists which outfit and ship became which, at the root of the archive so the game never loads it
+    fn swap_manifest(
+        &mut self,
+        seed: u64,
+        (outfit_seed, ship_seed): (u64, u64),
+    ) -> Result<(), Box<dyn Error>> {
+        let mut manifest =
+            format!("seed: {seed}\noutfit seed: {outfit_seed}\nship seed: {ship_seed}\n\n");
 
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_version);
+        for swap in &self.swaps {
+            manifest.push_str(swap);
+            manifest.push('\n');
+        }
 
-        let dependencies = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "dependencies" ;
-            {
-                : "game version", crate::GAME_VERSION ;
-            }
-        );
+        self.archive.write_file("swaps.txt", manifest.as_bytes())
+    }
 
-        self.output_data
-            .push_root_node(plugin_txt_source, dependencies);
+    fn description(&mut self, settings: &config::ChaosConfig) -> Result<(), Box<dyn Error>> {
+        let plugin_txt = preview_plugin_txt(settings)?;
 
-        self.zip_root_nodes("plugin.txt", output_root_node_count)
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
     }
 
-    fn outfits(&mut self, data: &Data, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
+    /// Without `swap_names` every outfit keeps its own name, and without `swap_assets` its own images
+    /// With `by_category` outfits are only swapped with others of the same `category`, and with
+    /// `weight_by_cost` with others of about the same `cost`
+    /// With `preserve_outfit_space` the original's `mass` and `outfit space` are written back into
+    /// the redefinition, so whatever else the swap changes, every ship stays as fittable as before
+    fn outfits(
+        &mut self,
+        data: &Data,
+        rng: &mut XoShiRo256SS,
+        settings: &config::ChaosConfig,
+    ) -> Result<(), Box<dyn Error>> {
         let output_root_node_count = self.output_data.root_nodes().len();
 
         let outfit_output_source = self.output_data.insert_source(String::new());
 
         let outfit_data = self.get_outfit_data(data, outfit_output_source);
 
-        let mut outfit_keys = outfit_data.keys().collect::<Vec<_>>();
-
-        outfit_keys.sort_unstable();
-
-        let outfit_swaps = outfit_keys
-            .iter()
-            .zip(
-                outfit_keys
-                    .shuffled_indices_with_rng(rng)
-                    .into_iter()
-                    .filter_map(|i| outfit_keys.get(i)),
-            )
-            .collect::<HashMap<_, _>>();
+        let excluded = settings.exclude();
+
+        let outfit_keys = outfit_data
+            .keys()
+            .filter(|outfit| !excluded.iter().any(|excluded| excluded == *outfit))
+            .collect::<Vec<_>>();
+
+        let outfit_swaps = shuffled_pairs(
+            outfit_data.keys().collect::<Vec<_>>().as_slice(),
+            *settings.no_self_swap(),
+            (
+                |outfit: &&str| {
+                    outfit_data.get(outfit).map(|outfit| {
+                        (
+                            outfit.category.filter(|_| *settings.by_category()),
+                            outfit
+                                .cost
+                                .map(cost_tier)
+                                .filter(|_| *settings.weight_by_cost()),
+                        )
+                    })
+                },
+                |outfit: &&str| excluded.iter().any(|excluded| excluded == outfit),
+            ),
+            rng,
+        );
 
         for original in &outfit_keys {
             let swap = outfit_swaps.get(original).expect("Outfit data must exist");
             let swapped_data = outfit_data.get(**swap).expect("Outfit data must exist");
 
+            self.swaps
+                .push(format!("outfit \"{original}\" -> \"{swap}\""));
+
+            let original_data = outfit_data.get(**original).expect("Outfit data must exist");
+
+            let name_data = if *settings.swap_names() {
+                swapped_data
+            } else {
+                original_data
+            };
+
+            let asset_data = if *settings.swap_assets() {
+                swapped_data
+            } else {
+                original_data
+            };
+
             let outfit = tree_from_tokens!(
                 &mut self.output_data; outfit_output_source =>
                 : "outfit", original ;
                 {
-                    : "display name", swapped_data.name ;
+                    : "display name", name_data.name ;
                 }
             );
 
-            self.output_data.push_child(outfit, swapped_data.thumbnail);
+            self.output_data.push_child(outfit, asset_data.thumbnail);
 
-            if let Some(series) = swapped_data.series {
+            if let Some(series) = asset_data.series {
                 self.output_data.push_child(outfit, series);
             }
 
-            if let Some(index) = swapped_data.index {
+            if let Some(index) = asset_data.index {
                 self.output_data.push_child(outfit, index);
             }
 
+            // only weapons that already make a sound get another one, and only the `sound` itself
+            // is written into their `weapon`, leaving every stat alone
+            if *settings.swap_assets()
+                && original_data.sound.is_some()
+                && let Some(sound) = asset_data.sound
+            {
+                let weapon = tree_from_tokens!(
+                    &mut self.output_data; outfit_output_source =>
+                    : "weapon" ;
+                );
+
+                self.output_data.push_child(weapon, sound);
+                self.output_data.push_child(outfit, weapon);
+            }
+
+            if *settings.preserve_outfit_space() {
+                for footprint in [original_data.mass, original_data.outfit_space]
+                    .into_iter()
+                    .flatten()
+                {
+                    self.output_data.push_child(outfit, footprint);
+                }
+            }
+
             self.output_data
                 .push_root_node(outfit_output_source, outfit);
         }
@@ -282,7 +670,15 @@ impl Chaos<'_> {
         self.zip_root_nodes("data/outfits.txt", output_root_node_count)
     }
 
-    fn ships(&mut self, data: &Data, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
+    /// Without `swap_names` every ship keeps its own names, and without `swap_assets` its own images
+    /// With `by_category` ships are only swapped with others of the same `category`, and with
+    /// `weight_by_cost` with others of about the same `cost`
+    fn ships(
+        &mut self,
+        data: &Data,
+        rng: &mut XoShiRo256SS,
+        settings: &config::ChaosConfig,
+    ) -> Result<(), Box<dyn Error>> {
         let output_root_node_count = self.output_data.root_nodes().len();
 
         let ship_output_source = self.output_data.insert_source(String::new());
@@ -291,31 +687,58 @@ impl Chaos<'_> {
 
         self.get_ship_variant_data(data, ship_output_source, &mut ship_data);
 
-        let mut ship_keys = ship_data.keys().collect::<Vec<_>>();
-
-        ship_keys.sort_unstable();
-
-        let ship_swaps = ship_keys
-            .iter()
-            .zip(
-                ship_keys
-                    .shuffled_indices_with_rng(rng)
-                    .into_iter()
-                    .filter_map(|i| ship_keys.get(i)),
-            )
-            .collect::<HashMap<_, _>>();
+        let excluded = settings.exclude();
+
+        let ship_keys = ship_data
+            .keys()
+            .filter(|ship| !excluded.iter().any(|excluded| excluded == *ship))
+            .collect::<Vec<_>>();
+
+        let ship_swaps = shuffled_pairs(
+            ship_data.keys().collect::<Vec<_>>().as_slice(),
+            *settings.no_self_swap(),
+            (
+                |ship: &&str| {
+                    ship_data.get(ship).map(|ship| {
+                        (
+                            ship.category.filter(|_| *settings.by_category()),
+                            ship.cost
+                                .map(cost_tier)
+                                .filter(|_| *settings.weight_by_cost()),
+                        )
+                    })
+                },
+                |ship: &&str| excluded.iter().any(|excluded| excluded == ship),
+            ),
+            rng,
+        );
 
         for original in &ship_keys {
             let original_data = ship_data.get(**original).expect("Ship data must exist");
             let swap = ship_swaps.get(original).expect("Ship data must exist");
             let swapped_data = ship_data.get(**swap).expect("Ship data must exist");
 
+            self.swaps
+                .push(format!("ship \"{original}\" -> \"{swap}\""));
+
+            let name_data = if *settings.swap_names() {
+                swapped_data
+            } else {
+                original_data
+            };
+
+            let asset_data = if *settings.swap_assets() {
+                swapped_data
+            } else {
+                original_data
+            };
+
             let ship = if original_data.model == **original {
                 tree_from_tokens!(
                     &mut self.output_data; ship_output_source =>
                     : "ship", original ;
                     {
-                        : "display name", swapped_data.name ;
+                        : "display name", name_data.name ;
                     }
                 )
             } else {
@@ -323,26 +746,26 @@ impl Chaos<'_> {
                     &mut self.output_data; ship_output_source =>
                     : "ship", original_data.model, original ;
                     {
-                        : "display name", swapped_data.name ;
+                        : "display name", name_data.name ;
                     }
                 )
             };
 
-            if let Some(noun) = swapped_data.noun {
+            if let Some(noun) = name_data.noun {
                 self.output_data.push_child(ship, noun);
             }
 
-            if let Some(plural) = swapped_data.plural {
+            if let Some(plural) = name_data.plural {
                 self.output_data.push_child(ship, plural);
             }
 
-            if let Some(sprite) = swapped_data.sprite {
+            if let Some(sprite) = asset_data.sprite {
                 self.output_data.push_child(ship, sprite);
             }
 
-            if let Some(thumbnail) = swapped_data.thumbnail {
+            if let Some(thumbnail) = asset_data.thumbnail {
                 self.output_data.push_child(ship, thumbnail);
-            } else if let Some(sprite) = swapped_data.sprite
+            } else if let Some(sprite) = asset_data.sprite
                 && let Some(tokens) = self.output_data.get_tokens(sprite)
                 && let Some(token) = tokens.get(1)
                 && let Some(sprite) = self.output_data.get_lexeme(ship_output_source, token)
@@ -370,18 +793,11 @@ impl Chaos<'_> {
 
         let system_data = Self::get_system_data(data);
 
-        let mut system_keys = system_data.keys().collect::<Vec<_>>();
-
-        system_keys.sort_unstable();
+        let system_keys = system_data.keys().collect::<Vec<_>>();
 
         let system_swaps = system_keys
-            .iter()
-            .zip(
-                system_keys
-                    .shuffled_indices_with_rng(rng)
-                    .into_iter()
-                    .filter_map(|i| system_keys.get(i)),
-            )
+            .shuffled_pairs_with_rng(rng)
+            .into_iter()
             .collect::<HashMap<_, _>>();
 
         for original in &system_keys {
@@ -410,18 +826,11 @@ impl Chaos<'_> {
 
         let planet_data = Self::get_planet_data(data);
 
-        let mut planet_keys = planet_data.keys().collect::<Vec<_>>();
-
-        planet_keys.sort_unstable();
+        let planet_keys = planet_data.keys().collect::<Vec<_>>();
 
         let planet_swaps = planet_keys
-            .iter()
-            .zip(
-                planet_keys
-                    .shuffled_indices_with_rng(rng)
-                    .into_iter()
-                    .filter_map(|i| planet_keys.get(i)),
-            )
+            .shuffled_pairs_with_rng(rng)
+            .into_iter()
             .collect::<HashMap<_, _>>();
 
         for original in &planet_keys {
@@ -443,11 +852,55 @@ impl Chaos<'_> {
         self.zip_root_nodes("data/planets.txt", output_root_node_count)
     }
 
+    /// Only planets with a landscape take part, and each is redefined with nothing but a new one
+    fn landscapes(&mut self, data: &Data, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
+        let output_root_node_count = self.output_data.root_nodes().len();
+
+        let landscape_output_source = self.output_data.insert_source(String::new());
+
+        let landscape_data = Self::get_landscape_data(data);
+
+        let planet_keys = landscape_data.keys().collect::<Vec<_>>();
+
+        let landscape_swaps = planet_keys
+            .shuffled_pairs_with_rng(rng)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        for original in &planet_keys {
+            let swap = landscape_swaps
+                .get(original)
+                .expect("Landscape data must exist");
+            let landscape = landscape_data
+                .get(**swap)
+                .expect("Landscape data must exist");
+
+            if landscape_data.get(**original) == Some(landscape) {
+                continue;
+            }
+
+            let planet = tree_from_tokens!(
+                &mut self.output_data; landscape_output_source =>
+                : "planet", original ;
+                {
+                    : "landscape", landscape ;
+                }
+            );
+
+            self.output_data
+                .push_root_node(landscape_output_source, planet);
+        }
+
+        self.zip_root_nodes("data/landscapes.txt", output_root_node_count)
+    }
+
+    /// Copies are made in the order the data was read and kept sorted by name, and only the sorted
+    /// keys are iterated when writing, so the same seed always gives a byte-identical plugin
     fn get_outfit_data<'a>(
         &mut self,
         data: &'a Data,
         outfit_output_source: SourceIndex,
-    ) -> HashMap<&'a str, OutfitData<'a>> {
+    ) -> BTreeMap<&'a str, OutfitData<'a>> {
         node_path_iter!(data; "outfit")
             .filter(|(source_index, node_index)| {
                 data.get_tokens(*node_index)
@@ -469,7 +922,7 @@ impl Chaos<'_> {
                     })
             })
             .fold(
-                HashMap::new(),
+                BTreeMap::new(),
                 |mut accum, (outfit_source_index, outfit)| {
                     let outfit_name = data
                         .get_tokens(outfit)
@@ -479,6 +932,9 @@ impl Chaos<'_> {
                             "The iterator should use a filter to ensure all outfits have a name",
                         );
 
+                    // a repeated definition patches the previous one instead of replacing it
+                    let previous = accum.remove(outfit_name);
+
                     accum.insert(
                         outfit_name,
                         OutfitData {
@@ -492,16 +948,35 @@ impl Chaos<'_> {
                                         })
                                     })
                                     .last()
+                                    .or_else(|| previous.as_ref().map(|previous| previous.name))
                                     .map_or(outfit_name, |outfit_name| outfit_name),
+                            category: last_category(data, outfit_source_index, outfit)
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.category)),
+                            cost: last_cost(data, outfit_source_index, outfit)
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.cost)),
                             thumbnail:
-                                self.get_copy_of_child_node(data, (outfit_source_index, outfit), "thumbnail", 2, outfit_output_source).unwrap_or_else(||
+                                self.get_copy_of_child_node(data, (outfit_source_index, outfit), "thumbnail", 2, outfit_output_source)
+                                    .or_else(|| previous.as_ref().map(|previous| previous.thumbnail))
+                                    .unwrap_or_else(||
                                         tree_from_tokens!(
                                             &mut self.output_data; outfit_output_source =>
                                             : "thumbnail", "outfit/unknown" ;
                                     ),
                                 ),
-                            series: self.get_copy_of_child_node(data, (outfit_source_index, outfit), "series", 2, outfit_output_source),
-                            index: self.get_copy_of_child_node(data, (outfit_source_index, outfit), "index", 2, outfit_output_source),
+                            series: self.get_copy_of_child_node(data, (outfit_source_index, outfit), "series", 2, outfit_output_source)
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.series)),
+                            index: self.get_copy_of_child_node(data, (outfit_source_index, outfit), "index", 2, outfit_output_source)
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.index)),
+                            sound: node_path_iter!(data => (outfit_source_index, outfit); "weapon")
+                                .filter_map(|(_, weapon)| {
+                                    self.get_copy_of_child_node(data, (outfit_source_index, weapon), "sound", 2, outfit_output_source)
+                                })
+                                .last()
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.sound)),
+                            mass: self.get_copy_of_child_node(data, (outfit_source_index, outfit), "mass", 2, outfit_output_source)
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.mass)),
+                            outfit_space: self.get_copy_of_child_node(data, (outfit_source_index, outfit), "outfit space", 2, outfit_output_source)
+                                .or_else(|| previous.as_ref().and_then(|previous| previous.outfit_space)),
                         },
                     );
 
@@ -514,27 +989,32 @@ impl Chaos<'_> {
         &mut self,
         data: &'a Data,
         ship_output_source: SourceIndex,
-    ) -> HashMap<&'a str, ShipData<'a>> {
+    ) -> BTreeMap<&'a str, ShipData<'a>> {
         node_path_iter!(data; "ship")
             .filter(|(_, node_index)| {
                 data.get_tokens(*node_index)
                     .map_or(0, <[Spanned<Token>]>::len)
                     == 2
             })
-            .fold(HashMap::new(), |mut accum, (ship_source_index, ship)| {
+            .fold(BTreeMap::new(), |mut accum, (ship_source_index, ship)| {
                 let ship_name = data
                     .get_tokens(ship)
                     .and_then(|tokens| tokens.get(1))
                     .and_then(|token| data.get_lexeme(ship_source_index, token))
                     .expect("The iterator should use a filter to ensure all ships have a name");
 
-                let ship_sprite = self.get_copy_of_child_node(
-                    data,
-                    (ship_source_index, ship),
-                    "sprite",
-                    2,
-                    ship_output_source,
-                );
+                // a repeated definition patches the previous one instead of replacing it
+                let previous = accum.remove(ship_name);
+
+                let ship_sprite = self
+                    .get_copy_of_child_node(
+                        data,
+                        (ship_source_index, ship),
+                        "sprite",
+                        2,
+                        ship_output_source,
+                    )
+                    .or_else(|| previous.as_ref().and_then(|previous| previous.sprite));
 
                 accum.insert(
                     ship_name,
@@ -548,30 +1028,41 @@ impl Chaos<'_> {
                                 })
                             })
                             .last()
+                            .or_else(|| previous.as_ref().map(|previous| previous.name))
                             .map_or(ship_name, |ship_name| ship_name),
+                        category: last_ship_category(data, ship_source_index, ship)
+                            .or_else(|| previous.as_ref().and_then(|previous| previous.category)),
+                        cost: last_ship_cost(data, ship_source_index, ship)
+                            .or_else(|| previous.as_ref().and_then(|previous| previous.cost)),
                         model: ship_name,
-                        plural: self.get_copy_of_child_node(
-                            data,
-                            (ship_source_index, ship),
-                            "plural",
-                            2,
-                            ship_output_source,
-                        ),
-                        noun: self.get_copy_of_child_node(
-                            data,
-                            (ship_source_index, ship),
-                            "noun",
-                            2,
-                            ship_output_source,
-                        ),
+                        plural: self
+                            .get_copy_of_child_node(
+                                data,
+                                (ship_source_index, ship),
+                                "plural",
+                                2,
+                                ship_output_source,
+                            )
+                            .or_else(|| previous.as_ref().and_then(|previous| previous.plural)),
+                        noun: self
+                            .get_copy_of_child_node(
+                                data,
+                                (ship_source_index, ship),
+                                "noun",
+                                2,
+                                ship_output_source,
+                            )
+                            .or_else(|| previous.as_ref().and_then(|previous| previous.noun)),
                         sprite: ship_sprite,
-                        thumbnail: self.get_copy_of_child_node(
-                            data,
-                            (ship_source_index, ship),
-                            "thumbnail",
-                            2,
-                            ship_output_source,
-                        ),
+                        thumbnail: self
+                            .get_copy_of_child_node(
+                                data,
+                                (ship_source_index, ship),
+                                "thumbnail",
+                                2,
+                                ship_output_source,
+                            )
+                            .or_else(|| previous.as_ref().and_then(|previous| previous.thumbnail)),
                     },
                 );
 
@@ -579,11 +1070,14 @@ impl Chaos<'_> {
             })
     }
 
+    /// Variants that set any of their own assets get a swap of their own, falling back to their
+    /// base's assets for the rest, and are written back as `ship "Base" "Variant"`
+    /// The game copies a variant's missing assets from its base, so the rest follow their base's swap
     fn get_ship_variant_data<'a>(
         &mut self,
         data: &'a Data,
         ship_output_source: SourceIndex,
-        ship_data: &mut HashMap<&'a str, ShipData<'a>>,
+        ship_data: &mut BTreeMap<&'a str, ShipData<'a>>,
     ) {
         node_path_iter!(data; "ship")
             .filter(|(source_index, node_index)| {
@@ -610,64 +1104,60 @@ impl Chaos<'_> {
                     .and_then(|token| data.get_lexeme(ship_source_index, token))
                     .expect("The iterator should use a filter to ensure all ships have a name");
 
-                let ship_sprite = self
-                    .get_copy_of_child_node(
+                // a repeated definition of a variant patches the previous one, which in turn falls
+                // back to its base for whatever neither of them set
+                let previous = ship_data.remove(ship_variant);
+                let base = ship_data.get(&ship_model);
+
+                let fallback = |field: fn(&ShipData<'a>) -> Option<NodeIndex>| {
+                    previous
+                        .as_ref()
+                        .and_then(field)
+                        .or_else(|| base.and_then(field))
+                };
+
+                let mut copy_of_child_node = |kind: &str| {
+                    self.get_copy_of_child_node(
                         data,
                         (ship_source_index, ship),
-                        "sprite",
+                        kind,
                         2,
                         ship_output_source,
                     )
-                    .or_else(|| ship_data.get(&ship_model).and_then(|data| data.sprite));
-
-                ship_data.insert(
-                    ship_variant,
-                    ShipData {
-                        name: node_path_iter!(data => (ship_source_index, ship); "display name")
-                            .filter_map(|(_, node_index)| {
-                                data.get_tokens(node_index).and_then(|tokens| {
-                                    tokens
-                                        .get(1)
-                                        .and_then(|token| data.get_lexeme(ship_source_index, token))
-                                })
+                };
+
+                let variant_data = ShipData {
+                    name: node_path_iter!(data => (ship_source_index, ship); "display name")
+                        .filter_map(|(_, node_index)| {
+                            data.get_tokens(node_index).and_then(|tokens| {
+                                tokens
+                                    .get(1)
+                                    .and_then(|token| data.get_lexeme(ship_source_index, token))
                             })
-                            .last()
-                            .map_or(ship_model, |ship_name| ship_name),
-                        model: ship_model,
-                        plural: self
-                            .get_copy_of_child_node(
-                                data,
-                                (ship_source_index, ship),
-                                "plural",
-                                2,
-                                ship_output_source,
-                            )
-                            .or_else(|| ship_data.get(&ship_model).and_then(|data| data.plural)),
-                        noun: self
-                            .get_copy_of_child_node(
-                                data,
-                                (ship_source_index, ship),
-                                "noun",
-                                2,
-                                ship_output_source,
-                            )
-                            .or_else(|| ship_data.get(&ship_model).and_then(|data| data.noun)),
-                        sprite: ship_sprite,
-                        thumbnail: self
-                            .get_copy_of_child_node(
-                                data,
-                                (ship_source_index, ship),
-                                "thumbnail",
-                                2,
-                                ship_output_source,
-                            )
-                            .or(ship_sprite),
-                    },
-                );
+                        })
+                        .last()
+                        .or_else(|| previous.as_ref().map(|previous| previous.name))
+                        .or_else(|| base.map(|base| base.name))
+                        .map_or(ship_model, |ship_name| ship_name),
+                    category: last_ship_category(data, ship_source_index, ship)
+                        .or_else(|| previous.as_ref().and_then(|previous| previous.category))
+                        .or_else(|| base.and_then(|base| base.category)),
+                    cost: last_ship_cost(data, ship_source_index, ship)
+                        .or_else(|| previous.as_ref().and_then(|previous| previous.cost))
+                        .or_else(|| base.and_then(|base| base.cost)),
+                    model: ship_model,
+                    plural: copy_of_child_node("plural").or_else(|| fallback(|data| data.plural)),
+                    noun: copy_of_child_node("noun").or_else(|| fallback(|data| data.noun)),
+                    sprite: copy_of_child_node("sprite").or_else(|| fallback(|data| data.sprite)),
+                    thumbnail: copy_of_child_node("thumbnail")
+                        .or_else(|| fallback(|data| data.thumbnail)),
+                };
+
+                ship_data.insert(ship_variant, variant_data);
             });
     }
 
-    fn get_system_data(data: &Data) -> HashMap<&str, SystemData<'_>> {
+    fn get_system_data(data: &Data) -> BTreeMap<&str, SystemData<'_>> {
         node_path_iter!(data; "system")
             .filter(|(_, node_index)| {
                 data.get_tokens(*node_index)
@@ -675,7 +1165,7 @@ impl Chaos<'_> {
                     == 2
             })
             .fold(
-                HashMap::new(),
+                BTreeMap::new(),
                 |mut accum, (system_source_index, system)| {
                     let system_name = data
                         .get_tokens(system)
@@ -707,7 +1197,36 @@ impl Chaos<'_> {
             )
     }
 
-    fn get_planet_data(data: &Data) -> HashMap<&str, PlanetData<'_>> {
+    /// The landscape of the last definition of every planet that sets one
+    fn get_landscape_data(data: &Data) -> BTreeMap<&str, &str> {
+        let mut landscape_data = BTreeMap::new();
+
+        for (source_index, planet) in node_path_iter!(data; "planet") {
+            let Some(planet_name) = data
+                .get_tokens(planet)
+                .filter(|tokens| tokens.len() == 2)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+            else {
+                continue;
+            };
+
+            if let Some(landscape) = node_path_iter!(data => (source_index, planet); "landscape")
+                .filter_map(|(_, landscape)| {
+                    data.get_tokens(landscape)
+                        .and_then(|tokens| tokens.get(1))
+                        .and_then(|token| data.get_lexeme(source_index, token))
+                })
+                .last()
+            {
+                landscape_data.insert(planet_name, landscape);
+            }
+        }
+
+        landscape_data
+    }
+
+    fn get_planet_data(data: &Data) -> BTreeMap<&str, PlanetData<'_>> {
         node_path_iter!(data; "planet")
             .filter(|(_, node_index)| {
                 data.get_tokens(*node_index)
@@ -715,7 +1234,7 @@ impl Chaos<'_> {
                     == 2
             })
             .fold(
-                HashMap::new(),
+                BTreeMap::new(),
                 |mut accum, (planet_source_index, planet)| {
                     let planet_name = data
                         .get_tokens(planet)
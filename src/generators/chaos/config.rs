@@ -1,12 +1,24 @@
+// the defaults are the same as `config/chaos/default.txt`
 crate::macros::wasm_newtype! {
     in main =>
     #[derive(Debug)]
-    pub ChaosConfig;
-    seed: u64,
-    outfits: bool,
-    ships: bool,
-    systems: bool,
-    planets: bool,
+    pub ChaosConfig, ChaosBuilder;
+    seed: u64 = 0,
+    outfit_seed: u64 = 0,
+    ship_seed: u64 = 0,
+    outfits: bool = true,
+    ships: bool = true,
+    systems: bool = true,
+    planets: bool = true,
+    landscapes: bool = false,
+    swap_names: bool = true,
+    swap_assets: bool = true,
+    by_category: bool = false,
+    weight_by_cost: bool = false,
+    no_self_swap: bool = false,
+    preserve_outfit_space: bool = false,
+    exclude: Vec<String> = vec![],
+    requires: Vec<String> = vec![],
 }
 
 pub mod from_file {
@@ -19,12 +31,23 @@ pub mod from_file {
     #[must_use]
     pub fn parse(source: &str) -> Option<ChaosConfig> {
         config::parse_config!(
-            source => ChaosConfig;
+            source => ChaosConfig as builder;
             seed => { int of u64 => seed }
+            outfit_seed => { int of u64 => outfit_seed }
+            ship_seed => { int of u64 => ship_seed }
             outfits => { bool => *outfits }
             ships => { bool => *ships }
             systems => { bool => *systems }
             planets => { bool => *planets }
+            landscapes => { bool => *landscapes }
+            swap_names => { bool => *swap_names }
+            swap_assets => { bool => *swap_assets }
+            by_category => { bool => *by_category }
+            weight_by_cost => { bool => *weight_by_cost }
+            no_self_swap => { bool => *no_self_swap }
+            preserve_outfit_space => { bool => *preserve_outfit_space }
+            exclude => { list => config::string_list(exclude)? }
+            requires => { list => config::string_list(requires)? }
         )
     }
 }
@@ -55,7 +78,7 @@ pub mod page {
                 .with_element(
                     HtmlElement::new("p")
                         .with_text("This plugin can shuffle the sprites, thumbnails, and names of every ship and outfit.<br/>")
-                        .with_text("It can also shuffle the names of every system and planet.<br/>")
+                        .with_text("It can also shuffle the names of every system and planet, and every planet's landscape.<br/>")
                         .with_text("Everything will play the same, mostly, but the hitboxes for ships will be different and you won't know what anything is at a glance")
                 )
                 .with_element(
@@ -69,6 +92,7 @@ pub mod page {
                 )
     }
 
+    #[allow(clippy::too_many_lines)]
     fn chaos_fieldset() -> HtmlElement {
         let settings = config::from_file::parse(DEFAULT_CONFIG_FILE);
         let settings = settings.as_ref();
@@ -86,6 +110,38 @@ pub mod page {
                     input
                 }
             }))
+            .with_element(html::page::labeled(
+                "chaos-outfit-seed",
+                "",
+                "outfit seed (0 to follow the seed):",
+                {
+                    let input = HtmlElement::new("input")
+                        .with_attribute("type", "number")
+                        .required();
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", *settings.outfit_seed())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-ship-seed",
+                "",
+                "ship seed (0 to follow the seed):",
+                {
+                    let input = HtmlElement::new("input")
+                        .with_attribute("type", "number")
+                        .required();
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", *settings.ship_seed())
+                    } else {
+                        input
+                    }
+                },
+            ))
             .with_element(html::page::labeled(
                 "chaos-outfits",
                 "",
@@ -145,5 +201,145 @@ pub mod page {
                     }
                 },
             ))
+            .with_element(html::page::labeled(
+                "chaos-landscapes",
+                "",
+                "shuffle planet landscapes:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.landscapes()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-swap-names",
+                "",
+                "shuffle ship and outfit names:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.swap_names()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-swap-assets",
+                "",
+                "shuffle ship and outfit images:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.swap_assets()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-by-category",
+                "",
+                "only swap ships and outfits within the same category:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.by_category()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-weight-by-cost",
+                "",
+                "only swap ships and outfits of a similar cost:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.weight_by_cost()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-no-self-swap",
+                "",
+                "never leave a ship or outfit as itself:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.no_self_swap()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-preserve-outfit-space",
+                "",
+                "keep every outfit's own mass and outfit space:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.preserve_outfit_space()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-exclude",
+                "",
+                "outfits and ships to leave alone (comma separated):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", settings.exclude().join(", ").as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "chaos-requires",
+                "",
+                "required plugins (comma separated):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", settings.requires().join(", ").as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
     }
 }
@@ -19,9 +19,21 @@ const PLUGIN_VERSION: &str = "0.1.0";
 fn find_named_objects<'a>(
     data: &'a Data,
     source_index: SourceIndex,
-    node_index: NodeIndex,
+    (node_index, depth): (NodeIndex, u64),
     names: &mut Vec<&'a str>,
 ) {
+    if depth >= generators::MAX_OBJECT_DEPTH {
+        generators::log(
+            format!(
+                "Objects are nested more than {} deep, so the deeper ones were ignored",
+                generators::MAX_OBJECT_DEPTH
+            )
+            .as_str(),
+        );
+
+        return;
+    }
+
     for (_, object) in node_path_iter!(data => (source_index, node_index); "object") {
         if let Some(tokens) = data.get_tokens(object)
             && tokens.len() >= 2
@@ -30,22 +42,55 @@ fn find_named_objects<'a>(
             names.push(name);
         }
 
-        find_named_objects(data, source_index, object, names);
+        find_named_objects(data, source_index, (object, depth + 1), names);
     }
 }
 
 #[allow(clippy::missing_errors_doc)]
 pub fn process_data(data_folder: &DataFolder) -> Result<Vec<u8>, Box<dyn Error>> {
-    let data = data_folder.data();
+    process_parsed(data_folder.data())
+}
 
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(data: &Data) -> Result<Vec<u8>, Box<dyn Error>> {
     let mut output = vec![];
 
+    let mut archive = Zip::new(&mut output);
+
+    write_plugin(data, &mut archive, true)?;
+
+    archive.finish()?;
+
+    Ok(output)
+}
+
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt() -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        PLUGIN_NAME,
+        PLUGIN_DESCRIPTION.lines().collect::<Vec<_>>().as_slice(),
+        PLUGIN_VERSION,
+        &[],
+    )
+}
+
+/// Writes the plugin into an archive that may be shared with other generators
+/// Without `plugin_txt`, whoever owns the archive is expected to write the `plugin.txt`
+pub(crate) fn write_plugin(
+    data: &Data,
+    archive: &mut Zip,
+    plugin_txt: bool,
+) -> Result<(), Box<dyn Error>> {
     let mut generator = FullMap {
-        archive: Zip::new(&mut output),
+        archive,
         output_data: Data::default(),
     };
 
-    generator.description()?;
+    if plugin_txt {
+        generator.description()?;
+    }
 
     generator.archive.write_dir("data/")?;
 
@@ -53,24 +98,22 @@ pub fn process_data(data_folder: &DataFolder) -> Result<Vec<u8>, Box<dyn Error>>
 
     generator.main_event(data)?;
 
-    generator.archive.finish()?;
-
-    Ok(output)
+    Ok(())
 }
 
-struct FullMap<'a> {
-    archive: Zip<'a>,
+struct FullMap<'a, 'b> {
+    archive: &'a mut Zip<'b>,
     output_data: Data,
 }
 
-impl FullMap<'_> {
+impl FullMap<'_, '_> {
     fn zip_root_nodes<P: Into<PathBuf>>(
         &mut self,
         path: P,
         from: usize,
     ) -> Result<(), Box<dyn Error>> {
         generators::zip_root_nodes(
-            &mut self.archive,
+            self.archive,
             path,
             &self.output_data,
             &self.output_data.root_nodes()[from..],
@@ -78,47 +121,9 @@ impl FullMap<'_> {
     }
 
     fn description(&mut self) -> Result<(), Box<dyn Error>> {
-        let output_root_node_count = self.output_data.root_nodes().len();
-        let plugin_txt_source = self.output_data.insert_source(String::new());
-
-        let plugin_name = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "name", PLUGIN_NAME ;
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_name);
-
-        for about in PLUGIN_DESCRIPTION.lines().map(str::trim) {
-            let plugin_about = tree_from_tokens!(
-                &mut self.output_data; plugin_txt_source =>
-                : "about", about ;
-            );
-
-            self.output_data
-                .push_root_node(plugin_txt_source, plugin_about);
-        }
-
-        let plugin_version = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "version", PLUGIN_VERSION ;
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_version);
-
-        let dependencies = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "dependencies" ;
-            {
-                : "game version", crate::GAME_VERSION ;
-            }
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, dependencies);
+        let plugin_txt = preview_plugin_txt()?;
 
-        self.zip_root_nodes("plugin.txt", output_root_node_count)
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
     }
 
     fn main_mission(&mut self) -> Result<(), Box<dyn Error>> {
@@ -171,7 +176,7 @@ impl FullMap<'_> {
                     .expect("The iterator should have a filter applied such that only nodes with two or more tokens are allowed")
             );
 
-            find_named_objects(data, source_index, system, &mut planet_names);
+            find_named_objects(data, source_index, (system, 0), &mut planet_names);
         }
 
         system_names.sort_unstable();
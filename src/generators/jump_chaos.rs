@@ -0,0 +1,186 @@
+pub mod config;
+
+use crate::{
+    generators,
+    wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex},
+    zippy::Zip,
+};
+
+use endless_sky_rw::{Data, DataFolder, Node, Span, Token, node_path_iter, tree_from_tokens};
+
+use std::{collections::BTreeMap, error::Error, path::PathBuf};
+
+const PLUGIN_NAME: &str = "Jump Chaos";
+
+const PLUGIN_VERSION: &str = "0.1.0";
+
+/// The jump range the game uses for systems that don't set their own
+const DEFAULT_JUMP_RANGE: &str = "100";
+
+/// The name of both the event with the shuffled jump ranges and the mission that applies it
+const EVENT_NAME: &str = "Jump Chaos: Shuffle Jump Ranges";
+
+#[allow(clippy::missing_errors_doc)]
+pub fn process_data(
+    data_folder: &DataFolder,
+    settings: &config::JumpChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    process_parsed(data_folder.data(), settings)
+}
+
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(
+    data: &Data,
+    settings: &config::JumpChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut rng = XoShiRo256SS::new(*settings.seed());
+    let mut output = vec![];
+
+    let mut generator = JumpChaos {
+        archive: Zip::new(&mut output),
+        output_data: Data::default(),
+    };
+
+    generator.description(settings)?;
+
+    generator.archive.write_dir("data/")?;
+
+    generator.jump_ranges(data, &mut rng)?;
+
+    generator.archive.finish()?;
+
+    Ok(output)
+}
+
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt(settings: &config::JumpChaosConfig) -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        PLUGIN_NAME,
+        about_lines(settings).as_slice(),
+        PLUGIN_VERSION,
+        settings.requires(),
+    )
+}
+
+/// The `about` lines of the `plugin.txt`
+#[must_use]
+pub fn about_lines(settings: &config::JumpChaosConfig) -> Vec<String> {
+    vec![
+        "Shuffles the jump range of every system, without moving any of them.".to_string(),
+        format!("PRNG seed: {}", settings.seed()),
+    ]
+}
+
+struct JumpChaos<'a> {
+    archive: Zip<'a>,
+    output_data: Data,
+}
+
+impl JumpChaos<'_> {
+    fn zip_root_nodes<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        from: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        generators::zip_root_nodes(
+            &mut self.archive,
+            path,
+            &self.output_data,
+            &self.output_data.root_nodes()[from..],
+        )
+    }
+
+    fn description(&mut self, settings: &config::JumpChaosConfig) -> Result<(), Box<dyn Error>> {
+        let plugin_txt = preview_plugin_txt(settings)?;
+
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
+    }
+
+    /// The new jump ranges are applied by an event, like any other change to the map once a game is
+    /// going, instead of redefining the systems themselves
+    fn jump_ranges(&mut self, data: &Data, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
+        let output_root_node_count = self.output_data.root_nodes().len();
+
+        let jump_range_source = self.output_data.insert_source(String::new());
+
+        let jump_ranges = Self::get_jump_ranges(data);
+
+        let system_names = jump_ranges.keys().collect::<Vec<_>>();
+
+        let event = tree_from_tokens!(
+            &mut self.output_data; jump_range_source =>
+            : "event", EVENT_NAME ;
+        );
+
+        for (original, swap) in system_names.shuffled_pairs_with_rng(rng) {
+            let jump_range = jump_ranges.get(**swap).expect("Jump range data must exist");
+
+            let system = tree_from_tokens!(
+                &mut self.output_data; jump_range_source =>
+                : "system", original ;
+                {
+                    : "jump range", jump_range ;
+                }
+            );
+
+            self.output_data.push_child(event, system);
+        }
+
+        self.output_data.push_root_node(jump_range_source, event);
+
+        let mission = tree_from_tokens!(
+            &mut self.output_data; jump_range_source =>
+            : "mission", EVENT_NAME ;
+            {
+                : "invisible" ;
+                : "non-blocking" ;
+                : "landing" ;
+                : "on", "offer" ;
+                {
+                    : "event", EVENT_NAME, "0" ;
+                }
+            }
+        );
+
+        self.output_data.push_root_node(jump_range_source, mission);
+
+        self.zip_root_nodes("data/jump_ranges.txt", output_root_node_count)
+    }
+
+    /// Every system's jump range, the last definition winning, and the game's default when a system never sets one
+    fn get_jump_ranges(data: &Data) -> BTreeMap<&str, &str> {
+        let mut jump_ranges = BTreeMap::new();
+
+        for (source_index, system) in node_path_iter!(data; "system") {
+            let Some(system_name) = data
+                .get_tokens(system)
+                .filter(|tokens| tokens.len() == 2)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+            else {
+                continue;
+            };
+
+            let jump_range = node_path_iter!(data => (source_index, system); "jump range")
+                .filter_map(|(_, node_index)| {
+                    data.get_tokens(node_index)
+                        .and_then(|tokens| tokens.get(1))
+                        .and_then(|token| data.get_lexeme(source_index, token))
+                })
+                .last();
+
+            match jump_range {
+                Some(jump_range) => {
+                    jump_ranges.insert(system_name, jump_range);
+                }
+                None => {
+                    jump_ranges.entry(system_name).or_insert(DEFAULT_JUMP_RANGE);
+                }
+            }
+        }
+
+        jump_ranges
+    }
+}
@@ -0,0 +1,6 @@
+crate::macros::seed_only_config! {
+    jump_chaos => JumpChaosConfig;
+    title: "Jump Chaos", anchor: "Jump_Chaos", class: "jump-chaos";
+    "This plugin shuffles the jump range of every system without moving any of them.<br/>",
+    "Systems that never set a jump range put the game's default into the pool, so some quiet systems may become long-range hubs.",
+}
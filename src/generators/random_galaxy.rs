@@ -10,7 +10,7 @@ use self::{
 
 use crate::{
     generators,
-    wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex, weighted_choice::WeightedChoice},
+    wandom::{self, XoShiRo256SS, shuffle_index::ShuffleIndex, weighted_choice::WeightedChoice},
     zippy::Zip,
 };
 
@@ -28,6 +28,17 @@ const PLUGIN_DESCRIPTION: &str = "\
 
 const PLUGIN_VERSION: &str = "0.1.0";
 
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt(settings: &config::RandomGalaxyConfig) -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        PLUGIN_NAME,
+        PLUGIN_DESCRIPTION.lines().collect::<Vec<_>>().as_slice(),
+        PLUGIN_VERSION,
+        settings.requires(),
+    )
+}
+
 #[allow(clippy::missing_errors_doc)]
 pub fn process_data(
     data_folder: &DataFolder,
@@ -102,47 +113,9 @@ impl RandomGalaxy<'_> {
     }
 
     fn description(&mut self) -> Result<(), Box<dyn Error>> {
-        let output_root_node_count = self.output_data.root_nodes().len();
-        let plugin_txt_source = self.output_data.insert_source(String::new());
-
-        let plugin_name = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "name", PLUGIN_NAME ;
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_name);
-
-        for about in PLUGIN_DESCRIPTION.lines().map(str::trim) {
-            let plugin_about = tree_from_tokens!(
-                &mut self.output_data; plugin_txt_source =>
-                : "about", about ;
-            );
-
-            self.output_data
-                .push_root_node(plugin_txt_source, plugin_about);
-        }
-
-        let plugin_version = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "version", PLUGIN_VERSION ;
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_version);
-
-        let dependencies = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "dependencies" ;
-            {
-                : "game version", crate::GAME_VERSION ;
-            }
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, dependencies);
+        let plugin_txt = preview_plugin_txt(&self.settings)?;
 
-        self.zip_root_nodes("plugin.txt", output_root_node_count)
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
     }
 
     fn galaxy(&mut self, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
@@ -197,11 +170,11 @@ impl RandomGalaxy<'_> {
         output_root_node_count: usize,
         galaxy_source: SourceIndex,
     ) -> Result<(), Box<dyn Error>> {
-        let mut system_name_rng = XoShiRo256SS::new(rng.step());
-        let mut system_placement_rng = XoShiRo256SS::new(rng.step());
+        let mut system_name_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
+        let mut system_placement_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
 
-        let mut star_rng = XoShiRo256SS::new(rng.step());
-        let mut planet_rng = XoShiRo256SS::new(rng.step());
+        let mut star_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
+        let mut planet_rng = XoShiRo256SS::with_warmup(rng.step(), wandom::WARMUP_STEPS);
 
         let galaxy = named_systems::plot(
             (&mut system_name_rng, &mut system_placement_rng),
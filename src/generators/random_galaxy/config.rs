@@ -13,6 +13,7 @@ crate::macros::wasm_newtype! {
     },
     seed: u64,
     reveal_all: bool,
+    requires: Vec<String>,
     clusters: Vec<random_galaxy::config::Cluster>,
     system_name_sources: random_galaxy::config::SystemNameSources,
     sprites: random_galaxy::config::Sprites,
@@ -218,6 +219,7 @@ pub mod from_file {
             name => { string => name.to_string() }
             seed => { int of u64 => seed }
             reveal_all => { bool => *reveal_all }
+            requires => { list => config::string_list(requires)? }
             clusters => { list where !clusters.is_empty() => {
                 clusters.iter().filter_map(|cluster| {
                     self::cluster(cluster, &system_names, &stars, &planets)
@@ -985,6 +987,20 @@ pub mod page {
                     }
                 },
             ))
+            .with_element(html::page::labeled(
+                "random-galaxy-requires",
+                "",
+                "required plugins (comma separated):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", settings.requires().join(", ").as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
             .with_element(random_galaxy_clusters_fieldset(settings))
             .with_element(random_galaxy_system_name_sources_fieldset(settings))
             .with_element(random_galaxy_star_groups_fieldset(settings))
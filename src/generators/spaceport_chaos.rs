@@ -0,0 +1,246 @@
+pub mod config;
+
+use crate::{
+    generators,
+    wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex},
+    zippy::Zip,
+};
+
+use endless_sky_rw::{
+    Data, DataFolder, Node, NodeIndex, SourceIndex, Span, Spanned, Token, node_path_iter,
+    tree_from_tokens,
+};
+
+use std::{collections::BTreeMap, error::Error, path::PathBuf};
+
+const PLUGIN_NAME: &str = "Spaceport Chaos";
+
+const PLUGIN_VERSION: &str = "0.1.0";
+
+/// Every `object` named after a planet, in the order they appear, moons included
+fn find_named_objects(
+    data: &Data,
+    source_index: SourceIndex,
+    (node_index, depth): (NodeIndex, u64),
+    named_objects: &mut Vec<NodeIndex>,
+) {
+    if depth >= generators::MAX_OBJECT_DEPTH {
+        generators::log(
+            format!(
+                "Objects are nested more than {} deep, so the deeper ones were ignored",
+                generators::MAX_OBJECT_DEPTH
+            )
+            .as_str(),
+        );
+
+        return;
+    }
+
+    for (_, object) in node_path_iter!(data => (source_index, node_index); "object") {
+        if data
+            .get_tokens(object)
+            .is_some_and(|tokens| tokens.len() >= 2)
+        {
+            named_objects.push(object);
+        }
+
+        find_named_objects(data, source_index, (object, depth + 1), named_objects);
+    }
+}
+
+#[allow(clippy::missing_errors_doc)]
+pub fn process_data(
+    data_folder: &DataFolder,
+    settings: &config::SpaceportChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    process_parsed(data_folder.data(), settings)
+}
+
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(
+    data: &Data,
+    settings: &config::SpaceportChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut rng = XoShiRo256SS::new(*settings.seed());
+    let mut output = vec![];
+
+    let mut generator = SpaceportChaos {
+        archive: Zip::new(&mut output),
+        output_data: Data::default(),
+    };
+
+    generator.description(settings)?;
+
+    generator.archive.write_dir("data/")?;
+
+    generator.systems(data, &mut rng)?;
+
+    generator.archive.finish()?;
+
+    Ok(output)
+}
+
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt(
+    settings: &config::SpaceportChaosConfig,
+) -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        PLUGIN_NAME,
+        about_lines(settings).as_slice(),
+        PLUGIN_VERSION,
+        settings.requires(),
+    )
+}
+
+/// The `about` lines of the `plugin.txt`
+#[must_use]
+pub fn about_lines(settings: &config::SpaceportChaosConfig) -> Vec<String> {
+    vec![
+        "Shuffles which object every planet sits on, within each of their systems.".to_string(),
+        format!("PRNG seed: {}", settings.seed()),
+    ]
+}
+
+struct SpaceportChaos<'a> {
+    archive: Zip<'a>,
+    output_data: Data,
+}
+
+impl SpaceportChaos<'_> {
+    fn zip_root_nodes<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        from: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        generators::zip_root_nodes(
+            &mut self.archive,
+            path,
+            &self.output_data,
+            &self.output_data.root_nodes()[from..],
+        )
+    }
+
+    fn description(
+        &mut self,
+        settings: &config::SpaceportChaosConfig,
+    ) -> Result<(), Box<dyn Error>> {
+        let plugin_txt = preview_plugin_txt(settings)?;
+
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
+    }
+
+    fn systems(&mut self, data: &Data, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
+        let output_root_node_count = self.output_data.root_nodes().len();
+
+        let system_output_source = self.output_data.insert_source(String::new());
+
+        for (system_name, (source_index, system)) in Self::get_system_data(data) {
+            let mut named_objects = vec![];
+
+            find_named_objects(data, source_index, (system, 0), &mut named_objects);
+
+            // with a single planet there is nowhere else for it to go
+            if named_objects.len() < 2 {
+                continue;
+            }
+
+            let planet_names = named_objects
+                .iter()
+                .filter_map(|&object| {
+                    data.get_tokens(object)
+                        .and_then(|tokens| tokens.get(1))
+                        .and_then(|token| data.get_lexeme(source_index, token))
+                })
+                .collect::<Vec<_>>();
+
+            let planet_swaps = planet_names
+                .as_slice()
+                .shuffled_pairs_with_rng(rng)
+                .into_iter()
+                .map(|(_, swap)| *swap)
+                .collect::<Vec<_>>();
+
+            let output_system = tree_from_tokens!(
+                &mut self.output_data; system_output_source =>
+                : "system", system_name ;
+            );
+
+            // redefining a system's objects replaces all of them, so every object is copied, not
+            // just the named ones, to keep the unlandable ones in place
+            for (_, object) in node_path_iter!(data => (source_index, system); "object") {
+                if let Some(output_object) = generators::copy_node(
+                    data,
+                    (source_index, object),
+                    &mut self.output_data,
+                    system_output_source,
+                    [].as_slice(),
+                ) {
+                    self.output_data.push_child(output_system, output_object);
+                }
+            }
+
+            let mut output_named_objects = vec![];
+
+            find_named_objects(
+                &self.output_data,
+                system_output_source,
+                (output_system, 0),
+                &mut output_named_objects,
+            );
+
+            for (output_object, planet_name) in output_named_objects.into_iter().zip(planet_swaps) {
+                self.rename_object(system_output_source, output_object, planet_name);
+            }
+
+            self.output_data
+                .push_root_node(system_output_source, output_system);
+        }
+
+        self.zip_root_nodes("data/spaceports.txt", output_root_node_count)
+    }
+
+    fn rename_object(
+        &mut self,
+        output_source: SourceIndex,
+        output_object: NodeIndex,
+        planet_name: &str,
+    ) {
+        let Some((start, end)) = self.output_data.push_source(output_source, planet_name) else {
+            return;
+        };
+
+        if let Some(Node::Some { tokens } | Node::Parent { tokens, .. }) =
+            self.output_data.get_mut_node(output_object)
+            && let Some(name) = tokens.get_mut(1)
+        {
+            *name = Spanned::new(Token::Symbol, Span::new(output_source.index(), start, end));
+        }
+    }
+
+    /// The last definition of every system that places any objects, since that's the one the game keeps
+    fn get_system_data(data: &Data) -> BTreeMap<&str, (SourceIndex, NodeIndex)> {
+        let mut system_data = BTreeMap::new();
+
+        for (source_index, system) in node_path_iter!(data; "system") {
+            let Some(system_name) = data
+                .get_tokens(system)
+                .filter(|tokens| tokens.len() == 2)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+            else {
+                continue;
+            };
+
+            if node_path_iter!(data => (source_index, system); "object")
+                .next()
+                .is_some()
+            {
+                system_data.insert(system_name, (source_index, system));
+            }
+        }
+
+        system_data
+    }
+}
@@ -0,0 +1,6 @@
+crate::macros::seed_only_config! {
+    spaceport_chaos => SpaceportChaosConfig;
+    title: "Spaceport Chaos", anchor: "Spaceport_Chaos", class: "spaceport-chaos";
+    "This plugin shuffles which object in a system each planet sits on, without moving any systems.<br/>",
+    "Only systems with at least two landable objects are touched, so your usual spaceport may now be the moon next door.",
+}
@@ -0,0 +1,239 @@
+pub mod config;
+
+use crate::{
+    generators,
+    wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex},
+    zippy::Zip,
+};
+
+use endless_sky_rw::{
+    Data, DataFolder, Node, NodeIndex, SourceIndex, Span, Spanned, Token, node_path_iter,
+    tree_from_tokens,
+};
+
+use std::{collections::BTreeMap, error::Error, path::PathBuf};
+
+const PLUGIN_NAME: &str = "Star Chaos";
+
+const PLUGIN_VERSION: &str = "0.1.0";
+
+#[allow(clippy::missing_errors_doc)]
+pub fn process_data(
+    data_folder: &DataFolder,
+    settings: &config::StarChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    process_parsed(data_folder.data(), settings)
+}
+
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(
+    data: &Data,
+    settings: &config::StarChaosConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut rng = XoShiRo256SS::new(*settings.seed());
+    let mut output = vec![];
+
+    let mut generator = StarChaos {
+        archive: Zip::new(&mut output),
+        output_data: Data::default(),
+    };
+
+    generator.description(settings)?;
+
+    generator.archive.write_dir("data/")?;
+
+    generator.stars(data, &mut rng)?;
+
+    generator.archive.finish()?;
+
+    Ok(output)
+}
+
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt(settings: &config::StarChaosConfig) -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        PLUGIN_NAME,
+        about_lines(settings).as_slice(),
+        PLUGIN_VERSION,
+        settings.requires(),
+    )
+}
+
+/// The `about` lines of the `plugin.txt`
+#[must_use]
+pub fn about_lines(settings: &config::StarChaosConfig) -> Vec<String> {
+    vec![
+        "Shuffles the look of every system's star, without changing anything you can fly to."
+            .to_string(),
+        format!("PRNG seed: {}", settings.seed()),
+    ]
+}
+
+struct StarChaos<'a> {
+    archive: Zip<'a>,
+    output_data: Data,
+}
+
+struct StarData<'a> {
+    source_index: SourceIndex,
+    system: NodeIndex,
+    /// Which of the system's top-level objects is the star
+    star_position: usize,
+    sprite: &'a str,
+}
+
+impl StarChaos<'_> {
+    fn zip_root_nodes<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        from: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        generators::zip_root_nodes(
+            &mut self.archive,
+            path,
+            &self.output_data,
+            &self.output_data.root_nodes()[from..],
+        )
+    }
+
+    fn description(&mut self, settings: &config::StarChaosConfig) -> Result<(), Box<dyn Error>> {
+        let plugin_txt = preview_plugin_txt(settings)?;
+
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
+    }
+
+    fn stars(&mut self, data: &Data, rng: &mut XoShiRo256SS) -> Result<(), Box<dyn Error>> {
+        let output_root_node_count = self.output_data.root_nodes().len();
+
+        let system_output_source = self.output_data.insert_source(String::new());
+
+        let star_data = Self::get_star_data(data);
+
+        let system_names = star_data.keys().collect::<Vec<_>>();
+
+        for (original, swap) in system_names.shuffled_pairs_with_rng(rng) {
+            let original_data = star_data.get(**original).expect("Star data must exist");
+            let swapped_data = star_data.get(**swap).expect("Star data must exist");
+
+            if original_data.sprite == swapped_data.sprite {
+                continue;
+            }
+
+            let output_system = tree_from_tokens!(
+                &mut self.output_data; system_output_source =>
+                : "system", original ;
+            );
+
+            // redefining a system's objects replaces all of them, so the planets are copied too,
+            // and only the star's sprite is changed
+            for (position, (_, object)) in node_path_iter!(
+                data => (original_data.source_index, original_data.system); "object"
+            )
+            .enumerate()
+            {
+                let Some(output_object) = generators::copy_node(
+                    data,
+                    (original_data.source_index, object),
+                    &mut self.output_data,
+                    system_output_source,
+                    [].as_slice(),
+                ) else {
+                    continue;
+                };
+
+                if position == original_data.star_position {
+                    self.replace_sprite(system_output_source, output_object, swapped_data.sprite);
+                }
+
+                self.output_data.push_child(output_system, output_object);
+            }
+
+            self.output_data
+                .push_root_node(system_output_source, output_system);
+        }
+
+        self.zip_root_nodes("data/stars.txt", output_root_node_count)
+    }
+
+    fn replace_sprite(
+        &mut self,
+        output_source: SourceIndex,
+        output_object: NodeIndex,
+        sprite: &str,
+    ) {
+        let Some((_, output_sprite)) =
+            node_path_iter!(&self.output_data => (output_source, output_object); "sprite").next()
+        else {
+            return;
+        };
+
+        let Some((start, end)) = self.output_data.push_source(output_source, sprite) else {
+            return;
+        };
+
+        if let Some(Node::Some { tokens } | Node::Parent { tokens, .. }) =
+            self.output_data.get_mut_node(output_sprite)
+            && let Some(sprite) = tokens.get_mut(1)
+        {
+            *sprite = Spanned::new(Token::Symbol, Span::new(output_source.index(), start, end));
+        }
+    }
+
+    /// The star of the last definition of every system that places any objects
+    /// The star is the first unnamed top-level object with a `star/` sprite, so systems without one
+    /// (like rogue planets in deep space) are left out
+    fn get_star_data(data: &Data) -> BTreeMap<&str, StarData<'_>> {
+        let mut star_data = BTreeMap::new();
+
+        for (source_index, system) in node_path_iter!(data; "system") {
+            let Some(system_name) = data
+                .get_tokens(system)
+                .filter(|tokens| tokens.len() == 2)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+            else {
+                continue;
+            };
+
+            let mut objects = node_path_iter!(data => (source_index, system); "object").peekable();
+
+            // a later definition with objects replaces all of the earlier ones, star included
+            if objects.peek().is_none() {
+                continue;
+            }
+
+            let star = objects
+                .enumerate()
+                .find_map(|(star_position, (_, object))| {
+                    if data.get_tokens(object)?.len() != 1 {
+                        return None;
+                    }
+
+                    node_path_iter!(data => (source_index, object); "sprite")
+                        .filter_map(|(_, sprite)| {
+                            data.get_tokens(sprite)
+                                .and_then(|tokens| tokens.get(1))
+                                .and_then(|token| data.get_lexeme(source_index, token))
+                        })
+                        .next()
+                        .filter(|sprite| sprite.starts_with("star/"))
+                        .map(|sprite| StarData {
+                            source_index,
+                            system,
+                            star_position,
+                            sprite,
+                        })
+                });
+
+            if let Some(star) = star {
+                star_data.insert(system_name, star);
+            } else {
+                star_data.remove(system_name);
+            }
+        }
+
+        star_data
+    }
+}
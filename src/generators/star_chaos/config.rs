@@ -0,0 +1,6 @@
+crate::macros::seed_only_config! {
+    star_chaos => StarChaosConfig;
+    title: "Star Chaos", anchor: "Star_Chaos", class: "star-chaos";
+    "This plugin shuffles the look of every system's star, and nothing else.<br/>",
+    "Positions, links, and planets all stay where they are, so only the sky changes.",
+}
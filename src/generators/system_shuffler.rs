@@ -1,9 +1,11 @@
 pub mod config;
+pub mod geometry;
+pub mod swaps;
 
 use crate::{
     generators,
-    wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex},
-    zippy::Zip,
+    wandom::XoShiRo256SS,
+    zippy::{self, Zip},
 };
 
 use endless_sky_rw::{
@@ -13,8 +15,9 @@ use endless_sky_rw::{
 
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     error::Error,
+    io,
     path::PathBuf,
 };
 
@@ -22,17 +25,121 @@ const PLUGIN_NAME: &str = "System Shuffler";
 
 const PLUGIN_VERSION: &str = "0.4.1";
 
-const INSTALLED: &str = "System Shuffler: Installed";
-const CURRENT_PRESET: &str = "System Shuffler: Current Preset";
-const LAST_SHUFFLE_DAY: &str = "System Shuffler: Last Shuffle Day";
+/// How many branches the conversations that pick a preset can have before they get slow for the
+/// game to load and step through
+/// There are a few per preset, times every uploaded event that touches shuffled systems
+const MAX_CONDITIONAL_BRANCHES: usize = 10_000;
 
-const RESTORE_PREFIX: &str = "System Shuffler: Restore Preset";
-const ACTIVATE_PREFIX: &str = "System Shuffler: Activate Preset";
-
-struct SystemShuffler<'a> {
-    archive: Zip<'a>,
+struct SystemShuffler<'a, 'b> {
+    archive: &'a mut Zip<'b>,
     output_data: Data,
     settings: config::SystemShufflerConfig,
+    names: Names,
+    event_scopes: Vec<String>,
+    /// Problems that didn't stop the generation, kept for the report
+    warnings: Vec<String>,
+    /// Every system's own music, only filled in when shuffled systems take on their new region's music
+    system_music: HashMap<String, String>,
+    /// The music most of the systems in each system's region play
+    neighborhood_music: HashMap<String, String>,
+    /// Only set outside of `GeometryMode::Swap`, where presets move the galaxy instead of swapping
+    geometry: Option<geometry::Geometry>,
+    /// How the preset being written moves the galaxy, if `geometry` is set
+    preset_transform: geometry::Transform,
+}
+
+/// A machine-readable summary of one generation, for tools that want more than the zip
+pub struct Report {
+    seed: u64,
+    /// The swaps of every preset in order, leaving out systems that stay where they are
+    preset_swaps: Vec<BTreeMap<String, String>>,
+    system_count: usize,
+    /// How many shuffled root nodes there are of each kind (`system`, `wormhole`, ...)
+    kind_counts: BTreeMap<String, usize>,
+    /// The uploaded events that touch shuffled systems, and so get their own restore and activate copies
+    affected_events: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl Report {
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let string_list = |strings: &[String]| {
+            strings
+                .iter()
+                .map(|string| generators::json_string(string))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        format!(
+            "{{\"seed\":{},\"preset_swaps\":[{}],\"system_count\":{},\"kind_counts\":{{{}}},\"affected_events\":[{}],\"warnings\":[{}]}}",
+            // seeds go past the 53 bits a JS number can hold exactly
+            generators::json_string(self.seed.to_string().as_str()),
+            self.preset_swaps
+                .iter()
+                .map(|system_swaps| {
+                    format!(
+                        "{{{}}}",
+                        system_swaps
+                            .iter()
+                            .map(|(original, swap)| format!(
+                                "{}:{}",
+                                generators::json_string(original),
+                                generators::json_string(swap)
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            self.system_count,
+            self.kind_counts
+                .iter()
+                .map(|(kind, count)| format!("{}:{count}", generators::json_string(kind)))
+                .collect::<Vec<_>>()
+                .join(","),
+            string_list(self.affected_events.as_slice()),
+            string_list(self.warnings.as_slice()),
+        )
+    }
+}
+
+struct Names {
+    installed: String,
+    current_preset: String,
+    last_shuffle_day: String,
+    restore_prefix: String,
+    activate_prefix: String,
+    select_preset: String,
+    restore_universe: String,
+    manual_shuffle: String,
+    initial_backpatch: String,
+    applied_preset: String,
+    target_preset: String,
+    tracks_applied_preset: String,
+    reconcile_preset: String,
+}
+
+impl Names {
+    fn new(namespace: &str) -> Self {
+        Self {
+            installed: format!("{namespace}: Installed"),
+            current_preset: format!("{namespace}: Current Preset"),
+            last_shuffle_day: format!("{namespace}: Last Shuffle Day"),
+            restore_prefix: format!("{namespace}: Restore Preset"),
+            activate_prefix: format!("{namespace}: Activate Preset"),
+            select_preset: format!("AAAAA {namespace}: Select Preset"),
+            restore_universe: format!("{namespace}: Restore Universe"),
+            manual_shuffle: format!("{namespace}: Manual Shuffle"),
+            initial_backpatch: format!("aaaaa {namespace} Initial Backpatch"),
+            applied_preset: format!("{namespace}: Applied Preset"),
+            target_preset: format!("{namespace}: Target Preset"),
+            tracks_applied_preset: format!("{namespace}: Tracks Applied Preset"),
+            reconcile_preset: format!("AAAAA {namespace}: Reconcile Preset"),
+        }
+    }
 }
 
 #[allow(clippy::missing_errors_doc)]
@@ -40,19 +147,841 @@ pub fn process_data(
     data_folder: &DataFolder,
     settings: config::SystemShufflerConfig,
 ) -> Result<Vec<u8>, Box<dyn Error>> {
-    let data = data_folder.data();
+    process_parsed(data_folder.data(), settings)
+}
 
-    let mut rng = XoShiRo256SS::new(*settings.seed());
+/// Like `process_data`, but for an upload that was already parsed, so several generators can share it
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed(
+    data: &Data,
+    settings: config::SystemShufflerConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    process_parsed_with_report(data, settings).map(|(output, _)| output)
+}
+
+/// Like `process_parsed`, but also returns a report of what was shuffled
+#[allow(clippy::missing_errors_doc)]
+pub fn process_parsed_with_report(
+    data: &Data,
+    settings: config::SystemShufflerConfig,
+) -> Result<(Vec<u8>, Report), Box<dyn Error>> {
     let mut output = vec![];
 
-    let mut generator = SystemShuffler {
-        archive: Zip::new(&mut output),
-        output_data: Data::default(),
-        settings,
-    };
+    let mut archive = Zip::new(&mut output);
+
+    let include_full_map = *settings.include_full_map();
+
+    let report = write_plugin(data, settings, &mut archive, true)?;
+
+    // the shuffler only moves systems around and never renames them, so the map is revealed
+    // from the original data, in its own data directory so none of the files collide
+    if include_full_map {
+        archive.set_data_dir("data/full_map/");
+
+        generators::full_map::write_plugin(data, &mut archive, false)?;
+    }
+
+    archive.finish()?;
+
+    Ok((output, report))
+}
+
+/// Generates one plugin per region (the government its systems belong to), each only shuffling the
+/// systems in its own region, and zips them all together so players can pick which ones to install
+#[allow(clippy::missing_errors_doc)]
+pub fn process_data_per_region(
+    data_folder: &DataFolder,
+    settings: &config::SystemShufflerConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut output = vec![];
+
+    let mut archive = Zip::new(&mut output);
+
+    let regions = system_regions(data_folder.data())
+        .into_values()
+        .collect::<BTreeSet<_>>();
+
+    for region in regions {
+        let mut region_settings = settings.clone();
+
+        region_settings.region = Some(region.to_string());
+
+        let region_plugin = process_parsed(data_folder.data(), region_settings)?;
+
+        archive.write_file(
+            format!("{}.zip", zippy::sanitize_zip_path(region)),
+            region_plugin.as_slice(),
+        )?;
+    }
+
+    archive.finish()?;
+
+    Ok(output)
+}
+
+/// What the `plugin.txt` will say, without reading any game data
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_plugin_txt(
+    settings: &config::SystemShufflerConfig,
+) -> Result<String, Box<dyn Error>> {
+    generators::plugin_txt(
+        plugin_name(settings),
+        about_lines(settings).as_slice(),
+        settings
+            .plugin_version()
+            .as_deref()
+            .unwrap_or(PLUGIN_VERSION),
+        settings.requires(),
+    )
+}
+
+/// The `about` lines of the `plugin.txt`, summarizing the settings the plugin was generated with
+#[must_use]
+pub fn about_lines(settings: &config::SystemShufflerConfig) -> Vec<String> {
+    let plugin_description_txt = format!(
+        "\
+        An Endless Sky \"no logic\" location randomizer.\n\
+        \n\n\
+        \
+        {}\
+        - PRNG seed: {}\n\
+        - {} possible universe presets\n\
+        {}\
+        {}\
+        {}\
+        {}\
+        {}\
+        {}\
+        {}\
+        {}
+        ",
+        if *settings.shuffle_once_on_install() {
+            "In addition to shuffling once immediately upon installation, this plugin was generated with the following settings:\n"
+        } else {
+            "This plugin was generated with the following settings:\n"
+        },
+        settings.seed(),
+        preset_count(settings),
+        if settings.preset_names().is_empty() {
+            String::new()
+        } else {
+            format!("- Named presets: {}\n", settings.preset_names().join(", "))
+        },
+        settings
+            .region()
+            .as_ref()
+            .map_or_else(String::new, |region| format!(
+                "- Only systems in the {region} region are shuffled\n"
+            )),
+        match settings.geometry_mode() {
+            config::GeometryMode::Swap => "",
+            config::GeometryMode::Rotate =>
+                "- Every preset rotates the whole galaxy instead of swapping systems\n",
+            config::GeometryMode::Mirror =>
+                "- Every preset mirrors the whole galaxy instead of swapping systems\n",
+        },
+        if *settings.include_inverse_presets() {
+            "- Half of the presets are the inverse of the other half\n"
+        } else {
+            ""
+        },
+        if *settings.include_full_map() {
+            "- The entire map can be revealed via any job board\n"
+        } else {
+            ""
+        },
+        if *settings.one_way() {
+            "- The shuffle is permanent, there is no way to unshuffle\n"
+        } else {
+            ""
+        },
+        if *settings.shuffle_chance() > 0 {
+            format!(
+                "- A {}% chance to shuffle to a different preset every time you land\n",
+                settings.shuffle_chance()
+            )
+        } else {
+            String::new()
+        },
+        if *settings.fixed_shuffle_days() > 0 {
+            format!(
+                "- A guaranteed shuffle roughly once every {} days\n",
+                settings.fixed_shuffle_days()
+            )
+        } else {
+            String::new()
+        },
+    );
+
+    plugin_description_txt
+        .lines()
+        .map(str::trim)
+        .filter(|about| !about.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// The `plugin_name`, or the default for a shuffler that wasn't given one
+fn plugin_name(settings: &config::SystemShufflerConfig) -> &str {
+    settings.plugin_name().as_deref().unwrap_or(PLUGIN_NAME)
+}
+
+/// Shufflers with different plugin names keep their conditions, events, and missions apart too,
+/// unless they were given the same `namespace`
+fn namespace(settings: &config::SystemShufflerConfig) -> &str {
+    settings
+        .namespace()
+        .as_deref()
+        .unwrap_or_else(|| plugin_name(settings))
+}
+
+/// Checks a preset index from JS, which may be negative, fractional, or past the last preset
+/// Preset 0 is the original universe, so the valid range is `0..=preset_count`
+#[allow(clippy::missing_errors_doc)]
+pub fn preset_index(
+    settings: &config::SystemShufflerConfig,
+    value: f64,
+) -> Result<usize, Box<dyn Error>> {
+    let preset_count = preset_count(settings);
+
+    let last_preset = u32::try_from(preset_count).map_or(f64::MAX, f64::from);
+
+    if !value.is_finite() || value.fract() != 0.0 || value < 0.0 || value > last_preset {
+        return Err(Box::new(io::Error::other(format!(
+            "Preset index {value} is out of range, it must be a whole number from 0 to {preset_count} :("
+        ))));
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok(value as usize)
+}
+
+/// The folder a preset is written to, its name from `preset_names` (matched by index from preset 0)
+/// or `universe_preset_N` for the presets past the end of the names
+fn preset_folder_name(settings: &config::SystemShufflerConfig, preset_index: usize) -> String {
+    settings.preset_names().get(preset_index).map_or_else(
+        || format!("universe_preset_{preset_index}"),
+        |preset_name| zippy::sanitize_zip_path(preset_name),
+    )
+}
+
+fn preset_count(settings: &config::SystemShufflerConfig) -> usize {
+    let max_presets = usize::from(*settings.max_presets());
+
+    if *settings.include_inverse_presets() {
+        max_presets * 2
+    } else {
+        max_presets
+    }
+}
+
+/// Writes the plugin into an archive that may be shared with other generators
+/// Without `plugin_txt`, whoever owns the archive is expected to write the `plugin.txt`
+#[allow(clippy::too_many_lines)]
+pub(crate) fn write_plugin(
+    data: &Data,
+    settings: config::SystemShufflerConfig,
+    archive: &mut Zip,
+    plugin_txt: bool,
+) -> Result<Report, Box<dyn Error>> {
+    check_settings(&settings)?;
+
+    // plugins for different regions can be installed together, so each needs its own namespace
+    let namespace = settings.region().as_ref().map_or_else(
+        || namespace(&settings).to_string(),
+        |region| format!("{}: {region}", namespace(&settings)),
+    );
+
+    let names = Names::new(namespace.as_str());
+
+    let mut generator = SystemShuffler {
+        archive,
+        output_data: Data::default(),
+        settings,
+        names,
+        event_scopes: vec![],
+        warnings: vec![],
+        system_music: HashMap::new(),
+        neighborhood_music: HashMap::new(),
+        geometry: None,
+        preset_transform: geometry::Transform::default(),
+    };
+
+    if *generator.settings.normalize_music() {
+        let system_music = system_music(data);
+
+        generator.neighborhood_music = neighborhood_music(data, &system_music)
+            .into_iter()
+            .map(|(system_name, music)| (system_name.to_string(), music.to_string()))
+            .collect();
+
+        generator.system_music = system_music
+            .into_iter()
+            .map(|(system_name, music)| (system_name.to_string(), music.to_string()))
+            .collect();
+    }
+
+    if plugin_txt {
+        generator.description()?;
+    }
+
+    let optional_node_kinds = optional_node_kinds(&generator.settings);
+
+    generator.log("Detecting shuffled data");
+
+    let Detection {
+        system_names,
+        persistent_nodes,
+        persistent_event_node_keys,
+        persistent_event_nodes,
+    } = detect(
+        data,
+        *generator.settings.detect_wormholes(),
+        optional_node_kinds.as_slice(),
+    );
+
+    generator.log(
+        format!(
+            "Found {} systems and {} events to shuffle",
+            system_names.len(),
+            persistent_event_node_keys.len()
+        )
+        .as_str(),
+    );
+
+    // without any systems every preset would be an empty swap, which is a valid but useless plugin
+    if system_names.is_empty() {
+        return Err(Box::new(io::Error::other(
+            "The uploaded data doesn't define any systems to shuffle :(",
+        )));
+    }
+
+    let undefined_systems = find_undefined_systems(
+        data,
+        system_names.as_slice(),
+        &persistent_nodes,
+        &persistent_event_nodes,
+    );
+
+    if !undefined_systems.is_empty() {
+        return Err(Box::new(io::Error::other(format!(
+            "Uploaded data references systems {} that are not defined; upload the base systems too.",
+            undefined_systems.join(", ")
+        ))));
+    }
+
+    if generator.settings.preset_names().len() > preset_count(&generator.settings) + 1 {
+        generator.warn(too_many_preset_names(&generator.settings));
+    }
+
+    let event_layout = match (
+        *generator.settings.mirror_layout(),
+        *generator.settings.scope_events_by_kind(),
+        *generator.settings.split_by_government(),
+    ) {
+        (true, false, false) => split_by_source(&persistent_nodes)
+            .into_iter()
+            .map(|(source, source_nodes)| (format!("Source {source}"), source_nodes))
+            .collect(),
+        (false, true, false) => split_by_kind(&persistent_nodes)
+            .into_iter()
+            .map(|(kind, kind_nodes)| (kind.to_string(), kind_nodes))
+            .collect(),
+        (false, false, true) => split_by_government(data, &persistent_nodes)
+            .into_iter()
+            .map(|(government, government_nodes)| (government.to_string(), government_nodes))
+            .collect(),
+        (false, false, false) => vec![],
+        _ => unreachable!("Only one event layout can be picked, which `check_settings` verifies"),
+    };
+
+    generator.event_scopes = event_layout
+        .iter()
+        .map(|(scope, _)| scope.clone())
+        .collect();
+
+    generator.archive.write_dir("data/")?;
+
+    generator.main_data(persistent_event_node_keys.as_slice())?;
+
+    generator.archive.write_dir("data/presets/")?;
+
+    if *generator.settings.split_by_government() && !*generator.settings.combine_preset_files() {
+        for (government, _) in &event_layout {
+            generator.archive.write_dir(format!(
+                "data/presets/{}/",
+                zippy::sanitize_zip_path(government)
+            ))?;
+        }
+    }
+
+    let shuffled_names = shuffled_system_names(data, system_names.as_slice(), &generator.settings)?;
+
+    let preset_swaps = preset_swaps(
+        &generator.settings,
+        (system_names.as_slice(), shuffled_names.as_slice()),
+    );
+
+    let preset_transforms = preset_transforms(&generator.settings);
+
+    if *generator.settings.geometry_mode() != config::GeometryMode::Swap {
+        generator.geometry = geometry::Geometry::new(
+            shuffled_names
+                .iter()
+                .filter_map(|&system_name| {
+                    persistent_nodes
+                        .get(&("system", system_name))
+                        .and_then(|nodes| nodes.get("pos"))
+                        .and_then(|pos_nodes| pos_nodes.first())
+                        .and_then(|&(_, source, node)| node_position(data, (source, node)))
+                        .map(|position| (system_name, position))
+                })
+                .collect::<Vec<_>>()
+                .as_slice(),
+        );
+    }
+
+    for (preset_index, system_swaps) in preset_swaps.iter().enumerate() {
+        generator.preset_transform = preset_transforms[preset_index];
+
+        generator.preset(
+            data,
+            preset_index,
+            system_swaps,
+            (&persistent_nodes, &event_layout),
+            (&persistent_event_node_keys, &persistent_event_nodes),
+        )?;
+
+        generator
+            .log(format!("Wrote preset {preset_index} of {}", preset_swaps.len() - 1).as_str());
+    }
+
+    generator.initial_backpatch_missions(persistent_event_node_keys.as_slice())?;
+
+    let mut kind_counts = BTreeMap::new();
+
+    for &(kind, _) in persistent_nodes.keys() {
+        *kind_counts.entry(kind.to_string()).or_insert(0) += 1;
+    }
+
+    Ok(Report {
+        seed: *generator.settings.seed(),
+        preset_swaps: preset_swaps
+            .iter()
+            .map(|system_swaps| {
+                system_swaps
+                    .iter()
+                    .filter(|(original, swap)| original != swap)
+                    .map(|(original, swap)| ((*original).to_string(), (*swap).to_string()))
+                    .collect()
+            })
+            .collect(),
+        system_count: system_names.len(),
+        kind_counts,
+        affected_events: persistent_event_node_keys
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        warnings: generator.warnings,
+    })
+}
+
+/// Counts the presets that swap every system exactly like an earlier one, the original included
+/// That happens with few systems and many presets, and only the swaps are computed to find out
+#[allow(clippy::missing_errors_doc)]
+pub fn count_duplicate_presets(
+    data: &Data,
+    settings: &config::SystemShufflerConfig,
+) -> Result<usize, Box<dyn Error>> {
+    let optional_node_kinds = optional_node_kinds(settings);
+
+    let detection = detect(
+        data,
+        *settings.detect_wormholes(),
+        optional_node_kinds.as_slice(),
+    );
+
+    let shuffled_names = shuffled_system_names(data, detection.system_names.as_slice(), settings)?;
+
+    // rotated and mirrored presets all keep every system's name, so only their transforms differ
+    if *settings.geometry_mode() != config::GeometryMode::Swap {
+        let preset_transforms = preset_transforms(settings);
+
+        return Ok(preset_transforms
+            .iter()
+            .enumerate()
+            .filter(|&(preset_index, transform)| {
+                preset_transforms[..preset_index].contains(transform)
+            })
+            .count());
+    }
+
+    let preset_swaps = preset_swaps(
+        settings,
+        (detection.system_names.as_slice(), shuffled_names.as_slice()),
+    );
+
+    Ok(preset_swaps
+        .iter()
+        .enumerate()
+        .filter(|&(preset_index, system_swaps)| preset_swaps[..preset_index].contains(system_swaps))
+        .count())
+}
+
+/// Everything that would stop the settings from generating or make them do less than asked for
+/// on this data, so the UI can point it out before generating
+#[must_use]
+pub fn validate_config(data: &Data, settings: &config::SystemShufflerConfig) -> Vec<String> {
+    let mut warnings = vec![];
+
+    if let Err(error) = check_settings(settings) {
+        warnings.push(error.to_string());
+    }
+
+    if settings.preset_names().len() > preset_count(settings) + 1 {
+        warnings.push(too_many_preset_names(settings));
+    }
+
+    let optional_node_kinds = optional_node_kinds(settings);
+
+    let Detection {
+        system_names,
+        persistent_nodes,
+        persistent_event_nodes,
+        ..
+    } = detect(
+        data,
+        *settings.detect_wormholes(),
+        optional_node_kinds.as_slice(),
+    );
+
+    if system_names.is_empty() {
+        warnings.push("The uploaded data doesn't define any systems to shuffle :(".to_string());
+
+        return warnings;
+    }
+
+    let undefined_systems = find_undefined_systems(
+        data,
+        system_names.as_slice(),
+        &persistent_nodes,
+        &persistent_event_nodes,
+    );
+
+    if !undefined_systems.is_empty() {
+        warnings.push(format!(
+            "Uploaded data references systems {} that are not defined; upload the base systems too.",
+            undefined_systems.join(", ")
+        ));
+    }
+
+    if *settings.split_by_government() && system_regions(data).is_empty() {
+        warnings.push(
+            "None of the uploaded systems have a government, so they would all be split into the same folder"
+                .to_string(),
+        );
+    }
+
+    for pinned_system in settings
+        .pinned_systems()
+        .iter()
+        .filter(|pinned_system| system_names.binary_search(&pinned_system.as_str()).is_err())
+    {
+        warnings.push(format!(
+            "Pinned system `{pinned_system}` is not shuffled anyway, so pinning it does nothing"
+        ));
+    }
+
+    match shuffled_system_names(data, system_names.as_slice(), settings) {
+        Ok(shuffled_names) if shuffled_names.len() < 2 => {
+            warnings.push(
+                "Only one system would be shuffled, so every preset leaves the universe as it is"
+                    .to_string(),
+            );
+        }
+        Ok(_) => {}
+        Err(error) => warnings.push(error.to_string()),
+    }
+
+    match count_duplicate_presets(data, settings) {
+        Ok(0) | Err(_) => {}
+        Ok(duplicates) => warnings.push(format!(
+            "{duplicates} of the presets swap every system exactly like an earlier one"
+        )),
+    }
+
+    warnings
+}
+
+/// The checks on the settings alone, before any data is read
+fn check_settings(settings: &config::SystemShufflerConfig) -> Result<(), Box<dyn Error>> {
+    // the config file and share codes already refuse these, but the constructor JS calls doesn't
+    // `random` is compared against the chance as a percentage, so past 100 it would always pass
+    if *settings.shuffle_chance() > 100 {
+        return Err(Box::new(io::Error::other(
+            "The shuffle chance is a percentage, so it can't be more than 100 :(",
+        )));
+    }
+
+    // preset 0 is the original universe, so without any others there's nothing to shuffle into
+    if *settings.max_presets() == 0 {
+        return Err(Box::new(io::Error::other(
+            "There needs to be at least one preset besides the original universe :(",
+        )));
+    }
+
+    // every later shuffle (and every reconciliation) restores the current preset before picking
+    // the next one, so without a way to restore, the universe can only ever be shuffled the once,
+    // on install
+    if *settings.one_way()
+        && (*settings.shuffle_chance() > 0
+            || *settings.fixed_shuffle_days() > 0
+            || *settings.manual_job()
+            || *settings.reconcile_external_changes()
+            || !*settings.shuffle_once_on_install())
+    {
+        return Err(Box::new(io::Error::other(
+            "A one way shuffle only happens on install, so it needs shuffle_once_on_install without any shuffle chance, fixed shuffle days, manual job, or reconciling external changes :(",
+        )));
+    }
+
+    if !*settings.shuffle_links()
+        && !*settings.shuffle_positions()
+        && *settings.geometry_mode() == config::GeometryMode::Swap
+    {
+        return Err(Box::new(io::Error::other(
+            "With neither links nor positions shuffled, every preset would leave the map as it is :(",
+        )));
+    }
+
+    if [
+        *settings.mirror_layout(),
+        *settings.scope_events_by_kind(),
+        *settings.split_by_government(),
+    ]
+    .into_iter()
+    .filter(|&layout| layout)
+    .count()
+        > 1
+    {
+        return Err(Box::new(io::Error::other(
+            "Events can be split by source file, by kind, or by government, but only one at a time :(",
+        )));
+    }
+
+    // two presets in one folder would write the same files twice, which the archive rejects
+    let mut preset_folder_names = HashSet::new();
+
+    for preset_index in 0..=preset_count(settings) {
+        let preset_folder_name = preset_folder_name(settings, preset_index);
+
+        if !preset_folder_names.insert(preset_folder_name.clone()) {
+            return Err(Box::new(io::Error::other(format!(
+                "More than one preset would be written to the folder \"{preset_folder_name}\", every preset needs a different name :("
+            ))));
+        }
+    }
+
+    Ok(())
+}
+
+fn too_many_preset_names(settings: &config::SystemShufflerConfig) -> String {
+    format!(
+        "Only presets 0 to {} are generated, so the names past that are never used",
+        preset_count(settings)
+    )
+}
+
+/// `optional_node_kinds` for `detect`, depending on what the settings shuffle besides positions and links
+fn optional_node_kinds(settings: &config::SystemShufflerConfig) -> Vec<&'static str> {
+    let mut optional_node_kinds = vec![];
+
+    if *settings.shuffle_minables() {
+        optional_node_kinds.push("minables");
+    }
+
+    // the game reads `hazard` in an event's system the same way it does `minables`, by adding and
+    // removing whole entries, so hazards are as safe to move around as asteroid fields
+    if *settings.shuffle_hazards() {
+        optional_node_kinds.push("hazard");
+    }
+
+    // `arrival` may hold `link` and `jump` distances as children, which are copied along with it
+    if *settings.shuffle_radii() {
+        optional_node_kinds.extend(["belt", "arrival"]);
+    }
+
+    // experimental: each object moves in with an `add object` carrying its whole subtree, moons
+    // included, and out with a `remove object` matched by its `sprite`, `distance`, `period`, and
+    // `offset`, the same way wormholes already move
+    // where an object is along its orbit comes from the date rather than anything saved, so moving
+    // it leaves nothing to corrupt, and missions find planets by name, so they follow the planet
+    // the replacement's own objects are taken out first, so no system ends up with both sets
+    if *settings.shuffle_objects() {
+        optional_node_kinds.push("object");
+    }
+
+    optional_node_kinds
+}
+
+/// The swaps of every preset, starting with the original universe and followed by any inverses
+fn preset_swaps<'a>(
+    settings: &config::SystemShufflerConfig,
+    (system_names, shuffled_names): (&[&'a str], &[&'a str]),
+) -> Vec<HashMap<&'a str, &'a str>> {
+    // rotated and mirrored presets move every system along with its own name, so none are swapped
+    let shuffled_names = if *settings.geometry_mode() == config::GeometryMode::Swap {
+        shuffled_names
+    } else {
+        &[]
+    };
+
+    let mut rng = XoShiRo256SS::new(*settings.seed());
+
+    let mut preset_swaps = (0..=usize::from(*settings.max_presets()))
+        .map(|preset_index| {
+            // with `no_fixed_points` every shuffled system moves, unless it's the only one
+            swaps::compute_preset_swaps_with_rng(
+                (system_names, shuffled_names),
+                &mut rng,
+                &swaps::SwapOptions {
+                    identity: preset_index == 0,
+                    derangement: *settings.no_fixed_points(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if *settings.include_inverse_presets() {
+        let inverse_swaps = preset_swaps
+            .iter()
+            .skip(1)
+            .map(SystemShuffler::invert_system_swaps)
+            .collect::<Vec<_>>();
+
+        preset_swaps.extend(inverse_swaps);
+    }
+
+    preset_swaps
+}
+
+/// The transform of every preset, lined up with `preset_swaps` and left as the original universe
+/// in `GeometryMode::Swap`
+fn preset_transforms(settings: &config::SystemShufflerConfig) -> Vec<geometry::Transform> {
+    let mut rng = XoShiRo256SS::new(*settings.seed());
+
+    let mut preset_transforms = (0..=usize::from(*settings.max_presets()))
+        .map(|preset_index| {
+            if preset_index == 0 {
+                geometry::Transform::default()
+            } else {
+                geometry::Transform::with_rng(*settings.geometry_mode(), &mut rng)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if *settings.include_inverse_presets() {
+        let inverse_transforms = preset_transforms
+            .iter()
+            .skip(1)
+            .map(geometry::Transform::inverse)
+            .collect::<Vec<_>>();
+
+        preset_transforms.extend(inverse_transforms);
+    }
+
+    preset_transforms
+}
+
+/// The x and y of a `pos` node, if both are numbers
+fn node_position(
+    data: &Data,
+    (source_index, node_index): (SourceIndex, NodeIndex),
+) -> Option<(f64, f64)> {
+    let tokens = data.get_tokens(node_index)?;
+
+    let coordinate = |token_index: usize| {
+        data.get_lexeme(source_index, tokens.get(token_index)?)?
+            .parse::<f64>()
+            .ok()
+    };
+
+    coordinate(1).zip(coordinate(2))
+}
+
+/// Builds a `has`/`not` condition, or the equivalent `!= 0`/`== 0` comparison when `numeric` is set
+fn flag_condition(
+    output_data: &mut Data,
+    source: SourceIndex,
+    numeric: bool,
+    (is_set, condition): (bool, &str),
+) -> NodeIndex {
+    if numeric {
+        tree_from_tokens!(
+            output_data; source =>
+            : condition, if is_set { "!=" } else { "==" }, "0" ;
+        )
+    } else {
+        tree_from_tokens!(
+            output_data; source =>
+            : if is_set { "has" } else { "not" }, condition ;
+        )
+    }
+}
+
+pub struct Detection<'a> {
+    system_names: Vec<&'a str>,
+    persistent_nodes: PersistentOriginalNodes<'a>,
+    persistent_event_node_keys: Vec<&'a str>,
+    persistent_event_nodes: HashMap<&'a str, PersistentOriginalNodes<'a>>,
+}
+
+impl Detection<'_> {
+    #[must_use]
+    pub const fn system_count(&self) -> usize {
+        self.system_names.len()
+    }
+
+    #[must_use]
+    pub const fn event_count(&self) -> usize {
+        self.persistent_event_node_keys.len()
+    }
 
-    generator.description()?;
+    /// Everything detection captured for one root node (like `("system", "Sol")`), outside of events
+    /// Each captured node kind maps to the action of every node of that kind, in the order they were found
+    #[must_use]
+    pub fn captured_nodes(
+        &self,
+        original_kind: &str,
+        original: &str,
+    ) -> BTreeMap<&str, Vec<&'static str>> {
+        self.persistent_nodes
+            .get(&(original_kind, original))
+            .map(|original_nodes| {
+                original_nodes
+                    .iter()
+                    .map(|(&node_kind, node_values)| {
+                        (
+                            node_kind,
+                            node_values
+                                .iter()
+                                .map(|(action, _, _)| action.name())
+                                .collect(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
 
+/// `optional_node_kinds` are extra system children (like `minables`) that get shuffled along with the rest
+#[must_use]
+pub fn detect<'a>(
+    data: &'a Data,
+    detect_wormholes: bool,
+    optional_node_kinds: &[&'static str],
+) -> Detection<'a> {
     let mut system_names = HashSet::new();
 
     let mut persistent_nodes = HashMap::new();
@@ -60,151 +989,120 @@ pub fn process_data(
     let mut planets = HashMap::new();
     let mut wormholes = HashSet::new();
 
-    find_wormholes_from_planets(data, &mut wormholes);
+    if detect_wormholes {
+        find_wormholes_from_planets(data, &mut wormholes);
+    }
 
     data_from_node(
         data,
         node_path_iter!(&data; "system" | "wormhole"),
         &mut system_names,
-        (&mut planets, &mut wormholes),
+        (detect_wormholes, &mut planets, &mut wormholes),
         &mut persistent_nodes,
+        optional_node_kinds,
     );
 
-    let (persistent_event_node_keys, persistent_event_nodes) =
-        find_persistent_event_nodes(data, &mut system_names, &mut planets, &mut wormholes);
+    let (persistent_event_node_keys, persistent_event_nodes) = find_persistent_event_nodes(
+        data,
+        &mut system_names,
+        (detect_wormholes, &mut planets, &mut wormholes),
+        optional_node_kinds,
+    );
 
     let mut system_names = system_names.into_iter().collect::<Vec<_>>();
 
     system_names.sort_unstable();
 
-    generator.archive.write_dir("data/")?;
-
-    generator.main_data(persistent_event_node_keys.as_slice())?;
-
-    generator.archive.write_dir("data/presets/")?;
-
-    for preset_index in 0..=(*generator.settings.max_presets()) {
-        generator.preset(
-            data,
-            &mut rng,
-            preset_index,
-            system_names.as_slice(),
-            &persistent_nodes,
-            (&persistent_event_node_keys, &persistent_event_nodes),
-        )?;
+    Detection {
+        system_names,
+        persistent_nodes,
+        persistent_event_node_keys,
+        persistent_event_nodes,
     }
-
-    generator.initial_backpatch_missions(persistent_event_node_keys.as_slice())?;
-
-    generator.archive.finish()?;
-
-    Ok(output)
 }
 
-impl SystemShuffler<'_> {
+impl SystemShuffler<'_, '_> {
     fn zip_root_nodes<P: Into<PathBuf>>(
         &mut self,
         path: P,
         from: usize,
     ) -> Result<(), Box<dyn Error>> {
         generators::zip_root_nodes(
-            &mut self.archive,
+            self.archive,
             path,
             &self.output_data,
             &self.output_data.root_nodes()[from..],
         )
     }
 
-    fn description(&mut self) -> Result<(), Box<dyn Error>> {
-        let plugin_description_txt = format!(
-            "\
-            An Endless Sky \"no logic\" location randomizer.\n\
-            \n\n\
-            \
-            {}\
-            - PRNG seed: {}\n\
-            - {} possible universe presets\n\
-            {}\
-            {}
-            ",
-            if *self.settings.shuffle_once_on_install() {
-                "In addition to shuffling once immediately upon installation, this plugin was generated with the following settings:\n"
-            } else {
-                "This plugin was generated with the following settings:\n"
-            },
-            self.settings.seed(),
-            self.settings.max_presets(),
-            if *self.settings.shuffle_chance() > 0 {
-                format!(
-                    "- A {}% chance to shuffle to a different preset every time you land\n",
-                    self.settings.shuffle_chance()
-                )
-            } else {
-                String::new()
-            },
-            if *self.settings.fixed_shuffle_days() > 0 {
-                format!(
-                    "- A guaranteed shuffle roughly once every {} days\n",
-                    self.settings.fixed_shuffle_days()
-                )
-            } else {
-                String::new()
-            },
-        );
-
-        let output_root_node_count = self.output_data.root_nodes().len();
-        let plugin_txt_source = self.output_data.insert_source(String::new());
-
-        let plugin_name = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "name", PLUGIN_NAME ;
-        );
-
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_name);
+    /// Problems that don't stop the generation are logged and kept for the report
+    fn warn(&mut self, warning: String) {
+        generators::log(warning.as_str());
 
-        for about in plugin_description_txt.lines().map(str::trim) {
-            if !about.is_empty() {
-                let plugin_about = tree_from_tokens!(
-                    &mut self.output_data; plugin_txt_source =>
-                    : "about", about ;
-                );
+        self.warnings.push(warning);
+    }
 
-                self.output_data
-                    .push_root_node(plugin_txt_source, plugin_about);
-            }
+    /// Only logs with `verbose` set, so normal users aren't spammed
+    fn log(&self, text: &str) {
+        if *self.settings.verbose() {
+            generators::log(format!("{}: {text}", plugin_name(&self.settings)).as_str());
         }
+    }
 
-        let plugin_version = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "version", PLUGIN_VERSION ;
-        );
+    fn preset_event_names(&self, preset_index: usize) -> (String, String) {
+        (
+            format!("{} {preset_index}", self.names.restore_prefix),
+            format!("{} {preset_index}", self.names.activate_prefix),
+        )
+    }
 
-        self.output_data
-            .push_root_node(plugin_txt_source, plugin_version);
+    fn scoped_event_names(&self, event_name: &str) -> Vec<String> {
+        if self.event_scopes.is_empty() {
+            vec![event_name.to_string()]
+        } else {
+            self.event_scopes
+                .iter()
+                .map(|scope| format!("{event_name}: {scope}"))
+                .collect()
+        }
+    }
 
-        let dependencies = tree_from_tokens!(
-            &mut self.output_data; plugin_txt_source =>
-            : "dependencies" ;
-            {
-                : "game version", crate::GAME_VERSION ;
-            }
-        );
+    fn preset_count(&self) -> usize {
+        preset_count(&self.settings)
+    }
 
-        self.output_data
-            .push_root_node(plugin_txt_source, dependencies);
+    fn description(&mut self) -> Result<(), Box<dyn Error>> {
+        let plugin_txt = preview_plugin_txt(&self.settings)?;
 
-        self.zip_root_nodes("plugin.txt", output_root_node_count)
+        self.archive.write_file("plugin.txt", plugin_txt.as_bytes())
     }
 
     fn main_data(&mut self, persistent_event_node_keys: &[&str]) -> Result<(), Box<dyn Error>> {
         let output_root_node_count = self.output_data.root_nodes().len();
 
+        // every preset gets a restore and an activate branch, plus one of each for every event
+        let conditional_branches =
+            2 * (self.preset_count() + 1) * (persistent_event_node_keys.len() + 1);
+
+        if conditional_branches > MAX_CONDITIONAL_BRANCHES {
+            self.warn(format!(
+                "Picking a preset takes {conditional_branches} branches, more than the {MAX_CONDITIONAL_BRANCHES} the game handles comfortably; try fewer presets, a region, or uploading less data"
+            ));
+        }
+
         self.main_mission(persistent_event_node_keys);
 
-        self.restore_job(persistent_event_node_keys);
+        if !*self.settings.one_way() {
+            self.restore_job(persistent_event_node_keys);
+        }
+
+        if *self.settings.manual_job() {
+            self.manual_trigger(persistent_event_node_keys);
+        }
 
-        self.manual_trigger(persistent_event_node_keys);
+        if *self.settings.reconcile_external_changes() {
+            self.reconcile_mission(persistent_event_node_keys);
+        }
 
         self.zip_root_nodes("data/main.txt", output_root_node_count)
     }
@@ -214,7 +1112,7 @@ impl SystemShuffler<'_> {
 
         let main_mission = tree_from_tokens!(
             &mut self.output_data; main_mission_source =>
-            : "mission", "AAAAA System Shuffler: Select Preset" ;
+            : "mission", self.names.select_preset.as_str() ;
             {
                 : "invisible" ;
                 : "repeat" ;
@@ -255,6 +1153,8 @@ impl SystemShuffler<'_> {
             self.output_data
                 .push_child(main_mission_to_offer, main_mission_to_offer_or);
 
+            // `random` is a whole number from 0 to 99, so `random < N` is exactly an N% chance and
+            // a chance of 100 always passes, no `<=` needed
             if *self.settings.shuffle_chance() > 0 {
                 let random_chance = tree_from_tokens!(
                     &mut self.output_data; main_mission_source =>
@@ -268,7 +1168,7 @@ impl SystemShuffler<'_> {
             if *self.settings.fixed_shuffle_days() > 0 {
                 let guaranteed = tree_from_tokens!(
                     &mut self.output_data; main_mission_source =>
-                    : "days since epoch", ">=", "(", LAST_SHUFFLE_DAY, "+", self.settings.fixed_shuffle_days(), ")" ;
+                    : "days since epoch", ">=", "(", self.names.last_shuffle_day.as_str(), "+", self.settings.fixed_shuffle_days(), ")" ;
                 );
 
                 self.output_data
@@ -276,9 +1176,11 @@ impl SystemShuffler<'_> {
             }
 
             if *self.settings.shuffle_once_on_install() {
-                let first_time = tree_from_tokens!(
-                    &mut self.output_data; main_mission_source =>
-                    : "not", INSTALLED ;
+                let first_time = flag_condition(
+                    &mut self.output_data,
+                    main_mission_source,
+                    *self.settings.numeric_conditions(),
+                    (false, self.names.installed.as_str()),
                 );
 
                 self.output_data
@@ -294,12 +1196,13 @@ impl SystemShuffler<'_> {
         self.output_data
             .push_child(main_mission, main_mission_on_offer);
 
-        let main_mission_conversation = tree_from_tokens!(
-            &mut self.output_data; main_mission_source =>
-            : "conversation" ;
-            {
-                : "The universe has shuffled. Good luck." ;
-            }
+        let main_mission_conversation = self.conversation(
+            main_mission_source,
+            self.settings
+                .shuffle_message()
+                .clone()
+                .unwrap_or_else(|| "The universe has shuffled. Good luck.".to_string())
+                .as_str(),
         );
 
         self.output_data
@@ -328,38 +1231,186 @@ impl SystemShuffler<'_> {
         reset: bool,
         persistent_event_node_keys: &[&str],
     ) {
-        for preset_index in 0..=(*self.settings.max_presets()) {
+        // a one way shuffle only ever starts from the original universe, so there is nothing to
+        // restore first
+        if !*self.settings.one_way() {
+            for preset_index in 0..=self.preset_count() {
+                self.conditional_events(
+                    (source, node),
+                    (false, "restore"),
+                    persistent_event_node_keys,
+                    preset_index,
+                );
+            }
+        }
+
+        let preset_selection = self.select_preset(source, reset);
+
+        self.output_data.push_child(node, preset_selection);
+
+        for preset_index in 0..=self.preset_count() {
             self.conditional_events(
                 (source, node),
-                (false, RESTORE_PREFIX, "restore"),
+                (true, "activate"),
                 persistent_event_node_keys,
                 preset_index,
             );
         }
 
-        let preset_selection = self.select_preset(source, reset);
+        if *self.settings.reconcile_external_changes() {
+            let applied_preset = self.applied_preset_action(source);
 
-        self.output_data.push_child(node, preset_selection);
+            self.output_data.push_child(node, applied_preset);
+        }
+    }
+
+    /// Remembers which preset is actually in the universe, so a change to the current preset made by
+    /// anything other than this plugin can be noticed and undone
+    fn applied_preset_action(&mut self, source: SourceIndex) -> NodeIndex {
+        tree_from_tokens!(
+            &mut self.output_data; source =>
+            : "action" ;
+            {
+                : self.names.applied_preset.as_str(), "=", self.names.current_preset.as_str() ;
+                : self.names.tracks_applied_preset.as_str(), "=", "1" ;
+            }
+        )
+    }
+
+    /// Catches the current preset being changed by something else (another plugin, the console) and
+    /// restores the preset that is really in the universe before activating the new one
+    /// Saves from before this was enabled don't track the applied preset until their next shuffle,
+    /// since activating a preset on top of itself would duplicate its changes
+    fn reconcile_mission(&mut self, persistent_event_node_keys: &[&str]) {
+        let reconcile_source = self.output_data.insert_source(String::new());
+
+        let reconcile_mission = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "mission", self.names.reconcile_preset.as_str() ;
+            {
+                : "invisible" ;
+                : "repeat" ;
+                : "non-blocking" ;
+                : "landing" ;
+                : "offer precedence", "-1000000" ;
+            }
+        );
+
+        self.output_data
+            .push_root_node(reconcile_source, reconcile_mission);
+
+        let reconcile_to_offer = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "to", "offer" ;
+        );
+
+        self.output_data
+            .push_child(reconcile_mission, reconcile_to_offer);
+
+        let tracks_applied_preset = flag_condition(
+            &mut self.output_data,
+            reconcile_source,
+            *self.settings.numeric_conditions(),
+            (true, self.names.tracks_applied_preset.as_str()),
+        );
+
+        self.output_data
+            .push_child(reconcile_to_offer, tracks_applied_preset);
+
+        let preset_changed = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : self.names.applied_preset.as_str(), "!=", self.names.current_preset.as_str() ;
+        );
+
+        self.output_data
+            .push_child(reconcile_to_offer, preset_changed);
+
+        let reconcile_on_offer = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "on", "offer" ;
+        );
+
+        self.output_data
+            .push_child(reconcile_mission, reconcile_on_offer);
+
+        let reconcile_conversation = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "conversation" ;
+            {
+                : "The universe has shifted to match its new preset." ;
+            }
+        );
+
+        self.output_data
+            .push_child(reconcile_on_offer, reconcile_conversation);
+
+        // the restore branches check the current preset, so it briefly points back at the applied one
+        let restore_applied = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "action" ;
+            {
+                : self.names.target_preset.as_str(), "=", self.names.current_preset.as_str() ;
+                : self.names.current_preset.as_str(), "=", self.names.applied_preset.as_str() ;
+            }
+        );
+
+        self.output_data
+            .push_child(reconcile_conversation, restore_applied);
 
-        for preset_index in 0..=(*self.settings.max_presets()) {
+        for preset_index in 0..=self.preset_count() {
             self.conditional_events(
-                (source, node),
-                (true, ACTIVATE_PREFIX, "activate"),
+                (reconcile_source, reconcile_conversation),
+                (false, "reconcile restore"),
+                persistent_event_node_keys,
+                preset_index,
+            );
+        }
+
+        let activate_target = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "action" ;
+            {
+                : self.names.current_preset.as_str(), "=", self.names.target_preset.as_str() ;
+            }
+        );
+
+        self.output_data
+            .push_child(reconcile_conversation, activate_target);
+
+        for preset_index in 0..=self.preset_count() {
+            self.conditional_events(
+                (reconcile_source, reconcile_conversation),
+                (true, "reconcile activate"),
                 persistent_event_node_keys,
                 preset_index,
             );
         }
+
+        let applied_preset = self.applied_preset_action(reconcile_source);
+
+        self.output_data
+            .push_child(reconcile_conversation, applied_preset);
+
+        let reconcile_failure = tree_from_tokens!(
+            &mut self.output_data; reconcile_source =>
+            : "fail" ;
+        );
+
+        self.output_data
+            .push_child(reconcile_on_offer, reconcile_failure);
     }
 
     fn select_preset(&mut self, source: SourceIndex, reset: bool) -> NodeIndex {
+        let preset_count = self.preset_count();
+
         if reset {
             tree_from_tokens!(
                 &mut self.output_data; source =>
                 : "action" ;
                 {
-                    : INSTALLED, "=", "1" ;
-                    : CURRENT_PRESET, "=", "0" ;
-                    : LAST_SHUFFLE_DAY, "=", "days since epoch" ;
+                    : self.names.installed.as_str(), "=", "1" ;
+                    : self.names.current_preset.as_str(), "=", "0" ;
+                    : self.names.last_shuffle_day.as_str(), "=", "days since epoch" ;
                 }
             )
         } else {
@@ -367,9 +1418,9 @@ impl SystemShuffler<'_> {
                 &mut self.output_data; source =>
                 : "action" ;
                 {
-                    : INSTALLED, "=", "1" ;
-                    : CURRENT_PRESET, "=", "(", format!("roll: {}", self.settings.max_presets()).as_str(), "+", "1", ")" ;
-                    : LAST_SHUFFLE_DAY, "=", "days since epoch" ;
+                    : self.names.installed.as_str(), "=", "1" ;
+                    : self.names.current_preset.as_str(), "=", "(", format!("roll: {preset_count}").as_str(), "+", "1", ")" ;
+                    : self.names.last_shuffle_day.as_str(), "=", "days since epoch" ;
                 }
             )
         }
@@ -380,10 +1431,22 @@ impl SystemShuffler<'_> {
 
         let restore_job = tree_from_tokens!(
             &mut self.output_data; restore_job_source =>
-            : "mission", "System Shuffler: Restore Universe" ;
+            : "mission", self.names.restore_universe.as_str() ;
             {
-                : "name", "Unshuffle the universe" ;
-                : "description", "Restore all systems in the universe to how they should be, free of charge." ;
+                :
+                    "name",
+                    self.settings
+                        .restore_job_name()
+                        .as_deref()
+                        .unwrap_or("Unshuffle the universe")
+                ;
+                :
+                    "description",
+                    self.settings
+                        .restore_job_description()
+                        .as_deref()
+                        .unwrap_or("Restore all systems in the universe to how they should be, free of charge.")
+                ;
                 : "repeat" ;
                 : "job" ;
             }
@@ -396,7 +1459,7 @@ impl SystemShuffler<'_> {
             &mut self.output_data; restore_job_source =>
             : "to", "offer" ;
             {
-                : CURRENT_PRESET, "!=", "0" ;
+                : self.names.current_preset.as_str(), "!=", "0" ;
             }
         );
 
@@ -411,12 +1474,15 @@ impl SystemShuffler<'_> {
         self.output_data
             .push_child(restore_job, restore_job_on_accept);
 
-        let restore_job_conversation = tree_from_tokens!(
-            &mut self.output_data; restore_job_source =>
-            : "conversation" ;
-            {
-                : "As per your request, the universe has been restored." ;
-            }
+        let restore_job_conversation = self.conversation(
+            restore_job_source,
+            self.settings
+                .restore_message()
+                .clone()
+                .unwrap_or_else(|| {
+                    "As per your request, the universe has been restored.".to_string()
+                })
+                .as_str(),
         );
 
         self.output_data
@@ -439,14 +1505,31 @@ impl SystemShuffler<'_> {
     }
 
     fn manual_trigger(&mut self, persistent_event_node_keys: &[&str]) {
+        let manual_job_description = self
+            .settings
+            .manual_job_description()
+            .clone()
+            .unwrap_or_else(|| {
+                format!(
+                    "Shuffle all systems in the universe to one of {} presets.",
+                    self.preset_count()
+                )
+            });
+
         let manual_job_source = self.output_data.insert_source(String::new());
 
         let manual_job = tree_from_tokens!(
             &mut self.output_data; manual_job_source =>
-            : "mission", "System Shuffler: Manual Shuffle" ;
+            : "mission", self.names.manual_shuffle.as_str() ;
             {
-                : "name", "Shuffle the universe" ;
-                : "description", format!("Shuffle all systems in the universe to one of {} presets.", self.settings.max_presets()).as_str() ;
+                :
+                    "name",
+                    self.settings
+                        .manual_job_name()
+                        .as_deref()
+                        .unwrap_or("Shuffle the universe")
+                ;
+                : "description", manual_job_description.as_str() ;
                 : "repeat" ;
                 : "job" ;
             }
@@ -463,12 +1546,15 @@ impl SystemShuffler<'_> {
         self.output_data
             .push_child(manual_job, manual_job_on_accept);
 
-        let manual_job_conversation = tree_from_tokens!(
-            &mut self.output_data; manual_job_source =>
-            : "conversation" ;
-            {
-                : "As per your request, the universe has shuffled. Good luck." ;
-            }
+        let manual_job_conversation = self.conversation(
+            manual_job_source,
+            self.settings
+                .manual_message()
+                .clone()
+                .unwrap_or_else(|| {
+                    "As per your request, the universe has shuffled. Good luck.".to_string()
+                })
+                .as_str(),
         );
 
         self.output_data
@@ -490,43 +1576,95 @@ impl SystemShuffler<'_> {
             .push_child(manual_job_on_accept, main_failure);
     }
 
+    #[allow(clippy::too_many_lines)]
     fn preset(
         &mut self,
         data: &Data,
-        rng: &mut XoShiRo256SS,
-        preset_index: u8,
-        system_names: &[&str],
-        persistent_nodes: &PersistentOriginalNodes<'_>,
+        preset_index: usize,
+        system_swaps: &HashMap<&str, &str>,
+        (persistent_nodes, event_layout): (
+            &PersistentOriginalNodes<'_>,
+            &[(String, PersistentOriginalNodes<'_>)],
+        ),
         (persistent_event_node_keys, persistent_event_nodes): (
             &[&str],
             &HashMap<&str, PersistentOriginalNodes<'_>>,
         ),
     ) -> Result<(), Box<dyn Error>> {
-        let preset_index = usize::from(preset_index);
-
         let shuffle_event_source = self.output_data.insert_source(String::new());
 
-        let system_swaps = Self::get_system_swaps(rng, system_names, preset_index);
-
-        let preset_path = format!("data/presets/universe_preset_{preset_index}");
+        let preset_path = format!(
+            "data/presets/{}",
+            preset_folder_name(&self.settings, preset_index)
+        );
 
         self.archive.write_dir(format!("{preset_path}/"))?;
 
-        let restore_name = format!("{RESTORE_PREFIX} {preset_index}");
-        let activate_name = format!("{ACTIVATE_PREFIX} {preset_index}");
+        let preset_root_node_count = self.output_data.root_nodes().len();
 
-        {
+        let (restore_name, activate_name) = self.preset_event_names(preset_index);
+
+        // a one way shuffle never restores, except for the original universe's copies of the
+        // uploaded events, which `initial_backpatch_missions` fires
+        let writes_restore = !*self.settings.one_way();
+        let writes_event_restore = writes_restore || preset_index == 0;
+
+        if event_layout.is_empty() {
             let output_root_node_count = self.output_data.root_nodes().len();
 
             self.preset_event(
                 data,
                 shuffle_event_source,
-                &system_swaps,
+                system_swaps,
                 persistent_nodes,
-                (restore_name.as_str(), activate_name.as_str()),
+                (
+                    writes_restore.then_some(restore_name.as_str()),
+                    activate_name.as_str(),
+                ),
             );
 
-            self.zip_root_nodes(format!("{preset_path}/main.txt"), output_root_node_count)?;
+            self.zip_preset_nodes(format!("{preset_path}/main.txt"), output_root_node_count)?;
+        } else {
+            for (scope, scope_nodes) in event_layout {
+                let output_root_node_count = self.output_data.root_nodes().len();
+
+                self.preset_event(
+                    data,
+                    shuffle_event_source,
+                    system_swaps,
+                    scope_nodes,
+                    (
+                        writes_restore
+                            .then(|| format!("{restore_name}: {scope}"))
+                            .as_deref(),
+                        format!("{activate_name}: {scope}").as_str(),
+                    ),
+                );
+
+                // `Source 3` is written to `source_3.txt`, and `wormhole` to `wormhole.txt`, while
+                // every government gets a folder with a copy of the preset folders in it
+                let scope_path = if *self.settings.split_by_government() {
+                    let government_preset_path = format!(
+                        "data/presets/{}/{}",
+                        zippy::sanitize_zip_path(scope),
+                        preset_folder_name(&self.settings, preset_index)
+                    );
+
+                    if !*self.settings.combine_preset_files() {
+                        self.archive
+                            .write_dir(format!("{government_preset_path}/"))?;
+                    }
+
+                    format!("{government_preset_path}/main.txt")
+                } else {
+                    format!(
+                        "{preset_path}/{}.txt",
+                        scope.to_lowercase().replace(' ', "_")
+                    )
+                };
+
+                self.zip_preset_nodes(scope_path, output_root_node_count)?;
+            }
         }
 
         {
@@ -543,16 +1681,18 @@ impl SystemShuffler<'_> {
                 self.preset_event(
                     data,
                     shuffle_event_source,
-                    &system_swaps,
+                    system_swaps,
                     event_map,
                     (
-                        format!("{restore_name}: {event_name}").as_str(),
+                        writes_event_restore
+                            .then(|| format!("{restore_name}: {event_name}"))
+                            .as_deref(),
                         format!("{activate_name}: {event_name}").as_str(),
                     ),
                 );
             }
 
-            self.zip_root_nodes(format!("{preset_path}/events.txt"), output_root_node_count)?;
+            self.zip_preset_nodes(format!("{preset_path}/events.txt"), output_root_node_count)?;
         }
 
         {
@@ -567,52 +1707,97 @@ impl SystemShuffler<'_> {
                 );
             }
 
-            self.zip_root_nodes(
+            self.zip_preset_nodes(
                 format!("{preset_path}/missions.txt"),
                 output_root_node_count,
+            )?;
+        }
+
+        self.preset_map(preset_index, system_swaps)?;
+
+        if *self.settings.combine_preset_files() {
+            // the game doesn't care which order events and missions are defined in, so everything
+            // is written in the same order the separate files would have been
+            self.zip_root_nodes(
+                format!("{preset_path}/preset_{preset_index}.txt"),
+                preset_root_node_count,
             )
+        } else {
+            Ok(())
         }
     }
 
-    fn get_system_swaps<'a>(
-        rng: &mut XoShiRo256SS,
-        system_names: &[&'a str],
+    /// Lists where every system went in a preset, at the root of the archive so the game never loads it
+    /// The map is informational only; the swaps also depend on every other setting (pinned systems,
+    /// the region, `max_presets` and so on) and on the uploaded data, so it carries the share code
+    /// of the settings rather than just the seed
+    fn preset_map(
+        &mut self,
         preset_index: usize,
-    ) -> HashMap<&'a str, &'a str> {
-        let shuffled = if preset_index == 0 {
-            (0..(system_names.len()))
-                .map(|i| system_names[i])
-                .collect::<Vec<_>>()
+        system_swaps: &HashMap<&str, &str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut map = format!(
+            "# informational only, the game never loads this file\n\
+             # the same swaps come from these settings used on the same uploaded data\n\
+             seed: {}\nsettings: {}\npreset: {preset_index} ({})\n\n",
+            self.settings.seed(),
+            self.settings.to_code(),
+            preset_folder_name(&self.settings, preset_index)
+        );
+
+        let mut system_swaps = system_swaps.iter().collect::<Vec<_>>();
+
+        system_swaps.sort_unstable();
+
+        for (original, replacement) in system_swaps {
+            map.push_str(format!("\"{original}\" -> \"{replacement}\"\n").as_str());
+        }
+
+        self.archive
+            .write_file(format!("preset_{preset_index}_map.txt"), map.as_bytes())
+    }
+
+    /// With `combine_preset_files`, nothing is written yet, since the whole preset goes into one file
+    fn zip_preset_nodes<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        from: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        if *self.settings.combine_preset_files() {
+            Ok(())
         } else {
-            system_names
-                .shuffled_indices_with_rng(rng)
-                .into_iter()
-                .map(|i| system_names[i])
-                .collect::<Vec<_>>()
-        };
+            self.zip_root_nodes(path, from)
+        }
+    }
 
-        system_names
+    fn invert_system_swaps<'a>(
+        system_swaps: &HashMap<&'a str, &'a str>,
+    ) -> HashMap<&'a str, &'a str> {
+        system_swaps
             .iter()
-            .copied()
-            .zip(shuffled)
+            .map(|(&original, &replacement)| (replacement, original))
             .collect::<HashMap<_, _>>()
     }
 
+    /// Without a `restore_name`, the restore event is still filled in alongside the activate one,
+    /// but it's left out of the plugin
     fn preset_event(
         &mut self,
         data: &Data,
         shuffle_event_source: SourceIndex,
         system_swaps: &HashMap<&str, &str>,
         persistent_nodes: &PersistentOriginalNodes<'_>,
-        (restore_name, activate_name): (&str, &str),
+        (restore_name, activate_name): (Option<&str>, &str),
     ) {
         let shuffle_event_restore = tree_from_tokens!(
             &mut self.output_data; shuffle_event_source =>
-            : "event", restore_name ;
+            : "event", restore_name.unwrap_or_default() ;
         );
 
-        self.output_data
-            .push_root_node(shuffle_event_source, shuffle_event_restore);
+        if restore_name.is_some() {
+            self.output_data
+                .push_root_node(shuffle_event_source, shuffle_event_restore);
+        }
 
         let shuffle_event_activate = tree_from_tokens!(
             &mut self.output_data; shuffle_event_source =>
@@ -639,6 +1824,10 @@ impl SystemShuffler<'_> {
         (restore_name, activate_name): (&str, &str),
     ) {
         for (should_activate, kind_name) in [(false, restore_name), (true, activate_name)] {
+            if !should_activate && *self.settings.one_way() {
+                continue;
+            }
+
             let shuffle_mission = tree_from_tokens!(
                 &mut self.output_data; shuffle_event_source =>
                 : "mission", format!("zzzzz {kind_name}: {event_name}") ;
@@ -657,23 +1846,36 @@ impl SystemShuffler<'_> {
             let mission_to_offer = tree_from_tokens!(
                 &mut self.output_data; shuffle_event_source =>
                 : "to", "offer" ;
-                {
-                    : "has", INSTALLED ;
-                    :
-                        CURRENT_PRESET,
-                        if should_activate {
-                            "=="
-                        } else {
-                            "!="
-                        },
-                        preset_index
-                    ;
-                }
             );
 
             self.output_data
                 .push_child(shuffle_mission, mission_to_offer);
 
+            let installed = flag_condition(
+                &mut self.output_data,
+                shuffle_event_source,
+                *self.settings.numeric_conditions(),
+                (true, self.names.installed.as_str()),
+            );
+
+            self.output_data.push_child(mission_to_offer, installed);
+
+            let current_preset = tree_from_tokens!(
+                &mut self.output_data; shuffle_event_source =>
+                :
+                    self.names.current_preset.as_str(),
+                    if should_activate {
+                        "=="
+                    } else {
+                        "!="
+                    },
+                    preset_index
+                ;
+            );
+
+            self.output_data
+                .push_child(mission_to_offer, current_preset);
+
             let mission_on_offer = tree_from_tokens!(
                 &mut self.output_data; shuffle_event_source =>
                 : "on", "offer" ;
@@ -705,13 +1907,33 @@ impl SystemShuffler<'_> {
         }
     }
 
+    fn unconditional_branch(&mut self, source: SourceIndex, label: &str) -> NodeIndex {
+        let branch = tree_from_tokens!(
+            &mut self.output_data; source =>
+            : "branch", label ;
+        );
+
+        let always = flag_condition(
+            &mut self.output_data,
+            source,
+            *self.settings.numeric_conditions(),
+            (false, "never"),
+        );
+
+        self.output_data.push_child(branch, always);
+
+        branch
+    }
+
     fn conditional_events(
         &mut self,
         (source, parent): (SourceIndex, NodeIndex),
-        (should_activate, event_name_prefix, label_suffix): (bool, &str, &str),
+        (should_activate, label_suffix): (bool, &str),
         persistent_event_node_keys: &[&str],
-        preset_index: u8,
+        preset_index: usize,
     ) {
+        let (restore_name, activate_name) = self.preset_event_names(preset_index);
+
         let run_label = format!("has {preset_index} {label_suffix}");
         let skip_label = format!("not {preset_index} {label_suffix}");
 
@@ -719,19 +1941,13 @@ impl SystemShuffler<'_> {
             &mut self.output_data; source =>
             : "branch", run_label.as_str() ;
             {
-                : CURRENT_PRESET, "==", preset_index ;
+                : self.names.current_preset.as_str(), "==", preset_index ;
             }
         );
 
         self.output_data.push_child(parent, event_branch);
 
-        let event_branch = tree_from_tokens!(
-            &mut self.output_data; source =>
-            : "branch", skip_label.as_str() ;
-            {
-                : "not", "never" ;
-            }
-        );
+        let event_branch = self.unconditional_branch(source, skip_label.as_str());
 
         self.output_data.push_child(parent, event_branch);
 
@@ -742,12 +1958,13 @@ impl SystemShuffler<'_> {
 
         self.output_data.push_child(parent, event_label);
 
-        let event_action = tree_from_tokens!(
-            &mut self.output_data; source =>
-            : "action" ;
-            {
-                : "event", format!("{event_name_prefix} {preset_index}"), "0" ;
-            }
+        let event_action = self.preset_event_action(
+            source,
+            if should_activate {
+                activate_name.as_str()
+            } else {
+                restore_name.as_str()
+            },
         );
 
         if should_activate {
@@ -755,9 +1972,6 @@ impl SystemShuffler<'_> {
         }
 
         for &event_name in persistent_event_node_keys {
-            let restore_name = format!("{RESTORE_PREFIX} {preset_index}");
-            let activate_name = format!("{ACTIVATE_PREFIX} {preset_index}");
-
             let run_label = format!("has {preset_index} {label_suffix} {event_name}");
             let skip_label = format!("not {preset_index} {label_suffix} {event_name}");
 
@@ -766,7 +1980,7 @@ impl SystemShuffler<'_> {
                 (restore_name.as_str(), activate_name.as_str()),
                 should_activate,
                 source,
-                usize::from(preset_index),
+                preset_index,
             );
 
             let event_branch = tree_from_tokens!(
@@ -778,13 +1992,7 @@ impl SystemShuffler<'_> {
 
             self.output_data.push_child(parent, event_branch);
 
-            let event_branch = tree_from_tokens!(
-                &mut self.output_data; source =>
-                : "branch", skip_label.as_str() ;
-                {
-                    : "not", "never" ;
-                }
-            );
+            let event_branch = self.unconditional_branch(source, skip_label.as_str());
 
             self.output_data.push_child(parent, event_branch);
 
@@ -823,19 +2031,69 @@ impl SystemShuffler<'_> {
 
         self.output_data.push_child(parent, event_label);
 
-        if preset_index == *self.settings.max_presets() {
-            let blank_action = tree_from_tokens!(
+        if preset_index == self.preset_count() {
+            self.emit_noop_action(source, parent);
+        }
+    }
+
+    /// A `conversation` with one line of text for every line of `message`, since the game shows
+    /// each text node as its own paragraph
+    fn conversation(&mut self, source: SourceIndex, message: &str) -> NodeIndex {
+        let conversation = tree_from_tokens!(
+            &mut self.output_data; source =>
+            : "conversation" ;
+        );
+
+        for line in message
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            let text = tree_from_tokens!(
                 &mut self.output_data; source =>
-                : "action" ;
-                {
-                    : CURRENT_PRESET, "=", CURRENT_PRESET ;
-                }
+                : line ;
+            );
+
+            self.output_data.push_child(conversation, text);
+        }
+
+        conversation
+    }
+
+    /// A conversation can't end on a `label`, since there would be nothing for the branches
+    /// jumping to it to land on, so the last preset's final skip label is followed by an action
+    /// that leaves everything as it was
+    fn emit_noop_action(&mut self, source: SourceIndex, parent: NodeIndex) {
+        let noop_action = tree_from_tokens!(
+            &mut self.output_data; source =>
+            : "action" ;
+            {
+                : self.names.current_preset.as_str(), "=", self.names.current_preset.as_str() ;
+            }
+        );
+
+        self.output_data.push_child(parent, noop_action);
+    }
+
+    fn preset_event_action(&mut self, source: SourceIndex, event_name: &str) -> NodeIndex {
+        let event_action = tree_from_tokens!(
+            &mut self.output_data; source =>
+            : "action" ;
+        );
+
+        for preset_event_name in self.scoped_event_names(event_name) {
+            let preset_event = tree_from_tokens!(
+                &mut self.output_data; source =>
+                : "event", preset_event_name, "0" ;
             );
 
-            self.output_data.push_child(parent, blank_action);
+            self.output_data.push_child(event_action, preset_event);
         }
+
+        event_action
     }
 
+    #[allow(clippy::too_many_lines)]
     fn event<'a>(
         &mut self,
         data: &'a Data,
@@ -844,16 +2102,28 @@ impl SystemShuffler<'_> {
         system_swaps: &HashMap<&'a str, &'a str>,
         persistent_nodes: &PersistentOriginalNodes<'a>,
     ) {
+        let replacement_for = |original_kind: &str, original: &'a str| {
+            if original_kind == "system" {
+                system_swaps.get(original).map_or(original, |swap| swap)
+            } else {
+                original
+            }
+        };
+
         let mut persistent_node_keys = persistent_nodes.keys().copied().collect::<Vec<_>>();
 
-        persistent_node_keys.sort_unstable();
+        // the emitted nodes are named after the replacement, so sort by that to keep each file in
+        // alphabetical order by the system it modifies
+        persistent_node_keys.sort_unstable_by_key(|&(original_kind, original)| {
+            (
+                original_kind,
+                replacement_for(original_kind, original),
+                original,
+            )
+        });
 
         for (original_kind, original) in persistent_node_keys {
-            let replacement = if original_kind == "system" {
-                system_swaps.get(original).map_or(original, |swap| swap)
-            } else {
-                original
-            };
+            let replacement = replacement_for(original_kind, original);
 
             let (removals, additions) = self.modify_node(
                 (original_kind, original),
@@ -864,7 +2134,7 @@ impl SystemShuffler<'_> {
             );
 
             // do everything but links first in case `remove link` is one of the removals or additions
-            let modified_nodes = removals
+            let mut modified_nodes = removals
                 .iter()
                 .filter(|&&node_index| {
                     !self.output_node_is_additive_link((shuffle_event_source, node_index))
@@ -896,6 +2166,15 @@ impl SystemShuffler<'_> {
                 )
                 .collect::<Vec<_>>();
 
+            // a system that keeps its position stays in its own region, and so with its own music
+            if original_kind == "system"
+                && *self.settings.shuffle_positions()
+                && let Some((restore_music, activate_music)) =
+                    self.modify_music(original, replacement, shuffle_event_source)
+            {
+                modified_nodes.extend([(false, None, restore_music), (true, None, activate_music)]);
+            }
+
             if modified_nodes
                 .iter()
                 .any(|(_, known_parent, _)| known_parent.is_none())
@@ -943,6 +2222,34 @@ impl SystemShuffler<'_> {
         }
     }
 
+    /// Sets `replacement` to the music of the region around `original` it moved into, and back to
+    /// its own music on restore
+    /// Systems without music of their own are left alone, since the game has no way to clear it again
+    fn modify_music(
+        &mut self,
+        original: &str,
+        replacement: &str,
+        shuffle_event_source: SourceIndex,
+    ) -> Option<(NodeIndex, NodeIndex)> {
+        let own_music = self.system_music.get(replacement)?.clone();
+        let neighborhood_music = self.neighborhood_music.get(original)?.clone();
+
+        if original == replacement || own_music == neighborhood_music {
+            return None;
+        }
+
+        Some((
+            tree_from_tokens!(
+                &mut self.output_data; shuffle_event_source =>
+                : "music", own_music.as_str() ;
+            ),
+            tree_from_tokens!(
+                &mut self.output_data; shuffle_event_source =>
+                : "music", neighborhood_music.as_str() ;
+            ),
+        ))
+    }
+
     fn output_node_is_additive_link(
         &self,
         (source_index, node_index): (SourceIndex, NodeIndex),
@@ -974,22 +2281,31 @@ impl SystemShuffler<'_> {
             &mut self.output_data; shuffle_event_source =>
             : "and" ;
             {
-                : CURRENT_PRESET, "==", preset_index ;
-                :
-                    "has",
-                    format!("event: {event_name}")
-                ;
-                :
-                    "not",
-                    if should_activate {
-                        format!("event: {activate_name}: {event_name}")
-                    } else {
-                        format!("event: {restore_name}: {event_name}")
-                    }
-                ;
+                : self.names.current_preset.as_str(), "==", preset_index ;
             }
         );
 
+        for (is_set, flag) in [
+            (true, format!("event: {event_name}")),
+            (
+                false,
+                if should_activate {
+                    format!("event: {activate_name}: {event_name}")
+                } else {
+                    format!("event: {restore_name}: {event_name}")
+                },
+            ),
+        ] {
+            let flag = flag_condition(
+                &mut self.output_data,
+                shuffle_event_source,
+                *self.settings.numeric_conditions(),
+                (is_set, flag.as_str()),
+            );
+
+            self.output_data.push_child(condition, flag);
+        }
+
         let (action1, action2) = (
             tree_from_tokens!(
                 &mut self.output_data; shuffle_event_source =>
@@ -1035,8 +2351,8 @@ impl SystemShuffler<'_> {
         let backpatch_source = self.output_data.insert_source(String::new());
 
         for event_name in persistent_event_node_keys {
-            let mission_name = format!("aaaaa System Shuffler Initial Backpatch: {event_name}");
-            let restore_name = format!("{RESTORE_PREFIX} 0");
+            let mission_name = format!("{}: {event_name}", self.names.initial_backpatch);
+            let (restore_name, _) = self.preset_event_names(0);
 
             let backpatch_mission = tree_from_tokens!(
                 &mut self.output_data; backpatch_source  =>
@@ -1056,24 +2372,33 @@ impl SystemShuffler<'_> {
             let mission_to_offer = tree_from_tokens!(
                 &mut self.output_data; backpatch_source =>
                 : "to", "offer" ;
-                {
-                    : "and" ;
-                    {
-                        :
-                            "has",
-                            format!("event: {event_name}")
-                        ;
-                        :
-                            "not",
-                            format!("event: {mission_name}")
-                        ;
-                    }
-                }
             );
 
             self.output_data
                 .push_child(backpatch_mission, mission_to_offer);
 
+            let mission_to_offer_and = tree_from_tokens!(
+                &mut self.output_data; backpatch_source =>
+                : "and" ;
+            );
+
+            self.output_data
+                .push_child(mission_to_offer, mission_to_offer_and);
+
+            for (is_set, flag) in [
+                (true, format!("event: {event_name}")),
+                (false, format!("event: {mission_name}")),
+            ] {
+                let flag = flag_condition(
+                    &mut self.output_data,
+                    backpatch_source,
+                    *self.settings.numeric_conditions(),
+                    (is_set, flag.as_str()),
+                );
+
+                self.output_data.push_child(mission_to_offer_and, flag);
+            }
+
             let mission_on_offer = tree_from_tokens!(
                 &mut self.output_data; backpatch_source =>
                 : "on", "offer" ;
@@ -1101,6 +2426,7 @@ impl SystemShuffler<'_> {
         )
     }
 
+    #[allow(clippy::too_many_lines)]
     fn modify_node(
         &mut self,
         (original_kind, original): (&str, &str),
@@ -1109,16 +2435,45 @@ impl SystemShuffler<'_> {
         system_swaps: &HashMap<&str, &str>,
         persistent_nodes: &PersistentOriginalNodes<'_>,
     ) -> (Vec<NodeIndex>, Vec<NodeIndex>) {
+        if *self.settings.geometry_mode() != config::GeometryMode::Swap {
+            return self.modify_node_geometry(
+                (original_kind, original),
+                data,
+                shuffle_event_source,
+                persistent_nodes,
+            );
+        }
+
+        let replacement_objects = if *self.settings.shuffle_objects() && original_kind == "system" {
+            system_swaps
+                .get(original)
+                .and_then(|replacement| persistent_nodes.get(&("system", *replacement)))
+                .and_then(|nodes| nodes.get("object"))
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+        } else {
+            [].as_slice()
+        };
+
         let persistent_nodes = persistent_nodes
             .get(&(original_kind, original))
             .expect("Data must be verified in previous steps");
 
+        // the modifications are only sorted by their first two tokens, so the node kinds need a
+        // stable order for ties to come out the same for the same seed and data
+        let mut node_kinds = persistent_nodes
+            .iter()
+            .filter(|&(node_kind, _)| self.shuffles_node_kind(node_kind))
+            .collect::<Vec<_>>();
+
+        node_kinds.sort_unstable_by_key(|&(node_kind, _)| node_kind);
+
         let (mut restoration, mut activation) = (None, None);
 
         for should_activate in [false, true] {
             let mut modified_nodes = vec![];
 
-            for (node_kind, node_values) in persistent_nodes {
+            for &(node_kind, node_values) in &node_kinds {
                 let mut removed_all = false;
 
                 for node_value in node_values {
@@ -1148,6 +2503,9 @@ impl SystemShuffler<'_> {
                             ));
                         }
                         "link" | "unlink" => {
+                            // a wormhole's links are removed with a bare `remove link`, which drops
+                            // every link at once, so only the first one is needed; every link is
+                            // still added back one by one, since `is_adding` never breaks
                             if original_kind == "wormhole" && !is_adding {
                                 if removed_all {
                                     break;
@@ -1156,14 +2514,19 @@ impl SystemShuffler<'_> {
                                 removed_all = true;
                             }
 
-                            modified_nodes.push(self.modify_link(
-                                (node_kind, original_kind),
-                                data,
-                                node_value,
-                                shuffle_event_source,
-                                is_adding,
-                                system_swaps,
-                            ));
+                            modified_nodes.extend(
+                                self.modify_link(
+                                    (node_kind, original_kind),
+                                    data,
+                                    node_value,
+                                    shuffle_event_source,
+                                    is_adding,
+                                    system_swaps,
+                                    (original_kind == "system")
+                                        .then(|| system_swaps.get(original).copied())
+                                        .flatten(),
+                                ),
+                            );
                         }
                         "object" => {
                             modified_nodes.push(self.modify_object(
@@ -1186,6 +2549,22 @@ impl SystemShuffler<'_> {
                 }
             }
 
+            // the replacement's own objects make way for the ones moving in, so they are taken out on
+            // activation and put back on restore, the opposite of what the moving objects do
+            for node_value in replacement_objects {
+                let is_adding = (!should_activate
+                    && matches!(node_value.0, NodeAction::Add | NodeAction::ClearAdd))
+                    || (should_activate
+                        && matches!(node_value.0, NodeAction::Remove | NodeAction::ClearRemove));
+
+                modified_nodes.push(self.modify_object(
+                    data,
+                    node_value,
+                    shuffle_event_source,
+                    is_adding,
+                ));
+            }
+
             modified_nodes.sort_by(|a, b| self.sort_modifications_by(shuffle_event_source, a, b));
 
             if should_activate {
@@ -1218,24 +2597,126 @@ impl SystemShuffler<'_> {
                 .and_then(|token| self.output_data.get_lexeme(source, token))
                 .expect("Only nodes with at least one token should be modified"),
         ) {
-            (a_lexeme, b_lexeme) if a_lexeme == b_lexeme => self
-                .output_data
-                .get_tokens(*a)
-                .and_then(|tokens| tokens.get(1))
-                .and_then(|token| self.output_data.get_lexeme(source, token))
-                .cmp(
-                    &self
-                        .output_data
-                        .get_tokens(*b)
-                        .and_then(|tokens| tokens.get(1))
-                        .and_then(|token| self.output_data.get_lexeme(source, token)),
-                ),
+            // with `sort_links`, every token breaks ties, so links to different systems come out
+            // in alphabetical order instead of the order they were uploaded in
+            (a_lexeme, b_lexeme) if a_lexeme == b_lexeme => {
+                let lexemes = |node_index: NodeIndex| {
+                    self.output_data
+                        .get_tokens(node_index)
+                        .unwrap_or_default()
+                        .iter()
+                        .skip(1)
+                        .take(if *self.settings.sort_links() {
+                            usize::MAX
+                        } else {
+                            1
+                        })
+                        .filter_map(|token| self.output_data.get_lexeme(source, token))
+                        .collect::<Vec<_>>()
+                };
+
+                lexemes(*a).cmp(&lexemes(*b))
+            }
             (_, "add" | "link") | ("remove" | "unlink", _) => Ordering::Less,
             ("add" | "link", _) | (_, "remove" | "unlink") => Ordering::Greater,
             (a, b) => a.cmp(b),
         }
     }
 
+    /// Positions and links can each be left out of the swaps, keeping either how the map looks or
+    /// where its jumps lead; the restore event skips the same kinds, since they never change
+    fn shuffles_node_kind(&self, node_kind: &str) -> bool {
+        match node_kind {
+            "pos" => *self.settings.shuffle_positions(),
+            "link" | "unlink" => *self.settings.shuffle_links(),
+            _ => true,
+        }
+    }
+
+    /// Rotated and mirrored presets keep every system's name, links, and everything else as they are,
+    /// so the only modifications are each moved system's `pos`, put back as it was on restore
+    fn modify_node_geometry(
+        &mut self,
+        (original_kind, original): (&str, &str),
+        data: &Data,
+        shuffle_event_source: SourceIndex,
+        persistent_nodes: &PersistentOriginalNodes<'_>,
+    ) -> (Vec<NodeIndex>, Vec<NodeIndex>) {
+        let (mut restoration, mut activation) = (vec![], vec![]);
+
+        if original_kind != "system"
+            || !self
+                .geometry
+                .as_ref()
+                .is_some_and(|geometry| geometry.moves(original))
+        {
+            return (restoration, activation);
+        }
+
+        for node_value in persistent_nodes
+            .get(&(original_kind, original))
+            .and_then(|nodes| nodes.get("pos"))
+            .into_iter()
+            .flatten()
+        {
+            restoration.push(self.modify_copy(data, node_value, shuffle_event_source));
+            activation.push(self.modify_pos(data, node_value, shuffle_event_source));
+        }
+
+        (restoration, activation)
+    }
+
+    /// Like `modify_copy`, but with the x and y moved by the preset's transform, so the galaxy turns
+    /// or flips as one while any tokens past them are kept
+    fn modify_pos(
+        &mut self,
+        data: &Data,
+        node_value: &(NodeAction, SourceIndex, NodeIndex),
+        shuffle_event_source: SourceIndex,
+    ) -> NodeIndex {
+        let Some((x, y)) = node_position(data, (node_value.1, node_value.2))
+            .zip(self.geometry.as_ref())
+            .map(|(position, geometry)| self.preset_transform.apply(geometry.center(), position))
+        else {
+            return self.modify_copy(data, node_value, shuffle_event_source);
+        };
+
+        let lexemes = data
+            .get_tokens(node_value.2)
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter_map(|(token_index, token)| match token_index {
+                1 => Some(geometry::coordinate(x)),
+                2 => Some(geometry::coordinate(y)),
+                _ => data
+                    .get_lexeme(node_value.1, token)
+                    .map(ToString::to_string),
+            })
+            .collect::<Vec<_>>();
+
+        let output_node = self.output_data.insert_node(Node::Some { tokens: vec![] });
+
+        for lexeme in lexemes {
+            if let Some((span_start, span_end)) = self
+                .output_data
+                .push_source(shuffle_event_source, lexeme.as_str())
+            {
+                self.output_data.push_token(
+                    output_node,
+                    Spanned::new(
+                        Token::Symbol,
+                        Span::new(shuffle_event_source.index(), span_start, span_end),
+                    ),
+                );
+            }
+        }
+
+        output_node
+    }
+
+    /// Copies the whole node, every token and child included, so a `pos` with anything past its
+    /// x and y keeps it instead of being rebuilt from just the two coordinates
     fn modify_copy(
         &mut self,
         data: &Data,
@@ -1275,17 +2756,66 @@ impl SystemShuffler<'_> {
                 : zeroed, "0" ;
             )
         }
-    }
+    }
+
+    /// Bare `link` and `unlink` directives in events all share the `("link", "")` or `("unlink", "")`
+    /// key, so each one comes through here on its own with both of its systems swapped; an `unlink`
+    /// is applied as is on activation and linked back up on restore
+    /// Self-links are invalid in the game, and the swaps should never produce one since they are a
+    /// one-to-one mapping, but any that slip through (say from a self-link in the uploaded data) are
+    /// dropped rather than emitted
+    /// Pinned systems map to themselves, so their own links still get their swapped neighbors
+    #[allow(clippy::too_many_arguments)]
+    fn modify_link(
+        &mut self,
+        (node_kind, original_kind): (&str, &str),
+        data: &Data,
+        node_value: &(NodeAction, SourceIndex, NodeIndex),
+        shuffle_event_source: SourceIndex,
+        is_adding: bool,
+        system_swaps: &HashMap<&str, &str>,
+        owner: Option<&str>,
+    ) -> Option<NodeIndex> {
+        let mut targets = if original_kind != "wormhole" || is_adding {
+            data.get_tokens(node_value.2)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|t| data.get_lexeme(node_value.1, t))
+                .skip_while(|l| *l != node_kind)
+                .skip(1)
+                .map(|lexeme| {
+                    *system_swaps
+                        .get(lexeme)
+                        .expect("Link data must be verified in previous steps")
+                })
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        // links go both ways, so the order of a link's two systems doesn't matter to the game
+        if *self.settings.sort_links() {
+            targets.sort_unstable();
+        }
+
+        let self_link = owner.map_or_else(
+            || targets.windows(2).any(|pair| pair[0] == pair[1]),
+            |owner| targets.contains(&owner),
+        );
+
+        if self_link {
+            let warning = format!(
+                "Dropped a link from {} to itself",
+                owner
+                    .or_else(|| targets.first().copied())
+                    .unwrap_or_default()
+            );
+
+            self.warn(warning);
+
+            return None;
+        }
 
-    fn modify_link(
-        &mut self,
-        (node_kind, original_kind): (&str, &str),
-        data: &Data,
-        node_value: &(NodeAction, SourceIndex, NodeIndex),
-        shuffle_event_source: SourceIndex,
-        is_adding: bool,
-        system_swaps: &HashMap<&str, &str>,
-    ) -> NodeIndex {
         let modified_link = if is_adding {
             match original_kind {
                 "link" | "unlink" => tree_from_tokens!(
@@ -1310,36 +2840,22 @@ impl SystemShuffler<'_> {
             }
         };
 
-        if original_kind != "wormhole" || is_adding {
-            for lexeme in data
-                .get_tokens(node_value.2)
-                .unwrap_or_default()
-                .iter()
-                .filter_map(|t| data.get_lexeme(node_value.1, t))
-                .skip_while(|l| *l != node_kind)
-                .skip(1)
-            {
-                let (start, end) = self
-                    .output_data
-                    .push_source(
-                        shuffle_event_source,
-                        system_swaps
-                            .get(lexeme)
-                            .expect("Link data must be verified in previous steps"),
-                    )
-                    .expect("Link data must be verified in previous steps");
-
-                self.output_data.push_token(
-                    modified_link,
-                    Spanned::new(
-                        Token::Symbol,
-                        Span::new(shuffle_event_source.index(), start, end),
-                    ),
-                );
-            }
+        for target in targets {
+            let (start, end) = self
+                .output_data
+                .push_source(shuffle_event_source, target)
+                .expect("Link data must be verified in previous steps");
+
+            self.output_data.push_token(
+                modified_link,
+                Spanned::new(
+                    Token::Symbol,
+                    Span::new(shuffle_event_source.index(), start, end),
+                ),
+            );
         }
 
-        modified_link
+        Some(modified_link)
     }
 
     fn modify_object(
@@ -1371,6 +2887,8 @@ impl SystemShuffler<'_> {
             )
             .expect("Object data must be verified in previous steps");
 
+        // only the top-level object gets the `add`/`remove` modifier, any moons are copied as
+        // plain nested `object` nodes so they move along with their parent
         if let Some(Node::Some { tokens } | Node::Parent { tokens, .. }) =
             self.output_data.get_mut_node(modified_object)
         {
@@ -1381,7 +2899,8 @@ impl SystemShuffler<'_> {
 
             if matches!(
                 data.get_tokens(node_value.2)
-                    .and_then(|tokens| data.get_lexeme(node_value.1, &tokens[0])),
+                    .and_then(|tokens| tokens.first())
+                    .and_then(|token| data.get_lexeme(node_value.1, token)),
                 Some("add" | "remove")
             ) && let Some(modifier) = tokens.first_mut()
             {
@@ -1394,6 +2913,8 @@ impl SystemShuffler<'_> {
         modified_object
     }
 
+    /// `is_adding` already has the polarity of `remove` patches flipped, so a `remove hidden` is
+    /// added back as a plain `hidden` on restore, and taken out again on activation
     fn modify_other(
         &mut self,
         node_kind: &str,
@@ -1425,6 +2946,44 @@ impl SystemShuffler<'_> {
                 tokens.remove(0);
             }
 
+            modified_copy
+        } else if matches!(
+            data.get_tokens(node_value.2)
+                .and_then(|tokens| tokens.first())
+                .and_then(|token| data.get_lexeme(node_value.1, token)),
+            Some("add" | "remove")
+        ) && (data.get_tokens(node_value.2).unwrap_or_default().len() >= 3
+            || !data
+                .get_children(node_value.2)
+                .unwrap_or_default()
+                .is_empty())
+        {
+            // an `add` or `remove` patch of one specific entry (like `add hazard X`) only takes that
+            // entry back out, instead of clearing every entry of its kind
+            let modified_copy = generators::copy_node(
+                data,
+                (node_value.1, node_value.2),
+                &mut self.output_data,
+                shuffle_event_source,
+                [].as_slice(),
+            )
+            .expect("Data must be verified in previous steps");
+
+            let (start, end) = self
+                .output_data
+                .push_source(shuffle_event_source, "remove")
+                .expect("Data must be verified in previous steps");
+
+            if let Some(Node::Some { tokens } | Node::Parent { tokens, .. }) =
+                self.output_data.get_mut_node(modified_copy)
+                && let Some(modifier) = tokens.first_mut()
+            {
+                *modifier = Spanned::new(
+                    Token::Symbol,
+                    Span::new(shuffle_event_source.index(), start, end),
+                );
+            }
+
             modified_copy
         } else {
             tree_from_tokens!(
@@ -1435,6 +2994,250 @@ impl SystemShuffler<'_> {
     }
 }
 
+/// The systems that swap places, which leaves out pinned systems and those outside the region
+fn shuffled_system_names<'a>(
+    data: &Data,
+    system_names: &[&'a str],
+    settings: &config::SystemShufflerConfig,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let regions = settings
+        .region()
+        .as_deref()
+        .map(|region| (region, system_regions(data)));
+
+    swaps::shuffled_names(
+        system_names,
+        settings.pinned_systems(),
+        regions.as_ref().map(|(region, regions)| (*region, regions)),
+    )
+}
+
+/// The music of the last definition of every system that sets one
+fn system_music(data: &Data) -> BTreeMap<&str, &str> {
+    let mut system_music = BTreeMap::new();
+
+    for (source_index, node_index) in node_path_iter!(data; "system") {
+        if let Some(system_name) = data
+            .get_tokens(node_index)
+            .and_then(|tokens| tokens.get(1))
+            .and_then(|t| data.get_lexeme(source_index, t))
+            && let Some(music) = node_path_iter!(data => (source_index, node_index); "music")
+                .filter_map(|(_, music)| {
+                    data.get_tokens(music)
+                        .and_then(|tokens| tokens.get(1))
+                        .and_then(|t| data.get_lexeme(source_index, t))
+                })
+                .last()
+        {
+            system_music.insert(system_name, music);
+        }
+    }
+
+    system_music
+}
+
+/// The music most of the systems in each system's region play, ties going to the first alphabetically
+fn neighborhood_music<'a>(
+    data: &'a Data,
+    system_music: &BTreeMap<&'a str, &'a str>,
+) -> BTreeMap<&'a str, &'a str> {
+    let system_regions = system_regions(data);
+
+    let mut region_music_counts = BTreeMap::<&str, BTreeMap<&str, usize>>::new();
+
+    for (system_name, music) in system_music {
+        if let Some(region) = system_regions.get(system_name) {
+            *region_music_counts
+                .entry(region)
+                .or_default()
+                .entry(music)
+                .or_default() += 1;
+        }
+    }
+
+    let region_music = region_music_counts
+        .into_iter()
+        .filter_map(|(region, music_counts)| {
+            music_counts
+                .into_iter()
+                .max_by(|(a_music, a_count), (b_music, b_count)| {
+                    a_count.cmp(b_count).then_with(|| b_music.cmp(a_music))
+                })
+                .map(|(music, _)| (region, music))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    system_regions
+        .into_iter()
+        .filter_map(|(system_name, region)| Some((system_name, *region_music.get(region)?)))
+        .collect()
+}
+
+/// Every system's region, which is the government it belongs to (or `Uninhabited`, like the game)
+#[must_use]
+pub fn system_regions(data: &Data) -> BTreeMap<&str, &str> {
+    let mut system_regions = BTreeMap::new();
+
+    for (source_index, node_index) in node_path_iter!(data; "system") {
+        if let Some(system_name) = data
+            .get_tokens(node_index)
+            .and_then(|tokens| tokens.get(1))
+            .and_then(|t| data.get_lexeme(source_index, t))
+        {
+            let government = node_path_iter!(data => (source_index, node_index); "government")
+                .filter_map(|(_, government)| {
+                    data.get_tokens(government)
+                        .and_then(|tokens| tokens.get(1))
+                        .and_then(|t| data.get_lexeme(source_index, t))
+                })
+                .last();
+
+            match (government, system_regions.get(system_name)) {
+                (Some(government), _) => {
+                    system_regions.insert(system_name, government);
+                }
+                (None, None) => {
+                    system_regions.insert(system_name, "Uninhabited");
+                }
+                (None, Some(_)) => {}
+            }
+        }
+    }
+
+    system_regions
+}
+
+#[must_use]
+pub fn planet_systems(data: &Data) -> BTreeMap<&str, Vec<&str>> {
+    let mut planet_systems = BTreeMap::new();
+
+    for (source_index, node_index) in node_path_iter!(data; "system") {
+        if let Some(system_name) = data
+            .get_tokens(node_index)
+            .and_then(|tokens| tokens.get(1))
+            .and_then(|t| data.get_lexeme(source_index, t))
+        {
+            find_planets_from_object(
+                data,
+                system_name,
+                (source_index, node_index),
+                &mut planet_systems,
+            );
+        }
+    }
+
+    planet_systems
+}
+
+/// The systems that differ between two uploads, like the game data before and after an update
+pub struct SystemDiff<'a> {
+    pub added: Vec<&'a str>,
+    pub removed: Vec<&'a str>,
+    /// Systems in both uploads whose links changed
+    pub changed: Vec<&'a str>,
+}
+
+/// Compares the systems the shuffler would detect in two uploads, by name and by what they link to
+#[must_use]
+pub fn diff_systems<'a>(before: &'a Data, after: &'a Data) -> SystemDiff<'a> {
+    let before_links = system_links(before);
+    let after_links = system_links(after);
+
+    SystemDiff {
+        added: after_links
+            .keys()
+            .filter(|system_name| !before_links.contains_key(*system_name))
+            .copied()
+            .collect(),
+        removed: before_links
+            .keys()
+            .filter(|system_name| !after_links.contains_key(*system_name))
+            .copied()
+            .collect(),
+        changed: before_links
+            .iter()
+            .filter(|(system_name, links)| {
+                after_links
+                    .get(*system_name)
+                    .is_some_and(|after_links| after_links != *links)
+            })
+            .map(|(system_name, _)| *system_name)
+            .collect(),
+    }
+}
+
+/// Every detected system and the systems its own definitions link it to, outside of any events
+fn system_links(data: &Data) -> BTreeMap<&str, BTreeSet<&str>> {
+    let detection = detect(data, false, [].as_slice());
+
+    detection
+        .system_names
+        .iter()
+        .map(|&system_name| {
+            let mut links = BTreeSet::new();
+
+            for &(action, source_index, node_index) in detection
+                .persistent_nodes
+                .get(&("system", system_name))
+                .and_then(|original_nodes| original_nodes.get("link"))
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+            {
+                let mut lexemes = data
+                    .get_tokens(node_index)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|token| data.get_lexeme(source_index, token))
+                    .skip_while(|&lexeme| lexeme != "link")
+                    .skip(1);
+
+                match (action, lexemes.next()) {
+                    (NodeAction::Remove | NodeAction::ClearRemove, Some(link)) => {
+                        links.remove(link);
+                    }
+                    (NodeAction::Remove | NodeAction::ClearRemove, None) => links.clear(),
+                    (NodeAction::Add | NodeAction::ClearAdd, Some(link)) => {
+                        links.insert(link);
+                    }
+                    (NodeAction::Add | NodeAction::ClearAdd, None) => {}
+                }
+            }
+
+            (system_name, links)
+        })
+        .collect()
+}
+
+fn find_planets_from_object<'a>(
+    data: &'a Data,
+    system_name: &'a str,
+    (source_index, node_index): (SourceIndex, NodeIndex),
+    planet_systems: &mut BTreeMap<&'a str, Vec<&'a str>>,
+) {
+    for child in data.filter_children(source_index, node_index, |source_index, tokens| {
+        matches!(
+            tokens
+                .first()
+                .and_then(|t| data.get_lexeme(source_index, t)),
+            Some("object")
+        )
+    }) {
+        if let Some(object_name) = data
+            .get_tokens(child)
+            .and_then(|tokens| tokens.get(1))
+            .and_then(|t| data.get_lexeme(source_index, t))
+        {
+            let systems = planet_systems.entry(object_name).or_default();
+
+            if !systems.contains(&system_name) {
+                systems.push(system_name);
+            }
+        }
+
+        find_planets_from_object(data, system_name, (source_index, child), planet_systems);
+    }
+}
+
 fn find_wormholes_from_planets<'a>(data: &'a Data, wormholes: &mut HashSet<&'a str>) {
     node_path_iter!(data; "planet")
         .filter(|(source_index, node_index)| {
@@ -1451,11 +3254,13 @@ fn find_wormholes_from_planets<'a>(data: &'a Data, wormholes: &mut HashSet<&'a s
                     .is_some()
         })
         .for_each(|(source_index, node_index)| {
-            wormholes.insert(
-                data.get_tokens(node_index)
-                    .and_then(|tokens| data.get_lexeme(source_index, &tokens[1]))
-                    .expect("The iterator should have a filter applied to ensure only nodes with at least two tokens make it here"),
-            );
+            if let Some(wormhole) = data
+                .get_tokens(node_index)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token))
+            {
+                wormholes.insert(wormhole);
+            }
         });
 }
 
@@ -1465,6 +3270,18 @@ fn find_wormholes_from_system<'a>(
     (depth, planets, wormholes): (u64, &mut HashMap<&'a str, &'a str>, &mut HashSet<&'a str>),
     persistent_nodes: &mut PersistentOriginalNodes<'a>,
 ) -> bool {
+    if depth >= generators::MAX_OBJECT_DEPTH {
+        generators::log(
+            format!(
+                "Objects in system `{system_name}` are nested more than {} deep, so the deeper ones were ignored",
+                generators::MAX_OBJECT_DEPTH
+            )
+            .as_str(),
+        );
+
+        return false;
+    }
+
     data.filter_children(source_index, node_index, |source_index, tokens| {
         let key_index = usize::from(matches!(
             tokens
@@ -1508,12 +3325,14 @@ fn find_wormholes_from_system<'a>(
             persistent_nodes,
         );
 
-        if depth == 0 && is_wormhole {
-            let action = match data
+        if depth == 0
+            && is_wormhole
+            && let Some(modifier) = data
                 .get_tokens(child)
-                .and_then(|tokens| data.get_lexeme(source_index, &tokens[0]))
-                .expect("The iterator should use a filter to ensure only nodes with at least one token (or two, if starting with add/remove) are allowed")
-            {
+                .and_then(|tokens| tokens.first())
+                .and_then(|token| data.get_lexeme(source_index, token))
+        {
+            let action = match modifier {
                 "remove" => {
                     if data.get_tokens(child).unwrap_or_default().len() >= 2
                         || !data.get_children(child).unwrap_or_default().is_empty()
@@ -1542,8 +3361,12 @@ fn find_wormholes_from_system<'a>(
 fn find_persistent_event_nodes<'a>(
     data: &'a Data,
     system_names: &mut HashSet<&'a str>,
-    planets: &mut HashMap<&'a str, &'a str>,
-    wormholes: &mut HashSet<&'a str>,
+    (detect_wormholes, planets, wormholes): (
+        bool,
+        &mut HashMap<&'a str, &'a str>,
+        &mut HashSet<&'a str>,
+    ),
+    optional_node_kinds: &[&'static str],
 ) -> (Vec<&'a str>, HashMap<&'a str, PersistentOriginalNodes<'a>>) {
     let mut persistent_event_node_keys = vec![];
     let mut persistent_event_nodes = HashMap::new();
@@ -1560,10 +3383,13 @@ fn find_persistent_event_nodes<'a>(
                     .is_some()
         })
     {
-        let event_name = data
+        let Some(event_name) = data
             .get_tokens(node_index)
-            .and_then(|tokens| data.get_lexeme(source_index, &tokens[1]))
-            .expect("The iterator should have a filter to ensure only nodes with at least two tokens make it here");
+            .and_then(|tokens| tokens.get(1))
+            .and_then(|token| data.get_lexeme(source_index, token))
+        else {
+            continue;
+        };
 
         let mut event_map = HashMap::new();
 
@@ -1571,8 +3397,9 @@ fn find_persistent_event_nodes<'a>(
             data,
             node_path_iter!(&data => (source_index, node_index); "system" | "wormhole" | "link" | "unlink"),
             system_names,
-            (planets, wormholes),
+            (detect_wormholes, planets, wormholes),
             &mut event_map,
+            optional_node_kinds,
         );
 
         if !event_map.is_empty() {
@@ -1584,14 +3411,250 @@ fn find_persistent_event_nodes<'a>(
     persistent_event_node_keys.sort_unstable();
 
     (
-        persistent_event_node_keys
-            .into_iter()
-            .map(|(_, event_name)| event_name)
-            .collect::<Vec<_>>(),
+        chronological_event_order(
+            data,
+            persistent_event_node_keys
+                .into_iter()
+                .map(|(_, event_name)| event_name)
+                .collect::<Vec<_>>()
+                .as_slice(),
+        ),
         persistent_event_nodes,
     )
 }
 
+/// Orders the uploaded events the way they are likely to happen in a playthrough, since every
+/// preset activates their copies in this order
+/// An event with a `date` comes after any with an earlier one, and an event a mission triggers
+/// comes after every event the mission needs to have happened before it's offered
+/// Events with nothing to go by keep the order they were uploaded in, which is also what breaks
+/// any cycle between them
+fn chronological_event_order<'a>(data: &Data, event_names: &[&'a str]) -> Vec<&'a str> {
+    let event_indices = event_names
+        .iter()
+        .enumerate()
+        .map(|(event_index, &event_name)| (event_name, event_index))
+        .collect::<HashMap<_, _>>();
+
+    // the events each event has to come after
+    let mut earlier_events = vec![BTreeSet::new(); event_names.len()];
+
+    let mut event_dates = vec![None; event_names.len()];
+
+    for (source_index, node_index) in node_path_iter!(&data; "event") {
+        if let Some(&event_index) = data
+            .get_tokens(node_index)
+            .and_then(|tokens| tokens.get(1))
+            .and_then(|token| data.get_lexeme(source_index, token))
+            .and_then(|event_name| event_indices.get(event_name))
+            && let Some(event_date) = node_path_iter!(&data => (source_index, node_index); "date")
+                .find_map(|(_, date)| event_date(data, (source_index, date)))
+        {
+            event_dates[event_index] = Some(event_date);
+        }
+    }
+
+    for (later_index, later_date) in event_dates.iter().enumerate() {
+        for (earlier_index, earlier_date) in event_dates.iter().enumerate() {
+            if let (Some(earlier_date), Some(later_date)) = (earlier_date, later_date)
+                && earlier_date < later_date
+            {
+                earlier_events[later_index].insert(earlier_index);
+            }
+        }
+    }
+
+    for (source_index, mission) in node_path_iter!(&data; "mission") {
+        let needed_events = node_path_iter!(&data => (source_index, mission); "to")
+            .filter(|&(_, to)| {
+                data.get_tokens(to)
+                    .and_then(|tokens| tokens.get(1))
+                    .and_then(|token| data.get_lexeme(source_index, token))
+                    == Some("offer")
+            })
+            .flat_map(|(_, to_offer)| subtree_lexemes(data, (source_index, to_offer)))
+            .filter_map(|lexeme| lexeme.strip_prefix("event: "))
+            .filter_map(|event_name| event_indices.get(event_name).copied())
+            .collect::<Vec<_>>();
+
+        for (_, on) in node_path_iter!(&data => (source_index, mission); "on") {
+            for (_, event) in node_path_iter!(&data => (source_index, on); "event") {
+                if let Some(&triggered_index) = data
+                    .get_tokens(event)
+                    .and_then(|tokens| tokens.get(1))
+                    .and_then(|token| data.get_lexeme(source_index, token))
+                    .and_then(|event_name| event_indices.get(event_name))
+                {
+                    earlier_events[triggered_index].extend(
+                        needed_events
+                            .iter()
+                            .filter(|&&needed_index| needed_index != triggered_index),
+                    );
+                }
+            }
+        }
+    }
+
+    let mut is_placed = vec![false; event_names.len()];
+    let mut ordered_names = Vec::with_capacity(event_names.len());
+
+    while ordered_names.len() < event_names.len() {
+        let unplaced = || (0..event_names.len()).filter(|&event_index| !is_placed[event_index]);
+
+        let Some(next_index) = unplaced()
+            .find(|&event_index| {
+                earlier_events[event_index]
+                    .iter()
+                    .all(|&earlier_index| is_placed[earlier_index])
+            })
+            .or_else(|| unplaced().next())
+        else {
+            break;
+        };
+
+        is_placed[next_index] = true;
+        ordered_names.push(event_names[next_index]);
+    }
+
+    ordered_names
+}
+
+/// An event's `date day month year`, as `(year, month, day)` so dates compare in order
+fn event_date(
+    data: &Data,
+    (source_index, node_index): (SourceIndex, NodeIndex),
+) -> Option<(u32, u32, u32)> {
+    let part = |token_index: usize| {
+        data.get_tokens(node_index)?
+            .get(token_index)
+            .and_then(|token| data.get_lexeme(source_index, token))?
+            .parse::<u32>()
+            .ok()
+    };
+
+    Some((part(3)?, part(2)?, part(1)?))
+}
+
+/// Every lexeme in a node and all of its children, for finding conditions wherever they're nested
+fn subtree_lexemes(data: &Data, (source_index, node_index): (SourceIndex, NodeIndex)) -> Vec<&str> {
+    let mut lexemes = data
+        .get_tokens(node_index)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|token| data.get_lexeme(source_index, token))
+        .collect::<Vec<_>>();
+
+    for &child in data.get_children(node_index).unwrap_or_default() {
+        lexemes.extend(subtree_lexemes(data, (source_index, child)));
+    }
+
+    lexemes
+}
+
+fn find_undefined_systems<'a>(
+    data: &'a Data,
+    system_names: &[&str],
+    persistent_nodes: &PersistentOriginalNodes<'a>,
+    persistent_event_nodes: &HashMap<&'a str, PersistentOriginalNodes<'a>>,
+) -> Vec<&'a str> {
+    let mut undefined_systems = persistent_event_nodes
+        .values()
+        .chain([persistent_nodes])
+        .flat_map(HashMap::values)
+        .flat_map(|original_nodes| {
+            ["link", "unlink"].into_iter().flat_map(|node_kind| {
+                original_nodes
+                    .get(node_kind)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .flat_map(move |&(_, source_index, node_index)| {
+                        data.get_tokens(node_index)
+                            .unwrap_or_default()
+                            .iter()
+                            .filter_map(move |t| data.get_lexeme(source_index, t))
+                            .skip_while(move |l| *l != node_kind)
+                            .skip(1)
+                    })
+            })
+        })
+        .filter(|lexeme| system_names.binary_search(lexeme).is_err())
+        .collect::<Vec<_>>();
+
+    undefined_systems.sort_unstable();
+    undefined_systems.dedup();
+
+    undefined_systems
+}
+
+/// Splits the persistent nodes by the kind of root node they belong to (`system`, `wormhole`, ...),
+/// so each kind can be restored or activated without touching the others
+fn split_by_kind<'a>(
+    persistent_nodes: &PersistentOriginalNodes<'a>,
+) -> BTreeMap<&'a str, PersistentOriginalNodes<'a>> {
+    let mut kind_layout = BTreeMap::new();
+
+    for (&key, original_nodes) in persistent_nodes {
+        kind_layout
+            .entry(key.0)
+            .or_insert_with(HashMap::new)
+            .insert(key, original_nodes.clone());
+    }
+
+    kind_layout
+}
+
+/// Splits the persistent nodes by the region (government) of the system they belong to, so every
+/// government's part of a preset can be written into its own folder
+/// Wormholes and root-level links don't belong to any one system, so they get a group of their own
+fn split_by_government<'a>(
+    data: &'a Data,
+    persistent_nodes: &PersistentOriginalNodes<'a>,
+) -> BTreeMap<&'a str, PersistentOriginalNodes<'a>> {
+    let system_regions = system_regions(data);
+
+    let mut government_layout = BTreeMap::new();
+
+    for (&key, original_nodes) in persistent_nodes {
+        let government = match key {
+            ("system", system_name) => system_regions
+                .get(system_name)
+                .copied()
+                .unwrap_or("Uninhabited"),
+            _ => "Other",
+        };
+
+        government_layout
+            .entry(government)
+            .or_insert_with(HashMap::new)
+            .insert(key, original_nodes.clone());
+    }
+
+    government_layout
+}
+
+fn split_by_source<'a>(
+    persistent_nodes: &PersistentOriginalNodes<'a>,
+) -> BTreeMap<usize, PersistentOriginalNodes<'a>> {
+    let mut source_layout = BTreeMap::new();
+
+    for (&key, original_nodes) in persistent_nodes {
+        let source = original_nodes
+            .values()
+            .flatten()
+            .map(|(_, source_index, _)| source_index.index())
+            .min()
+            .unwrap_or_default();
+
+        source_layout
+            .entry(source)
+            .or_insert_with(HashMap::new)
+            .insert(key, original_nodes.clone());
+    }
+
+    source_layout
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum NodeAction {
     Remove,
@@ -1600,6 +3663,17 @@ enum NodeAction {
     ClearAdd,
 }
 
+impl NodeAction {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Remove => "remove",
+            Self::ClearRemove => "clear remove",
+            Self::Add => "add",
+            Self::ClearAdd => "clear add",
+        }
+    }
+}
+
 type PersistentOriginalNodes<'a> = HashMap<(&'a str, &'a str), OriginalNodes<'a>>;
 
 type OriginalNodes<'a> = HashMap<&'a str, Vec<(NodeAction, SourceIndex, NodeIndex)>>;
@@ -1681,32 +3755,40 @@ fn data_from_node<'a>(
     data: &'a Data,
     nodes: impl Iterator<Item = (SourceIndex, NodeIndex)>,
     system_names: &mut HashSet<&'a str>,
-    (planets, wormholes): (&mut HashMap<&'a str, &'a str>, &mut HashSet<&'a str>),
+    (detect_wormholes, planets, wormholes): (
+        bool,
+        &mut HashMap<&'a str, &'a str>,
+        &mut HashSet<&'a str>,
+    ),
     persistent_nodes: &mut PersistentOriginalNodes<'a>,
+    optional_node_kinds: &[&'static str],
 ) {
     for (source_index, node_index) in
         nodes.filter(|(_, node_index)| data.get_tokens(*node_index).unwrap_or_default().len() >= 2)
     {
-        let original_node_kind = data
-            .get_tokens(node_index)
-            .and_then(|tokens| data.get_lexeme(source_index, &tokens[0]))
-            .expect("The iterator should use a filter to ensure only nodes with at least two tokens make it this far");
-
-        let original_node_name = data
-            .get_tokens(node_index)
-            .and_then(|tokens| data.get_lexeme(source_index, &tokens[1]))
-            .expect("The iterator should use a filter to ensure only nodes with at least two tokens make it this far");
+        let (Some(original_node_kind), Some(original_node_name)) = (
+            data.get_tokens(node_index)
+                .and_then(|tokens| tokens.first())
+                .and_then(|token| data.get_lexeme(source_index, token)),
+            data.get_tokens(node_index)
+                .and_then(|tokens| tokens.get(1))
+                .and_then(|token| data.get_lexeme(source_index, token)),
+        ) else {
+            continue;
+        };
 
         match original_node_kind {
             "system" => {
                 system_names.insert(original_node_name);
 
-                find_wormholes_from_system(
-                    data,
-                    (original_node_name, source_index, node_index),
-                    (0, planets, wormholes),
-                    persistent_nodes,
-                );
+                if detect_wormholes {
+                    find_wormholes_from_system(
+                        data,
+                        (original_node_name, source_index, node_index),
+                        (0, planets, wormholes),
+                        persistent_nodes,
+                    );
+                }
             }
             "link" => {
                 persistent_nodes.persist(
@@ -1727,7 +3809,16 @@ fn data_from_node<'a>(
             _ => {}
         }
 
-        for node_kind in interesting_nested_data(original_node_kind) {
+        let optional_node_kinds = if original_node_kind == "system" {
+            optional_node_kinds
+        } else {
+            [].as_slice()
+        };
+
+        for node_kind in interesting_nested_data(original_node_kind)
+            .iter()
+            .chain(optional_node_kinds)
+        {
             for child in data.filter_children(source_index, node_index, |source_index, tokens| {
                 let key_index = usize::from(matches!(
                     tokens
@@ -1743,11 +3834,15 @@ fn data_from_node<'a>(
                     Some(l) if l == *node_kind
                 )
             }) {
-                let action = match data
+                let Some(modifier) = data
                     .get_tokens(child)
-                    .and_then(|tokens| data.get_lexeme(source_index, &tokens[0]))
-                .expect("The iterator should use a filter to ensure only nodes with at least one tokens (or two, if starting with add/remove) are allowed")
-                {
+                    .and_then(|tokens| tokens.first())
+                    .and_then(|token| data.get_lexeme(source_index, token))
+                else {
+                    continue;
+                };
+
+                let action = match modifier {
                     "remove" => {
                         if data.get_tokens(child).unwrap_or_default().len() >= 2
                             || !data.get_children(child).unwrap_or_default().is_empty()
@@ -1772,6 +3867,10 @@ fn data_from_node<'a>(
     }
 }
 
+/// The nested data of a shuffled node that moves along with the swap
+/// A system's `object`s are deliberately left out, so its planets stay with its name wherever it
+/// is moved to, and only wormhole objects are moved (see `find_wormholes_from_system`) unless
+/// `shuffle_objects` adds them to the optional node kinds
 fn interesting_nested_data(original_node_kind: &str) -> &'static [&'static str] {
     match original_node_kind {
         "system" => [
@@ -1779,6 +3878,9 @@ fn interesting_nested_data(original_node_kind: &str) -> &'static [&'static str]
             // "government",
             "link",
             "jump range",
+            // flags, which the location's new system gets as a copy on activation and loses again
+            // through a bare `remove` on restore, so a visible system swapped into a hidden one's
+            // place is hidden only while the preset is active
             "inaccessible",
             "hidden",
             "shrouded",
@@ -1,34 +1,542 @@
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+use wasm_bindgen::prelude::*;
+
+/// What every preset does to the universe, either swapping systems around or moving the whole
+/// galaxy at once, which keeps the map recognizable and every link where it was
+#[cfg_attr(all(target_family = "wasm", target_os = "unknown"), wasm_bindgen)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryMode {
+    #[default]
+    Swap,
+    Rotate,
+    Mirror,
+}
+
+impl GeometryMode {
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "swap" => Some(Self::Swap),
+            "rotate" => Some(Self::Rotate),
+            "mirror" => Some(Self::Mirror),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Swap => "swap",
+            Self::Rotate => "rotate",
+            Self::Mirror => "mirror",
+        }
+    }
+}
+
+// the defaults are the same as `config/system_shuffler/default.txt`
 crate::macros::wasm_newtype! {
+    using crate::generators::system_shuffler ;
     in main =>
-    #[derive(Debug)]
-    pub SystemShufflerConfig ;
-    seed: u64,
-    max_presets: u8,
-    shuffle_chance: u8,
-    fixed_shuffle_days: u8,
-    shuffle_once_on_install: bool,
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub SystemShufflerConfig, SystemShufflerBuilder ;
+    pub(crate) seed: u64 = 0,
+    max_presets: u8 = 1,
+    shuffle_chance: u8 = 0,
+    fixed_shuffle_days: u8 = 0,
+    shuffle_once_on_install: bool = true,
+    include_inverse_presets: bool = false,
+    mirror_layout: bool = false,
+    scope_events_by_kind: bool = false,
+    detect_wormholes: bool = true,
+    numeric_conditions: bool = false,
+    shuffle_minables: bool = false,
+    shuffle_hazards: bool = false,
+    shuffle_radii: bool = false,
+    combine_preset_files: bool = false,
+    reconcile_external_changes: bool = false,
+    normalize_music: bool = false,
+    verbose: bool = false,
+    include_full_map: bool = false,
+    sort_links: bool = false,
+    one_way: bool = false,
+    split_by_government: bool = false,
+    no_fixed_points: bool = false,
+    shuffle_links: bool = true,
+    shuffle_positions: bool = true,
+    shuffle_objects: bool = false,
+    geometry_mode: system_shuffler::config::GeometryMode = system_shuffler::config::GeometryMode::Swap,
+    plugin_name: Option<String> = None,
+    plugin_version: Option<String> = None,
+    namespace: Option<String> = None,
+    pub(crate) region: Option<String> = None,
+    restore_job_name: Option<String> = None,
+    restore_job_description: Option<String> = None,
+    manual_job: bool = true,
+    manual_job_name: Option<String> = None,
+    manual_job_description: Option<String> = None,
+    shuffle_message: Option<String> = None,
+    restore_message: Option<String> = None,
+    manual_message: Option<String> = None,
+    requires: Vec<String> = vec![],
+    preset_names: Vec<String> = vec![],
+    pinned_systems: Vec<String> = vec![],
 }
 
 pub mod from_file {
     use crate::{
         config::{self, Value},
-        generators::system_shuffler::config::SystemShufflerConfig,
+        generators::system_shuffler::config::{GeometryMode, SystemShufflerConfig},
     };
 
     #[allow(unreachable_patterns)]
     #[must_use]
     pub fn parse(source: &str) -> Option<SystemShufflerConfig> {
         config::parse_config!(
-            source => SystemShufflerConfig;
+            source => SystemShufflerConfig as builder;
             seed => { int of u64 => seed }
-            max_presets => { int of u8 => max_presets }
+            max_presets => { int of u8 where max_presets > 0 => max_presets }
             shuffle_chance => { int of u8 where shuffle_chance <= 100 => shuffle_chance }
             fixed_shuffle_days => { int of u8 => fixed_shuffle_days }
             shuffle_once_on_install => { bool => *shuffle_once_on_install }
+            include_inverse_presets => { bool => *include_inverse_presets }
+            mirror_layout => { bool => *mirror_layout }
+            scope_events_by_kind => { bool => *scope_events_by_kind }
+            detect_wormholes => { bool => *detect_wormholes }
+            numeric_conditions => { bool => *numeric_conditions }
+            shuffle_minables => { bool => *shuffle_minables }
+            shuffle_hazards => { bool => *shuffle_hazards }
+            shuffle_radii => { bool => *shuffle_radii }
+            combine_preset_files => { bool => *combine_preset_files }
+            reconcile_external_changes => { bool => *reconcile_external_changes }
+            normalize_music => { bool => *normalize_music }
+            verbose => { bool => *verbose }
+            include_full_map => { bool => *include_full_map }
+            sort_links => { bool => *sort_links }
+            one_way => { bool => *one_way }
+            split_by_government => { bool => *split_by_government }
+            no_fixed_points => { bool => *no_fixed_points }
+            shuffle_links => { bool => *shuffle_links }
+            shuffle_positions => { bool => *shuffle_positions }
+            shuffle_objects => { bool => *shuffle_objects }
+            geometry_mode => { string where GeometryMode::from_name(geometry_mode).is_some() => GeometryMode::from_name(geometry_mode)? }
+            plugin_name => { string => config::optional_string(plugin_name) }
+            plugin_version => { string => config::optional_string(plugin_version) }
+            namespace => { string => config::optional_string(namespace) }
+            region => { string => config::optional_string(region) }
+            restore_job_name => { string => config::optional_string(restore_job_name) }
+            restore_job_description => { string => config::optional_string(restore_job_description) }
+            manual_job => { bool => *manual_job }
+            manual_job_name => { string => config::optional_string(manual_job_name) }
+            manual_job_description => { string => config::optional_string(manual_job_description) }
+            shuffle_message => { string => config::optional_string(shuffle_message) }
+            restore_message => { string => config::optional_string(restore_message) }
+            manual_message => { string => config::optional_string(manual_message) }
+            requires => { list => config::string_list(requires)? }
+            preset_names => { list => config::string_list(preset_names)? }
+            pinned_systems => { list => config::string_list(pinned_systems)? }
         )
     }
 }
 
+/// Packs every setting into a short code that can be shared in a link
+pub mod share_code {
+    use crate::{
+        config::{self, ShareCodeReader},
+        generators::system_shuffler::config::{GeometryMode, SystemShufflerConfig},
+        wandom,
+    };
+
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    use wasm_bindgen::prelude::*;
+
+    /// Bumped whenever the layout below changes, so codes with an older layout are rejected
+    /// instead of misread; any new field changes the layout, wherever it goes
+    const VERSION: u8 = 1;
+
+    /// The geometry mode takes the two bits after the last flag
+    const GEOMETRY_MODE_BIT: u32 = 19;
+
+    /// Links and positions are stored as whether they are kept rather than shuffled
+    const KEEP_LINKS_BIT: u32 = 21;
+    const KEEP_POSITIONS_BIT: u32 = 22;
+
+    const SHUFFLE_OBJECTS_BIT: u32 = 23;
+
+    #[cfg_attr(all(target_family = "wasm", target_os = "unknown"), wasm_bindgen)]
+    impl SystemShufflerConfig {
+        #[cfg_attr(
+            all(target_family = "wasm", target_os = "unknown"),
+            wasm_bindgen(js_name = toCode)
+        )]
+        #[must_use]
+        pub fn to_code(&self) -> String {
+            let mut bytes = vec![VERSION];
+
+            config::push_share_code_number(&mut bytes, *self.seed());
+
+            bytes.extend([
+                *self.max_presets(),
+                *self.shuffle_chance(),
+                *self.fixed_shuffle_days(),
+            ]);
+
+            let flags = [
+                *self.shuffle_once_on_install(),
+                *self.include_inverse_presets(),
+                *self.mirror_layout(),
+                *self.scope_events_by_kind(),
+                *self.detect_wormholes(),
+                *self.numeric_conditions(),
+                *self.shuffle_minables(),
+                *self.shuffle_hazards(),
+                *self.combine_preset_files(),
+                *self.reconcile_external_changes(),
+                *self.manual_job(),
+                *self.shuffle_radii(),
+                *self.normalize_music(),
+                *self.verbose(),
+                *self.include_full_map(),
+                *self.sort_links(),
+                *self.one_way(),
+                *self.split_by_government(),
+                *self.no_fixed_points(),
+            ]
+            .into_iter()
+            .enumerate()
+            .fold(0_u64, |flags, (bit, flag)| flags | (u64::from(flag) << bit))
+                | ((*self.geometry_mode() as u64) << GEOMETRY_MODE_BIT)
+                | (u64::from(!*self.shuffle_links()) << KEEP_LINKS_BIT)
+                | (u64::from(!*self.shuffle_positions()) << KEEP_POSITIONS_BIT)
+                | (u64::from(*self.shuffle_objects()) << SHUFFLE_OBJECTS_BIT);
+
+            config::push_share_code_number(&mut bytes, flags);
+
+            // unset strings are written as empty ones, which the config file treats the same way
+            for text in [
+                self.namespace(),
+                self.region(),
+                self.restore_job_name(),
+                self.restore_job_description(),
+                self.manual_job_name(),
+                self.manual_job_description(),
+                self.plugin_name(),
+                self.plugin_version(),
+                self.shuffle_message(),
+                self.restore_message(),
+                self.manual_message(),
+            ] {
+                config::push_share_code_string(&mut bytes, text.as_deref().unwrap_or_default());
+            }
+
+            config::push_share_code_number(&mut bytes, self.requires().len() as u64);
+
+            for required_plugin in self.requires() {
+                config::push_share_code_string(&mut bytes, required_plugin);
+            }
+
+            config::push_share_code_number(&mut bytes, self.preset_names().len() as u64);
+
+            for preset_name in self.preset_names() {
+                config::push_share_code_string(&mut bytes, preset_name);
+            }
+
+            config::push_share_code_number(&mut bytes, self.pinned_systems().len() as u64);
+
+            for pinned_system in self.pinned_systems() {
+                config::push_share_code_string(&mut bytes, pinned_system);
+            }
+
+            config::encode_share_code(bytes.as_slice())
+        }
+
+        /// The same settings with a seed hashed from `phrase`, see `wandom::seed_from_phrase`
+        #[cfg_attr(
+            all(target_family = "wasm", target_os = "unknown"),
+            wasm_bindgen(js_name = fromSeedPhrase)
+        )]
+        #[must_use]
+        #[allow(clippy::missing_const_for_fn)]
+        pub fn from_seed_phrase(phrase: &str, mut settings: Self) -> Self {
+            settings.seed = wandom::seed_from_phrase(phrase);
+
+            settings
+        }
+
+        #[cfg_attr(
+            all(target_family = "wasm", target_os = "unknown"),
+            wasm_bindgen(js_name = fromCode)
+        )]
+        #[must_use]
+        pub fn from_code(code: &str) -> Option<Self> {
+            let bytes = config::decode_share_code(code)?;
+
+            let mut reader = ShareCodeReader::new(bytes.as_slice());
+
+            if reader.byte()? != VERSION {
+                return None;
+            }
+
+            let seed = reader.number()?;
+            let max_presets = reader.byte().filter(|&max_presets| max_presets > 0)?;
+            let shuffle_chance = reader
+                .byte()
+                .filter(|&shuffle_chance| shuffle_chance <= 100)?;
+            let fixed_shuffle_days = reader.byte()?;
+
+            let flags = reader.number()?;
+            let flag = |bit: u32| flags & (1 << bit) != 0;
+
+            let geometry_mode = match (flags >> GEOMETRY_MODE_BIT) & 0b11 {
+                0 => GeometryMode::Swap,
+                1 => GeometryMode::Rotate,
+                2 => GeometryMode::Mirror,
+                _ => return None,
+            };
+
+            let mut optional_string = || reader.string().map(|text| config::optional_string(&text));
+
+            let namespace = optional_string()?;
+            let region = optional_string()?;
+            let restore_job_name = optional_string()?;
+            let restore_job_description = optional_string()?;
+            let manual_job_name = optional_string()?;
+            let manual_job_description = optional_string()?;
+            let plugin_name = optional_string()?;
+            let plugin_version = optional_string()?;
+            let shuffle_message = optional_string()?;
+            let restore_message = optional_string()?;
+            let manual_message = optional_string()?;
+
+            let requires = (0..reader.number()?)
+                .map(|_| reader.string())
+                .collect::<Option<Vec<_>>>()?;
+
+            let preset_names = (0..reader.number()?)
+                .map(|_| reader.string())
+                .collect::<Option<Vec<_>>>()?;
+
+            let pinned_systems = (0..reader.number()?)
+                .map(|_| reader.string())
+                .collect::<Option<Vec<_>>>()?;
+
+            if !reader.is_empty() {
+                return None;
+            }
+
+            Some(
+                Self::builder()
+                    .seed(seed)
+                    .max_presets(max_presets)
+                    .shuffle_chance(shuffle_chance)
+                    .fixed_shuffle_days(fixed_shuffle_days)
+                    .shuffle_once_on_install(flag(0))
+                    .include_inverse_presets(flag(1))
+                    .mirror_layout(flag(2))
+                    .scope_events_by_kind(flag(3))
+                    .detect_wormholes(flag(4))
+                    .numeric_conditions(flag(5))
+                    .shuffle_minables(flag(6))
+                    .shuffle_hazards(flag(7))
+                    .combine_preset_files(flag(8))
+                    .reconcile_external_changes(flag(9))
+                    .manual_job(flag(10))
+                    .shuffle_radii(flag(11))
+                    .normalize_music(flag(12))
+                    .verbose(flag(13))
+                    .include_full_map(flag(14))
+                    .sort_links(flag(15))
+                    .one_way(flag(16))
+                    .split_by_government(flag(17))
+                    .no_fixed_points(flag(18))
+                    .geometry_mode(geometry_mode)
+                    .shuffle_links(!flag(KEEP_LINKS_BIT))
+                    .shuffle_positions(!flag(KEEP_POSITIONS_BIT))
+                    .shuffle_objects(flag(SHUFFLE_OBJECTS_BIT))
+                    .namespace(namespace)
+                    .region(region)
+                    .restore_job_name(restore_job_name)
+                    .restore_job_description(restore_job_description)
+                    .manual_job_name(manual_job_name)
+                    .manual_job_description(manual_job_description)
+                    .plugin_name(plugin_name)
+                    .plugin_version(plugin_version)
+                    .shuffle_message(shuffle_message)
+                    .restore_message(restore_message)
+                    .manual_message(manual_message)
+                    .requires(requires)
+                    .preset_names(preset_names)
+                    .pinned_systems(pinned_systems)
+                    .build(),
+            )
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::VERSION;
+
+        use crate::{
+            config,
+            generators::system_shuffler::config::{GeometryMode, SystemShufflerConfig},
+            wandom::XoShiRo256SS,
+        };
+
+        const WORDS: [&str; 5] = [
+            "Sol",
+            "Ring of Friendship",
+            "ünïcödé",
+            "a \"quoted\" name",
+            "0",
+        ];
+
+        /// A config with every field picked by `rng`, so many seeds cover many combinations
+        fn random_config(rng: &mut XoShiRo256SS) -> SystemShufflerConfig {
+            let mut flag = || rng.rand_range(0, 2) == 1;
+            let flags = [(); 24].map(|()| flag());
+
+            let mut text = || {
+                let length = usize::try_from(rng.rand_range(0, 4))
+                    .expect("The length of a list will always fit in a usize");
+
+                (0..length)
+                    .map(|_| {
+                        WORDS[usize::try_from(rng.rand_range(0, WORDS.len() as u64))
+                            .expect("The index of a word will always fit in a usize")]
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let optional = |words: Vec<&str>| (!words.is_empty()).then(|| words.join(" "));
+            let list = |words: Vec<&str>| words.into_iter().map(str::to_string).collect();
+
+            let strings = [(); 11].map(|()| optional(text()));
+            let lists = [(); 3].map(|()| list(text()));
+
+            let [
+                namespace,
+                region,
+                restore_job_name,
+                restore_job_description,
+                manual_job_name,
+                manual_job_description,
+                plugin_name,
+                plugin_version,
+                shuffle_message,
+                restore_message,
+                manual_message,
+            ] = strings;
+
+            let [requires, preset_names, pinned_systems] = lists;
+
+            SystemShufflerConfig::builder()
+                .seed(rng.step())
+                .max_presets(u8::try_from(rng.rand_range(1, 256)).expect("1 to 255 fits in a u8"))
+                .shuffle_chance(
+                    u8::try_from(rng.rand_range(0, 101)).expect("0 to 100 fits in a u8"),
+                )
+                .fixed_shuffle_days(
+                    u8::try_from(rng.rand_range(0, 256)).expect("0 to 255 fits in a u8"),
+                )
+                .shuffle_once_on_install(flags[0])
+                .include_inverse_presets(flags[1])
+                .mirror_layout(flags[2])
+                .scope_events_by_kind(flags[3])
+                .detect_wormholes(flags[4])
+                .numeric_conditions(flags[5])
+                .shuffle_minables(flags[6])
+                .shuffle_hazards(flags[7])
+                .combine_preset_files(flags[8])
+                .reconcile_external_changes(flags[9])
+                .manual_job(flags[10])
+                .shuffle_radii(flags[11])
+                .normalize_music(flags[12])
+                .verbose(flags[13])
+                .include_full_map(flags[14])
+                .sort_links(flags[15])
+                .one_way(flags[16])
+                .split_by_government(flags[17])
+                .no_fixed_points(flags[18])
+                .shuffle_links(flags[19])
+                .shuffle_positions(flags[20])
+                .shuffle_objects(flags[21])
+                .geometry_mode(match (flags[22], flags[23]) {
+                    (false, _) => GeometryMode::Swap,
+                    (true, false) => GeometryMode::Rotate,
+                    (true, true) => GeometryMode::Mirror,
+                })
+                .namespace(namespace)
+                .region(region)
+                .restore_job_name(restore_job_name)
+                .restore_job_description(restore_job_description)
+                .manual_job_name(manual_job_name)
+                .manual_job_description(manual_job_description)
+                .plugin_name(plugin_name)
+                .plugin_version(plugin_version)
+                .shuffle_message(shuffle_message)
+                .restore_message(restore_message)
+                .manual_message(manual_message)
+                .requires(requires)
+                .preset_names(preset_names)
+                .pinned_systems(pinned_systems)
+                .build()
+        }
+
+        #[test]
+        fn codes_round_trip() {
+            let default = SystemShufflerConfig::default();
+
+            assert_eq!(
+                SystemShufflerConfig::from_code(&default.to_code()),
+                Some(default)
+            );
+
+            let mut rng = XoShiRo256SS::new(7);
+
+            for _ in 0..1000 {
+                let settings = random_config(&mut rng);
+                let code = settings.to_code();
+
+                assert_eq!(
+                    SystemShufflerConfig::from_code(&code),
+                    Some(settings),
+                    "{code}"
+                );
+            }
+        }
+
+        #[test]
+        fn other_versions_are_rejected() {
+            let code = SystemShufflerConfig::default().to_code();
+            let mut bytes = config::decode_share_code(&code).expect("A fresh code should decode");
+
+            for version in [0, VERSION + 1, u8::MAX] {
+                bytes[0] = version;
+
+                assert_eq!(
+                    SystemShufflerConfig::from_code(&config::encode_share_code(&bytes)),
+                    None,
+                    "version {version}"
+                );
+            }
+        }
+
+        #[test]
+        fn truncated_codes_are_rejected() {
+            let code = random_config(&mut XoShiRo256SS::new(7)).to_code();
+            let bytes = config::decode_share_code(&code).expect("A fresh code should decode");
+
+            for length in 0..bytes.len() {
+                assert_eq!(
+                    SystemShufflerConfig::from_code(&config::encode_share_code(&bytes[..length])),
+                    None,
+                    "{length} of {} bytes",
+                    bytes.len()
+                );
+            }
+        }
+    }
+}
+
 pub mod page {
     use crate::{
         generators::system_shuffler::config,
@@ -88,6 +596,7 @@ pub mod page {
             )
     }
 
+    #[allow(clippy::too_many_lines)]
     fn system_shuffler_fieldset() -> HtmlElement {
         let settings = config::from_file::parse(DEFAULT_CONFIG_FILE);
         let settings = settings.as_ref();
@@ -105,6 +614,12 @@ pub mod page {
                     input
                 }
             }))
+            .with_element(
+                HtmlElement::new("button")
+                    .with_class("system-shuffler-daily-seed")
+                    .with_attribute("type", "button")
+                    .with_text("use today's seed"),
+            )
             .with_element(html::page::labeled(
                 "system-shuffler-max-presets",
                 "",
@@ -139,6 +654,358 @@ pub mod page {
                     }
                 },
             ))
+            .with_element(html::page::labeled(
+                "system-shuffler-include-inverse-presets",
+                "",
+                "also include the inverse of every preset (doubles output size):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.include_inverse_presets()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-mirror-layout",
+                "",
+                "split each preset folder into one file per uploaded source file:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.mirror_layout()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-scope-events-by-kind",
+                "",
+                "split each preset into separate system and wormhole events (can't be combined with the option above):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.scope_events_by_kind()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-detect-wormholes",
+                "",
+                "detect wormholes (uncheck for faster generation if there are none):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.detect_wormholes()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-minables",
+                "",
+                "also shuffle asteroid minables between systems:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.shuffle_minables()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-hazards",
+                "",
+                "also shuffle environmental hazards between systems:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.shuffle_hazards()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-radii",
+                "",
+                "also shuffle asteroid belt and hyperspace arrival radii between systems:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.shuffle_radii()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-split-by-government",
+                "",
+                "write each government's systems into their own preset folder:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.split_by_government()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-no-fixed-points",
+                "",
+                "move every shuffled system in every preset:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.no_fixed_points()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-links",
+                "",
+                "swap links between systems (without this, every system keeps its own links):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.shuffle_links()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-positions",
+                "",
+                "swap positions between systems (without this, the map looks the same but jumps lead elsewhere):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.shuffle_positions()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-objects",
+                "",
+                "also swap planets and stations between systems (experimental):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.shuffle_objects()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-geometry-mode",
+                "",
+                "what every preset does to the universe:",
+                [
+                    (config::GeometryMode::Swap, "swap systems around"),
+                    (
+                        config::GeometryMode::Rotate,
+                        "rotate the whole galaxy, keeping every link",
+                    ),
+                    (
+                        config::GeometryMode::Mirror,
+                        "mirror the whole galaxy, keeping every link",
+                    ),
+                ]
+                .into_iter()
+                .fold(HtmlElement::new("select"), |select, (geometry_mode, text)| {
+                    let option = HtmlElement::new("option")
+                        .with_attribute("value", geometry_mode.name())
+                        .with_text(text);
+
+                    select.with_element(
+                        if let Some(settings) = settings
+                            && *settings.geometry_mode() == geometry_mode
+                        {
+                            option.selected()
+                        } else {
+                            option
+                        },
+                    )
+                }),
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-combine-preset-files",
+                "",
+                "write each preset into a single file:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.combine_preset_files()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-sort-links",
+                "",
+                "sort links by the system they lead to, so diffs between uploads stay small:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.sort_links()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-reconcile-external-changes",
+                "",
+                "follow the current preset when something else changes it:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.reconcile_external_changes()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-normalize-music",
+                "",
+                "give shuffled systems the music of the region they land in:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.normalize_music()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-one-way",
+                "",
+                "shuffle once for good, without any way to unshuffle:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.one_way()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-include-full-map",
+                "",
+                "also reveal the entire map via any job board:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.include_full_map()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-verbose",
+                "",
+                "log progress to the browser console:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.verbose()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-numeric-conditions",
+                "",
+                "write every condition as a numeric comparison instead of `has`/`not`:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.numeric_conditions()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
             .with_element(html::page::labeled_range(
                 "system-shuffler-shuffle-chance",
                 "",
@@ -155,5 +1022,239 @@ pub mod page {
                 (0u8, 255u8),
                 false,
             ))
+            .with_element(html::page::labeled(
+                "system-shuffler-plugin-name",
+                "",
+                "plugin name, so differently named shufflers can be installed together (blank for the default):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(plugin_name) = settings.plugin_name()
+                    {
+                        input.with_attribute("value", plugin_name.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-plugin-version",
+                "",
+                "plugin version (blank for the default):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(plugin_version) = settings.plugin_version()
+                    {
+                        input.with_attribute("value", plugin_version.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-namespace",
+                "",
+                "namespace for conditions, events, and missions (blank for the plugin name):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(namespace) = settings.namespace()
+                    {
+                        input.with_attribute("value", namespace.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-region",
+                "",
+                "only shuffle systems of this government (blank for every system):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(region) = settings.region()
+                    {
+                        input.with_attribute("value", region.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-restore-job-name",
+                "",
+                "restore job name (blank for the default):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(restore_job_name) = settings.restore_job_name()
+                    {
+                        input.with_attribute("value", restore_job_name.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-restore-job-description",
+                "",
+                "restore job description (blank for the default):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(restore_job_description) = settings.restore_job_description()
+                    {
+                        input.with_attribute("value", restore_job_description.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-manual-job",
+                "",
+                "offer a job to shuffle the universe on request:",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "checkbox");
+
+                    if let Some(settings) = settings
+                        && *settings.manual_job()
+                    {
+                        input.checked()
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-manual-job-name",
+                "",
+                "manual shuffle job name (blank for the default):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(manual_job_name) = settings.manual_job_name()
+                    {
+                        input.with_attribute("value", manual_job_name.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-manual-job-description",
+                "",
+                "manual shuffle job description (blank for the default):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings
+                        && let Some(manual_job_description) = settings.manual_job_description()
+                    {
+                        input.with_attribute("value", manual_job_description.as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-shuffle-message",
+                "",
+                "message when the universe shuffles on its own, one conversation line per line (blank for the default):",
+                {
+                    let textarea = HtmlElement::new("textarea");
+
+                    if let Some(settings) = settings
+                        && let Some(shuffle_message) = settings.shuffle_message()
+                    {
+                        textarea.with_text(shuffle_message)
+                    } else {
+                        textarea
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-restore-message",
+                "",
+                "message when the restore job is accepted, one conversation line per line (blank for the default):",
+                {
+                    let textarea = HtmlElement::new("textarea");
+
+                    if let Some(settings) = settings
+                        && let Some(restore_message) = settings.restore_message()
+                    {
+                        textarea.with_text(restore_message)
+                    } else {
+                        textarea
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-manual-message",
+                "",
+                "message when the manual shuffle job is accepted, one conversation line per line (blank for the default):",
+                {
+                    let textarea = HtmlElement::new("textarea");
+
+                    if let Some(settings) = settings
+                        && let Some(manual_message) = settings.manual_message()
+                    {
+                        textarea.with_text(manual_message)
+                    } else {
+                        textarea
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-requires",
+                "",
+                "required plugins (comma separated):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", settings.requires().join(", ").as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-preset-names",
+                "",
+                "preset names, starting from preset 0 (comma separated):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", settings.preset_names().join(", ").as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
+            .with_element(html::page::labeled(
+                "system-shuffler-pinned-systems",
+                "",
+                "systems that never move (comma separated):",
+                {
+                    let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                    if let Some(settings) = settings {
+                        input.with_attribute("value", settings.pinned_systems().join(", ").as_str())
+                    } else {
+                        input
+                    }
+                },
+            ))
     }
 }
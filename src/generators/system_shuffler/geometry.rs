@@ -0,0 +1,113 @@
+use crate::{generators::system_shuffler::config::GeometryMode, wandom::XoShiRo256SS};
+
+use std::collections::HashSet;
+
+/// How a rotated or mirrored preset moves the galaxy about its center
+/// The default is the original universe, left exactly where it is
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Transform {
+    /// How far a rotation turns the galaxy, or the angle of the axis a mirror flips it across
+    degrees: u16,
+    mirror: bool,
+}
+
+impl Transform {
+    /// A rotation never turns the galaxy by a whole circle, so every rotated preset changes something
+    pub fn with_rng(geometry_mode: GeometryMode, rng: &mut XoShiRo256SS) -> Self {
+        match geometry_mode {
+            GeometryMode::Swap => Self::default(),
+            GeometryMode::Rotate => Self {
+                degrees: u16::try_from(rng.rand_range(1, 360)).unwrap_or_default(),
+                mirror: false,
+            },
+            GeometryMode::Mirror => Self {
+                degrees: u16::try_from(rng.rand_range(0, 180)).unwrap_or_default(),
+                mirror: true,
+            },
+        }
+    }
+
+    /// Mirroring twice across the same axis is the original universe, so a mirror is its own inverse
+    #[must_use]
+    pub const fn inverse(&self) -> Self {
+        if self.mirror {
+            *self
+        } else {
+            Self {
+                degrees: (360 - self.degrees) % 360,
+                mirror: false,
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn apply(&self, (center_x, center_y): (f64, f64), (x, y): (f64, f64)) -> (f64, f64) {
+        let (offset_x, offset_y) = (x - center_x, y - center_y);
+
+        if self.mirror {
+            let (sin, cos) = (2.0 * f64::from(self.degrees)).to_radians().sin_cos();
+
+            (
+                cos.mul_add(offset_x, sin.mul_add(offset_y, center_x)),
+                sin.mul_add(offset_x, (-cos).mul_add(offset_y, center_y)),
+            )
+        } else {
+            let (sin, cos) = f64::from(self.degrees).to_radians().sin_cos();
+
+            (
+                cos.mul_add(offset_x, (-sin).mul_add(offset_y, center_x)),
+                sin.mul_add(offset_x, cos.mul_add(offset_y, center_y)),
+            )
+        }
+    }
+}
+
+/// The systems rotated and mirrored presets move, and the point they are all moved around
+#[derive(Debug)]
+pub struct Geometry {
+    center: (f64, f64),
+    systems: HashSet<String>,
+}
+
+impl Geometry {
+    /// The center is the average of every position, so the moved systems stay roughly where they were
+    #[must_use]
+    pub fn new(positions: &[(&str, (f64, f64))]) -> Option<Self> {
+        let count = f64::from(
+            u32::try_from(positions.len())
+                .ok()
+                .filter(|&count| count > 0)?,
+        );
+
+        let (sum_x, sum_y) = positions
+            .iter()
+            .fold((0.0, 0.0), |(sum_x, sum_y), (_, (x, y))| {
+                (sum_x + x, sum_y + y)
+            });
+
+        Some(Self {
+            center: (sum_x / count, sum_y / count),
+            systems: positions
+                .iter()
+                .map(|(system_name, _)| (*system_name).to_string())
+                .collect(),
+        })
+    }
+
+    #[must_use]
+    pub const fn center(&self) -> (f64, f64) {
+        self.center
+    }
+
+    #[must_use]
+    pub fn moves(&self, system_name: &str) -> bool {
+        self.systems.contains(system_name)
+    }
+}
+
+/// Rounded to a hundredth, which is far finer than anything the map shows
+#[must_use]
+pub fn coordinate(value: f64) -> String {
+    // adding zero turns a rounded `-0` into `0`
+    ((value * 100.0).round() / 100.0 + 0.0).to_string()
+}
@@ -0,0 +1,370 @@
+use crate::wandom::{XoShiRo256SS, shuffle_index::ShuffleIndex};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    io,
+};
+
+/// How many times a preset that came out the same as the original universe is shuffled again
+/// With only two systems each roll has even odds, so about 1 in 256 of those presets still do nothing
+pub(crate) const MAX_IDENTITY_REROLLS: usize = 8;
+
+#[derive(Debug, Default, Clone)]
+pub struct SwapOptions {
+    pub identity: bool,
+    /// No name is swapped with itself, unless it's the only one
+    pub derangement: bool,
+}
+
+#[must_use]
+pub fn compute_swaps<'a>(
+    names: &[&'a str],
+    seed: u64,
+    options: &SwapOptions,
+) -> HashMap<&'a str, &'a str> {
+    let mut rng = XoShiRo256SS::new(seed);
+    compute_swaps_with_rng(names, &mut rng, options)
+}
+
+/// The names that swap places, which leaves out `pinned` names and, with a `region`, every name
+/// that `regions` puts somewhere else
+#[allow(clippy::missing_errors_doc)]
+pub fn shuffled_names<'a>(
+    names: &[&'a str],
+    pinned: &[String],
+    region: Option<(&str, &BTreeMap<&str, &str>)>,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let unpinned_names = names
+        .iter()
+        .copied()
+        .filter(|name| !pinned.iter().any(|pinned_name| pinned_name == name))
+        .collect::<Vec<_>>();
+
+    if unpinned_names.is_empty() {
+        return Err(Box::new(io::Error::other(
+            "Every uploaded system is pinned, so there is nothing left to shuffle :(",
+        )));
+    }
+
+    let Some((region, regions)) = region else {
+        return Ok(unpinned_names);
+    };
+
+    let shuffled_names = unpinned_names
+        .into_iter()
+        .filter(|name| regions.get(name) == Some(&region))
+        .collect::<Vec<_>>();
+
+    if shuffled_names.is_empty() {
+        return Err(Box::new(io::Error::other(format!(
+            "No unpinned uploaded systems belong to the {region} region :("
+        ))));
+    }
+
+    Ok(shuffled_names)
+}
+
+/// The swaps of a whole preset, where only `shuffled_names` are swapped around and every other
+/// name in `names` stays where it is
+/// A small galaxy can shuffle back into the original universe by chance, which would make the
+/// preset a shuffle that does nothing, so outside `identity` it is rolled again a few times
+pub(crate) fn compute_preset_swaps_with_rng<'a>(
+    (names, shuffled_names): (&[&'a str], &[&'a str]),
+    rng: &mut XoShiRo256SS,
+    options: &SwapOptions,
+) -> HashMap<&'a str, &'a str> {
+    let mut swaps = compute_swaps_with_rng(shuffled_names, rng, options);
+
+    for _ in 0..MAX_IDENTITY_REROLLS {
+        if options.identity
+            || shuffled_names.len() < 2
+            || swaps
+                .iter()
+                .any(|(original, replacement)| original != replacement)
+        {
+            break;
+        }
+
+        swaps = compute_swaps_with_rng(shuffled_names, rng, options);
+    }
+
+    for &name in names {
+        swaps.entry(name).or_insert(name);
+    }
+
+    swaps
+}
+
+pub(crate) fn compute_swaps_with_rng<'a>(
+    names: &[&'a str],
+    rng: &mut XoShiRo256SS,
+    options: &SwapOptions,
+) -> HashMap<&'a str, &'a str> {
+    if options.identity {
+        names
+            .iter()
+            .map(|&name| (name, name))
+            .collect::<HashMap<_, _>>()
+    } else if options.derangement {
+        names
+            .deranged_pairs_with_rng(rng)
+            .into_iter()
+            .map(|(&original, &replacement)| (original, replacement))
+            .collect::<HashMap<_, _>>()
+    } else {
+        names
+            .shuffled_pairs_with_rng(rng)
+            .into_iter()
+            .map(|(&original, &replacement)| (original, replacement))
+            .collect::<HashMap<_, _>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SwapOptions, compute_preset_swaps_with_rng, compute_swaps, compute_swaps_with_rng,
+        shuffled_names,
+    };
+
+    use crate::wandom::XoShiRo256SS;
+
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    const NAMES: [&str; 6] = ["Sol", "Alpha", "Vega", "Deneb", "Rigel", "Altair"];
+
+    const SEED: u64 = 7;
+
+    fn assert_permutation(swaps: &HashMap<&str, &str>) {
+        assert_eq!(swaps.len(), NAMES.len());
+
+        let originals = swaps.keys().copied().collect::<HashSet<_>>();
+        let replacements = swaps.values().copied().collect::<HashSet<_>>();
+        let names = NAMES.into_iter().collect::<HashSet<_>>();
+
+        assert_eq!(originals, names);
+        assert_eq!(replacements, names);
+    }
+
+    #[test]
+    fn identity_maps_every_name_to_itself() {
+        let options = SwapOptions {
+            identity: true,
+            ..SwapOptions::default()
+        };
+
+        let swaps = compute_swaps(&NAMES, SEED, &options);
+
+        assert_permutation(&swaps);
+        assert!(
+            swaps
+                .iter()
+                .all(|(original, replacement)| original == replacement)
+        );
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        let swaps = compute_swaps(&NAMES, SEED, &SwapOptions::default());
+
+        assert_permutation(&swaps);
+    }
+
+    #[test]
+    fn derangement_has_no_fixed_points() {
+        let options = SwapOptions {
+            derangement: true,
+            ..SwapOptions::default()
+        };
+
+        for length in 2..=NAMES.len() {
+            for seed in 0..32 {
+                let swaps = compute_swaps(&NAMES[..length], seed, &options);
+
+                assert_eq!(swaps.len(), length);
+                assert!(
+                    swaps
+                        .iter()
+                        .all(|(original, replacement)| original != replacement),
+                    "{length} names with seed {seed} gave {swaps:?}"
+                );
+            }
+        }
+
+        assert_permutation(&compute_swaps(&NAMES, SEED, &options));
+    }
+
+    #[test]
+    fn same_seed_gives_same_swaps() {
+        let options = SwapOptions::default();
+
+        assert_eq!(
+            compute_swaps(&NAMES, SEED, &options),
+            compute_swaps(&NAMES, SEED, &options)
+        );
+    }
+
+    /// Every name but Sol is in the Republic, and Vega and Deneb are also the Free Worlds
+    fn regions() -> BTreeMap<&'static str, &'static str> {
+        NAMES
+            .into_iter()
+            .map(|name| {
+                let region = match name {
+                    "Sol" => "Syndicate",
+                    "Vega" | "Deneb" => "Free Worlds",
+                    _ => "Republic",
+                };
+
+                (name, region)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_combination_keeps_its_constraints() {
+        let regions = regions();
+        let pinned_lists = [
+            vec![],
+            vec!["Alpha".to_string()],
+            vec!["Alpha".to_string(), "Vega".to_string()],
+        ];
+
+        for identity in [false, true] {
+            for derangement in [false, true] {
+                for pinned in &pinned_lists {
+                    for region in [None, Some("Republic"), Some("Free Worlds")] {
+                        let context = format!(
+                            "identity {identity}, derangement {derangement}, pinned {pinned:?}, region {region:?}"
+                        );
+
+                        // pinning Vega leaves Deneb alone in the Free Worlds, which is still something to shuffle
+                        let shuffled_names =
+                            shuffled_names(&NAMES, pinned, region.map(|region| (region, &regions)))
+                                .unwrap_or_else(|error| panic!("{context}: {error}"));
+
+                        assert!(
+                            shuffled_names
+                                .iter()
+                                .all(|name| !pinned.iter().any(|pinned_name| pinned_name == name)),
+                            "{context}"
+                        );
+                        assert!(
+                            shuffled_names
+                                .iter()
+                                .all(|name| region.is_none_or(|region| regions[name] == region)),
+                            "{context}"
+                        );
+
+                        let options = SwapOptions {
+                            identity,
+                            derangement,
+                        };
+
+                        for seed in 0..32 {
+                            let swaps = compute_preset_swaps_with_rng(
+                                (&NAMES, shuffled_names.as_slice()),
+                                &mut XoShiRo256SS::new(seed),
+                                &options,
+                            );
+
+                            assert_permutation(&swaps);
+
+                            let moved = swaps
+                                .iter()
+                                .filter(|(original, replacement)| original != replacement)
+                                .map(|(original, _)| *original)
+                                .collect::<HashSet<_>>();
+
+                            assert!(
+                                moved.iter().all(|name| shuffled_names.contains(name)),
+                                "{context}, seed {seed}: {swaps:?}"
+                            );
+
+                            // everything that moves is swapped with another shuffled name
+                            assert!(
+                                moved
+                                    .iter()
+                                    .all(|name| shuffled_names.contains(&swaps[name])),
+                                "{context}, seed {seed}: {swaps:?}"
+                            );
+
+                            if identity || shuffled_names.len() < 2 {
+                                assert!(moved.is_empty(), "{context}, seed {seed}: {swaps:?}");
+                            } else if derangement {
+                                assert_eq!(
+                                    moved.len(),
+                                    shuffled_names.len(),
+                                    "{context}, seed {seed}: {swaps:?}"
+                                );
+                            } else {
+                                assert!(!moved.is_empty(), "{context}, seed {seed}: {swaps:?}");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_to_shuffle_is_an_error() {
+        let regions = regions();
+        let all_pinned = NAMES.map(str::to_string);
+
+        assert!(shuffled_names(&NAMES, &all_pinned, None).is_err());
+
+        // Sol is the only system in the Syndicate
+        assert!(
+            shuffled_names(&NAMES, &["Sol".to_string()], Some(("Syndicate", &regions))).is_err()
+        );
+        assert!(shuffled_names(&NAMES, &[], Some(("Pirate", &regions))).is_err());
+
+        assert_eq!(
+            shuffled_names(&NAMES, &[], Some(("Syndicate", &regions)))
+                .expect("Sol is in the Syndicate"),
+            ["Sol"]
+        );
+    }
+
+    #[test]
+    fn an_identity_roll_is_rolled_again() {
+        let names = ["Sol", "Vega"];
+        let options = SwapOptions::default();
+
+        let is_identity = |swaps: &HashMap<&str, &str>| {
+            swaps
+                .iter()
+                .all(|(original, replacement)| original == replacement)
+        };
+
+        // with two names, about half the seeds roll the identity first
+        let seed = (0..64)
+            .find(|&seed| {
+                is_identity(&compute_swaps_with_rng(
+                    &names,
+                    &mut XoShiRo256SS::new(seed),
+                    &options,
+                ))
+            })
+            .expect("some seed should roll the identity first");
+
+        let swaps =
+            compute_preset_swaps_with_rng((&names, &names), &mut XoShiRo256SS::new(seed), &options);
+
+        assert_eq!(swaps.len(), names.len());
+        assert!(!is_identity(&swaps), "seed {seed} gave {swaps:?}");
+
+        // the original universe is the identity on purpose, so it's never rolled again
+        let identity = compute_preset_swaps_with_rng(
+            (&names, &names),
+            &mut XoShiRo256SS::new(seed),
+            &SwapOptions {
+                identity: true,
+                ..SwapOptions::default()
+            },
+        );
+
+        assert!(is_identity(&identity));
+    }
+}
@@ -201,6 +201,20 @@ impl HtmlElement {
         self
     }
 
+    #[must_use]
+    pub fn selected(mut self) -> Self {
+        let toggle_attribute = "selected";
+
+        if !self.attributes.iter().any(
+            |attribute| matches!(attribute, HtmlAttribute::KeyOnly(key) if key == toggle_attribute),
+        ) {
+            self.attributes
+                .push(HtmlAttribute::KeyOnly(toggle_attribute.to_string()));
+        }
+
+        self
+    }
+
     #[must_use]
     pub fn webkitdirectory(mut self) -> Self {
         let toggle_attribute = "webkitdirectory";
@@ -278,7 +292,10 @@ pub fn page_contents() -> String {
 pub mod page {
     use crate::generators::{
         chaos::config::page as chaos_form, full_map::config::page as full_map_form,
+        jump_chaos::config::page as jump_chaos_form,
         random_galaxy::config::page as random_galaxy_form,
+        spaceport_chaos::config::page as spaceport_chaos_form,
+        star_chaos::config::page as star_chaos_form,
         system_shuffler::config::page as system_shuffler_form,
     };
 
@@ -634,6 +651,9 @@ pub mod page {
             .with_element(full_map_form::full_map())
             .with_element(system_shuffler_form::system_shuffler())
             .with_element(chaos_form::chaos())
+            .with_element(jump_chaos_form::jump_chaos())
+            .with_element(spaceport_chaos_form::spaceport_chaos())
+            .with_element(star_chaos_form::star_chaos())
             .with_element(random_galaxy_form::random_galaxy())
             .with_element(
                 HtmlElement::new("script")
@@ -708,6 +728,16 @@ pub mod page {
                             .with_element(goto("System_Shuffler", "System Shuffler")),
                     )
                     .with_element(HtmlElement::new("li").with_element(goto("Chaos", "Chaos")))
+                    .with_element(
+                        HtmlElement::new("li").with_element(goto("Jump_Chaos", "Jump Chaos")),
+                    )
+                    .with_element(
+                        HtmlElement::new("li")
+                            .with_element(goto("Spaceport_Chaos", "Spaceport Chaos")),
+                    )
+                    .with_element(
+                        HtmlElement::new("li").with_element(goto("Star_Chaos", "Star Chaos")),
+                    )
                     .with_element(
                         HtmlElement::new("li").with_element(goto("Random_Galaxy", "Random Galaxy")),
                     ),
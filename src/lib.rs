@@ -1,3 +1,5 @@
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub mod cli;
 pub mod config;
 pub mod generators;
 pub mod html;
@@ -1,5 +1,85 @@
 #[macro_export]
 macro_rules! __wasm_newtype {
+    (
+        $(using $($want:path $(, $wants:path)* $(,)? )? ;)?
+        in $mod_name:ident =>
+        $(#[$attribute:meta $(, $attributes:meta)* $(,)?])?
+        $v:vis $name:ident , $builder:ident ;
+        $($fv:vis $field:ident : $field_ty:ty = $default:expr,)+
+    ) => {
+        $v mod $mod_name {
+            $($(
+                use $want;
+                $(
+                    use $wants;
+                )*
+            )?)?
+
+            #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+            use wasm_bindgen::prelude::*;
+
+            #[cfg_attr(all(target_family = "wasm", target_os = "unknown"), wasm_bindgen)]
+            $(#[$attribute $(, $attributes)*])?
+            $v struct $name {
+                $($fv $field: $field_ty,)+
+            }
+
+            impl Default for $name {
+                fn default() -> Self {
+                    Self { $($field: $default,)+ }
+                }
+            }
+
+            impl $name {
+                #[must_use]
+                $v fn builder() -> $builder {
+                    $builder::default()
+                }
+
+                $(
+                    #[must_use]
+                    $v const fn $field(&self) -> &$field_ty {
+                        &self.$field
+                    }
+                )+
+            }
+
+            #[cfg_attr(all(target_family = "wasm", target_os = "unknown"), wasm_bindgen)]
+            #[derive(Default)]
+            $v struct $builder {
+                settings: $name,
+            }
+
+            // every setting has its own method, named after it, instead of one long list of
+            // arguments that has to be kept in order everywhere a config is made
+            #[cfg_attr(all(target_family = "wasm", target_os = "unknown"), wasm_bindgen)]
+            impl $builder {
+                #[cfg_attr(all(target_family = "wasm", target_os = "unknown"), wasm_bindgen(constructor))]
+                #[must_use]
+                $v fn new() -> Self {
+                    Self::default()
+                }
+
+                $(
+                    #[must_use]
+                    #[allow(clippy::missing_const_for_fn)]
+                    $v fn $field(mut self, $field: $field_ty) -> Self {
+                        self.settings.$field = $field;
+                        self
+                    }
+                )+
+
+                #[must_use]
+                #[allow(clippy::missing_const_for_fn)]
+                $v fn build(self) -> $name {
+                    self.settings
+                }
+            }
+        }
+
+        $v use $mod_name::{$name, $builder};
+    };
+
     (
         $(using $($want:path $(, $wants:path)* $(,)? )? ;)?
         in $mod_name:ident =>
@@ -48,3 +128,109 @@ macro_rules! __wasm_newtype {
 }
 
 pub use __wasm_newtype as wasm_newtype;
+
+/// The config, config file parser, and form of a generator whose only settings are its seed and
+/// the plugins it requires, so each of them only has to give its names and description
+#[macro_export]
+macro_rules! __seed_only_config {
+    (
+        $generator:ident => $name:ident ;
+        title: $title:literal , anchor: $anchor:literal , class: $class:literal ;
+        $($description:literal),+ $(,)?
+    ) => {
+        $crate::macros::wasm_newtype! {
+            in main =>
+            #[derive(Debug)]
+            pub $name;
+            seed: u64,
+            requires: Vec<String>,
+        }
+
+        pub mod from_file {
+            use $crate::{
+                config::{self, Value},
+                generators::$generator::config::$name,
+            };
+
+            #[allow(unreachable_patterns)]
+            #[must_use]
+            pub fn parse(source: &str) -> Option<$name> {
+                config::parse_config!(
+                    source => $name;
+                    seed => { int of u64 => seed }
+                    requires => { list => config::string_list(requires)? }
+                )
+            }
+        }
+
+        pub mod page {
+            use $crate::{
+                generators::$generator::config,
+                html::{self, HtmlElement},
+            };
+
+            const DEFAULT_CONFIG_FILE: &str = include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/config/",
+                stringify!($generator),
+                "/default.txt"
+            ));
+
+            #[must_use]
+            pub fn $generator() -> HtmlElement {
+                HtmlElement::new("form")
+                    .with_name(concat!($class, "-form"))
+                    .with_id(concat!($class, "-form"))
+                    .novalidate()
+                    .with_element(
+                        HtmlElement::new("h2").with_element(html::page::anchor($anchor, $title)),
+                    )
+                    .with_element(HtmlElement::new("p")$(.with_text($description))+)
+                    .with_element(fieldset())
+                    .with_element(
+                        HtmlElement::new("button")
+                            .with_id(concat!($class, "-output"))
+                            .with_attribute("type", "submit")
+                            .with_text("Generate and download"),
+                    )
+            }
+
+            fn fieldset() -> HtmlElement {
+                let settings = config::from_file::parse(DEFAULT_CONFIG_FILE);
+                let settings = settings.as_ref();
+
+                HtmlElement::new("fieldset")
+                    .with_element(
+                        HtmlElement::new("legend").with_text(concat!($title, " Settings:")),
+                    )
+                    .with_element(html::page::labeled(concat!($class, "-seed"), "", "seed:", {
+                        let input = HtmlElement::new("input")
+                            .with_attribute("type", "number")
+                            .required();
+
+                        if let Some(settings) = settings {
+                            input.with_attribute("value", *settings.seed())
+                        } else {
+                            input
+                        }
+                    }))
+                    .with_element(html::page::labeled(
+                        concat!($class, "-requires"),
+                        "",
+                        "required plugins (comma separated):",
+                        {
+                            let input = HtmlElement::new("input").with_attribute("type", "text");
+
+                            if let Some(settings) = settings {
+                                input.with_attribute("value", settings.requires().join(", ").as_str())
+                            } else {
+                                input
+                            }
+                        },
+                    ))
+            }
+        }
+    };
+}
+
+pub use __seed_only_config as seed_only_config;
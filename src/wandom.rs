@@ -4,8 +4,14 @@ pub mod shuffle_index {
     use super::XoShiRo256SS;
 
     pub trait ShuffleIndex {
+        type Item;
+
         fn len(&self) -> usize;
 
+        fn item_at(&self, index: usize) -> Option<&Self::Item>;
+
+        /// Empty collections give no indices and a single item only ever maps to itself, and
+        /// neither steps the rng, so they don't change what anything shuffled after them gets
         fn shuffled_indices_with_rng(&self, rng: &mut XoShiRo256SS) -> Vec<usize> {
             let mut indices = (0..(self.len())).collect::<Vec<usize>>();
 
@@ -24,24 +30,96 @@ pub mod shuffle_index {
             let mut rng = XoShiRo256SS::new(seed);
             self.shuffled_indices_with_rng(&mut rng)
         }
+
+        /// Pairs every item with its replacement, in the same order as `shuffled_indices_with_rng`
+        fn shuffled_pairs_with_rng(
+            &self,
+            rng: &mut XoShiRo256SS,
+        ) -> Vec<(&Self::Item, &Self::Item)> {
+            self.shuffled_indices_with_rng(rng)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(original, replacement)| {
+                    Some((self.item_at(original)?, self.item_at(replacement)?))
+                })
+                .collect()
+        }
+
+        fn shuffled_pairs(&self, seed: u64) -> Vec<(&Self::Item, &Self::Item)> {
+            let mut rng = XoShiRo256SS::new(seed);
+            self.shuffled_pairs_with_rng(&mut rng)
+        }
+
+        /// Like `shuffled_indices_with_rng`, but no index stays where it was unless there are fewer
+        /// than two of them, using Sattolo's algorithm, so every item is part of one big cycle
+        fn deranged_indices_with_rng(&self, rng: &mut XoShiRo256SS) -> Vec<usize> {
+            let mut indices = (0..(self.len())).collect::<Vec<usize>>();
+
+            for i in (1..(self.len())).rev() {
+                let j = rng.rand_range(0, i as u64);
+                indices.swap(
+                    usize::try_from(j).expect("The index swap range will always be within a usize"),
+                    i,
+                );
+            }
+
+            indices
+        }
+
+        fn deranged_indices(&self, seed: u64) -> Vec<usize> {
+            let mut rng = XoShiRo256SS::new(seed);
+            self.deranged_indices_with_rng(&mut rng)
+        }
+
+        /// Pairs every item with its replacement, in the same order as `deranged_indices_with_rng`
+        fn deranged_pairs_with_rng(
+            &self,
+            rng: &mut XoShiRo256SS,
+        ) -> Vec<(&Self::Item, &Self::Item)> {
+            self.deranged_indices_with_rng(rng)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(original, replacement)| {
+                    Some((self.item_at(original)?, self.item_at(replacement)?))
+                })
+                .collect()
+        }
     }
 
     impl<T> ShuffleIndex for &[T] {
+        type Item = T;
+
         fn len(&self) -> usize {
             <[T]>::len(self)
         }
+
+        fn item_at(&self, index: usize) -> Option<&T> {
+            self.get(index)
+        }
     }
 
     impl<T> ShuffleIndex for Vec<T> {
+        type Item = T;
+
         fn len(&self) -> usize {
             self.len()
         }
+
+        fn item_at(&self, index: usize) -> Option<&T> {
+            self.get(index)
+        }
     }
 
     impl<const N: usize, T> ShuffleIndex for [T; N] {
+        type Item = T;
+
         fn len(&self) -> usize {
             self.as_slice().len()
         }
+
+        fn item_at(&self, index: usize) -> Option<&T> {
+            self.get(index)
+        }
     }
 }
 
@@ -135,6 +213,66 @@ pub mod weighted_choice {
     }
 }
 
+/// Maps a JavaScript number to a seed without silently losing precision
+/// Whole numbers from 0 to 2^53 are exact in a double, so they are used as they are
+/// Anything else (fractions, negatives, infinities, and numbers too big to be exact) uses the bits of the double
+pub fn seed_from_f64(value: f64) -> u64 {
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+
+    if (0.0..=MAX_EXACT).contains(&value) && value.fract().to_bits() == 0 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let seed = value as u64;
+
+        seed
+    } else {
+        value.to_bits()
+    }
+}
+
+/// A seed that only depends on the date, so everyone generating on the same day gets the same universe
+/// It reads as the date itself (2026-10-16 is 20261016) as long as the month and day are in range
+pub fn seed_from_date(year: i32, month: u32, day: u32) -> u64 {
+    u64::from(year.cast_unsigned()) * 10_000 + u64::from(month) * 100 + u64::from(day)
+}
+
+/// A seed from a phrase, so a memorable one can be shared instead of a number
+/// It's the 64-bit FNV-1a hash of the UTF-8 bytes, which comes out the same on every platform,
+/// unlike `DefaultHasher`, whose algorithm is free to change between Rust versions
+pub const fn seed_from_phrase(phrase: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let bytes = phrase.as_bytes();
+    let mut hash = OFFSET_BASIS;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        hash ^= bytes[index] as u64;
+        hash = hash.wrapping_mul(PRIME);
+        index += 1;
+    }
+
+    hash
+}
+
+/// The web UI treats a seed of 0 as "pick one for me", since wasm has no randomness of its own
+/// Zero is replaced by a seed mixed from `entropy` (like JS `Math.random()`), which is never zero, and
+/// the generators record whichever seed they end up with so the result can be reproduced
+pub const fn seed_or_random(seed: u64, entropy: f64) -> u64 {
+    if seed != 0 {
+        return seed;
+    }
+
+    match XoShiRo256SS::new(entropy.to_bits()).step() {
+        0 => 1,
+        seed => seed,
+    }
+}
+
+/// How many outputs `XoShiRo256SS::with_warmup` should usually throw away
+/// xoshiro256 fully mixes its state within about a dozen steps, so 16 leaves some headroom
+pub const WARMUP_STEPS: usize = 16;
+
 pub struct XoShiRo256SS {
     state: [u64; 4],
 }
@@ -152,6 +290,21 @@ impl XoShiRo256SS {
         Self { state }
     }
 
+    /// Seeds like `new`, then throws away the first `warmup` outputs
+    /// Very similar seeds (like a base seed plus a preset index) can have correlated first outputs,
+    /// and a few steps are enough to mix every bit of the state into every other bit
+    pub const fn with_warmup(seed: u64, warmup: usize) -> Self {
+        let mut rng = Self::new(seed);
+        let mut step = 0;
+
+        while step < warmup {
+            rng.step();
+            step += 1;
+        }
+
+        rng
+    }
+
     pub const fn step(&mut self) -> u64 {
         let value = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
 
@@ -215,3 +368,153 @@ impl SplitMix64 {
         value ^ value.wrapping_shr(31)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        WARMUP_STEPS, XoShiRo256SS, seed_from_date, seed_from_f64, seed_from_phrase,
+        shuffle_index::ShuffleIndex,
+    };
+
+    use std::collections::HashSet;
+
+    const PAIRS: u64 = 10_000;
+
+    /// Which bits of the first output flip between each seed and the next one, like the per-domain
+    /// seeds someone picks by hand
+    fn adjacent_flips(warmup: usize) -> impl Iterator<Item = u64> {
+        (0..PAIRS).map(move |seed| {
+            XoShiRo256SS::with_warmup(seed, warmup).step()
+                ^ XoShiRo256SS::with_warmup(seed + 1, warmup).step()
+        })
+    }
+
+    #[test]
+    fn adjacent_seeds_flip_half_the_bits() {
+        let total_distance = adjacent_flips(WARMUP_STEPS)
+            .map(|flips| u64::from(flips.count_ones()))
+            .sum::<u64>();
+
+        // independent outputs differ in 32 of their 64 bits on average
+        assert!(
+            (31 * PAIRS..=33 * PAIRS).contains(&total_distance),
+            "{total_distance} bits flipped over {PAIRS} pairs"
+        );
+    }
+
+    #[test]
+    fn adjacent_seeds_flip_every_bit_evenly() {
+        let mut bit_flips = [0_u64; 64];
+
+        for flips in adjacent_flips(WARMUP_STEPS) {
+            for (bit, count) in bit_flips.iter_mut().enumerate() {
+                *count += (flips >> bit) & 1;
+            }
+        }
+
+        // a bit that flips well off half the time would tie neighbouring seeds together
+        for (bit, count) in bit_flips.into_iter().enumerate() {
+            assert!(
+                (PAIRS * 45 / 100..=PAIRS * 55 / 100).contains(&count),
+                "bit {bit} flipped {count} times over {PAIRS} pairs"
+            );
+        }
+    }
+
+    #[test]
+    fn warmup_only_skips_outputs() {
+        let mut rng = XoShiRo256SS::new(7);
+
+        for _ in 0..WARMUP_STEPS {
+            rng.step();
+        }
+
+        assert_eq!(
+            XoShiRo256SS::with_warmup(7, WARMUP_STEPS).step(),
+            rng.step()
+        );
+    }
+
+    #[test]
+    fn every_date_has_its_own_seed() {
+        assert_eq!(seed_from_date(2026, 10, 16), 20_261_016);
+        assert_eq!(seed_from_date(2026, 10, 16), seed_from_date(2026, 10, 16));
+
+        let mut seeds = HashSet::new();
+
+        for year in 1900..2200 {
+            for month in 1..=12 {
+                for day in 1..=31 {
+                    assert!(
+                        seeds.insert(seed_from_date(year, month, day)),
+                        "{year}-{month}-{day} shares its seed with another date"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn phrases_hash_the_same_everywhere() {
+        // the published FNV-1a test vectors, which a platform-dependent hash wouldn't match
+        assert_eq!(seed_from_phrase(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(seed_from_phrase("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(seed_from_phrase("foobar"), 0x8594_4171_f739_67e8);
+
+        assert_ne!(seed_from_phrase("Sol"), seed_from_phrase("sol"));
+    }
+
+    #[test]
+    fn js_numbers_keep_their_seeds_apart() {
+        const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+
+        assert_eq!(seed_from_f64(0.0), 0);
+        assert_eq!(seed_from_f64(12345.0), 12345);
+        assert_eq!(seed_from_f64(MAX_EXACT), 1 << 53);
+
+        // past 2^53 neighbouring doubles are 2 or more apart, so a cast would start merging them
+        let numbers = [
+            MAX_EXACT,
+            MAX_EXACT + 2.0,
+            MAX_EXACT + 4.0,
+            MAX_EXACT * 2.0,
+            18_014_398_509_481_988.0,
+            1e20,
+            1e20 + 16_384.0,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            -1.0,
+            -0.0,
+            0.5,
+            1.5,
+        ];
+
+        let seeds = numbers.map(seed_from_f64);
+
+        assert_eq!(seeds, numbers.map(seed_from_f64));
+        assert_eq!(seeds.iter().collect::<HashSet<_>>().len(), numbers.len());
+    }
+
+    #[test]
+    fn fewer_than_two_items_shuffle_to_themselves() {
+        let mut rng = XoShiRo256SS::new(7);
+
+        let empty: [&str; 0] = [];
+
+        assert!(empty.shuffled_indices_with_rng(&mut rng).is_empty());
+        assert!(empty.deranged_indices_with_rng(&mut rng).is_empty());
+        assert!(empty.shuffled_pairs_with_rng(&mut rng).is_empty());
+        assert!(empty.deranged_pairs_with_rng(&mut rng).is_empty());
+
+        let single = ["Sol"];
+
+        assert_eq!(single.shuffled_indices_with_rng(&mut rng), [0]);
+        assert_eq!(single.deranged_indices_with_rng(&mut rng), [0]);
+        assert_eq!(single.shuffled_pairs_with_rng(&mut rng), [(&"Sol", &"Sol")]);
+        assert_eq!(single.deranged_pairs_with_rng(&mut rng), [(&"Sol", &"Sol")]);
+
+        // none of them stepped the rng
+        assert_eq!(rng.step(), XoShiRo256SS::new(7).step());
+    }
+}
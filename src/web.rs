@@ -1,3 +1,5 @@
+use crate::generators::{json_string, system_shuffler::config::SystemShufflerConfig};
+
 use endless_sky_rw::DataFolder;
 
 use std::{error::Error, io};
@@ -26,6 +28,138 @@ pub fn generate_system_shuffler(
         .map_err(|error| error.to_string())
 }
 
+/// The plugin and its JSON report, since a wasm function can only return one value
+#[wasm_bindgen]
+pub struct GeneratedWithReport {
+    plugin_bytes: Vec<u8>,
+    report_json: String,
+}
+
+#[wasm_bindgen]
+impl GeneratedWithReport {
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn plugin_bytes(&self) -> Vec<u8> {
+        self.plugin_bytes.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn report_json(&self) -> String {
+        self.report_json.clone()
+    }
+}
+
+/// Lets the frontend read back the config it built, to show it or put it in a share link
+/// The Rust getters of the same names return references, which wasm can't hand out, so these copy
+#[wasm_bindgen]
+#[allow(clippy::missing_const_for_fn)]
+impl SystemShufflerConfig {
+    #[wasm_bindgen(getter = seed)]
+    #[must_use]
+    pub fn js_seed(&self) -> u64 {
+        *self.seed()
+    }
+
+    #[wasm_bindgen(getter = max_presets)]
+    #[must_use]
+    pub fn js_max_presets(&self) -> u8 {
+        *self.max_presets()
+    }
+
+    #[wasm_bindgen(getter = shuffle_chance)]
+    #[must_use]
+    pub fn js_shuffle_chance(&self) -> u8 {
+        *self.shuffle_chance()
+    }
+
+    #[wasm_bindgen(getter = fixed_shuffle_days)]
+    #[must_use]
+    pub fn js_fixed_shuffle_days(&self) -> u8 {
+        *self.fixed_shuffle_days()
+    }
+
+    #[wasm_bindgen(getter = shuffle_once_on_install)]
+    #[must_use]
+    pub fn js_shuffle_once_on_install(&self) -> bool {
+        *self.shuffle_once_on_install()
+    }
+}
+
+/// Generates the system shuffler along with a JSON report of its seed, swaps, counts, and warnings
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_with_report(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: crate::generators::system_shuffler::config::SystemShufflerConfig,
+) -> Result<JsValue, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| {
+            crate::generators::system_shuffler::process_parsed_with_report(
+                data_folder.data(),
+                settings,
+            )
+        })
+        .map(|(plugin_bytes, report)| {
+            GeneratedWithReport {
+                plugin_bytes,
+                report_json: report.to_json(),
+            }
+            .into()
+        })
+        .map_err(|error| error.to_string())
+}
+
+/// How many presets the settings would make that are identical to an earlier one, so the UI can
+/// suggest fewer presets or more systems
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn count_duplicate_presets(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: &crate::generators::system_shuffler::config::SystemShufflerConfig,
+) -> Result<u32, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| {
+            crate::generators::system_shuffler::count_duplicate_presets(
+                data_folder.data(),
+                settings,
+            )
+        })
+        .map(|duplicates| u32::try_from(duplicates).unwrap_or(u32::MAX))
+        .map_err(|error| error.to_string())
+}
+
+/// Warnings about settings that won't generate, or won't do all they say, with this upload
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn validate_config(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: &crate::generators::system_shuffler::config::SystemShufflerConfig,
+) -> Result<Vec<String>, String> {
+    read_upload(paths, sources)
+        .map(|data_folder| {
+            crate::generators::system_shuffler::validate_config(data_folder.data(), settings)
+        })
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_system_shuffler_per_region(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: &crate::generators::system_shuffler::config::SystemShufflerConfig,
+) -> Result<Vec<u8>, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| {
+            crate::generators::system_shuffler::process_data_per_region(&data_folder, settings)
+        })
+        .map_err(|error| error.to_string())
+}
+
 #[wasm_bindgen]
 #[allow(clippy::missing_errors_doc)]
 pub fn generate_chaos(
@@ -38,6 +172,94 @@ pub fn generate_chaos(
         .map_err(|error| error.to_string())
 }
 
+/// Like `generate_chaos`, but packs the images its sprites point to into the plugin
+/// wasm can't take a list of byte arrays, so every image's bytes are concatenated into one, with
+/// `image_lengths` saying where each one ends
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_chaos_with_images(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    image_paths: Vec<String>,
+    image_bytes: &[u8],
+    image_lengths: Vec<u32>,
+    settings: &crate::generators::chaos::config::ChaosConfig,
+) -> Result<Vec<u8>, String> {
+    if image_paths.len() != image_lengths.len()
+        || image_lengths
+            .iter()
+            .map(|&length| length as usize)
+            .sum::<usize>()
+            != image_bytes.len()
+    {
+        return Err(
+            "Every image needs a path and a length that add up to the bytes :(".to_string(),
+        );
+    }
+
+    let mut remaining_bytes = image_bytes;
+
+    let images = image_paths
+        .into_iter()
+        .zip(image_lengths)
+        .map(|(image_path, length)| {
+            let (bytes, rest) = remaining_bytes.split_at(length as usize);
+
+            remaining_bytes = rest;
+
+            (image_path, bytes.to_vec())
+        })
+        .collect::<Vec<_>>();
+
+    read_upload(paths, sources)
+        .and_then(|data_folder| {
+            crate::generators::chaos::process_parsed_with_images(
+                data_folder.data(),
+                images.as_slice(),
+                settings,
+            )
+        })
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_jump_chaos(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: &crate::generators::jump_chaos::config::JumpChaosConfig,
+) -> Result<Vec<u8>, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| crate::generators::jump_chaos::process_data(&data_folder, settings))
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_spaceport_chaos(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: &crate::generators::spaceport_chaos::config::SpaceportChaosConfig,
+) -> Result<Vec<u8>, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| {
+            crate::generators::spaceport_chaos::process_data(&data_folder, settings)
+        })
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_star_chaos(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    settings: &crate::generators::star_chaos::config::StarChaosConfig,
+) -> Result<Vec<u8>, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| crate::generators::star_chaos::process_data(&data_folder, settings))
+        .map_err(|error| error.to_string())
+}
+
 #[wasm_bindgen]
 #[allow(clippy::missing_errors_doc)]
 pub fn generate_random_galaxy(
@@ -52,6 +274,216 @@ pub fn generate_random_galaxy(
         .map_err(|error| error.to_string())
 }
 
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_full_map_plugin_txt() -> Result<String, String> {
+    crate::generators::full_map::preview_plugin_txt().map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_system_shuffler_plugin_txt(
+    settings: &crate::generators::system_shuffler::config::SystemShufflerConfig,
+) -> Result<String, String> {
+    crate::generators::system_shuffler::preview_plugin_txt(settings)
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_chaos_plugin_txt(
+    settings: &crate::generators::chaos::config::ChaosConfig,
+) -> Result<String, String> {
+    crate::generators::chaos::preview_plugin_txt(settings).map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_jump_chaos_plugin_txt(
+    settings: &crate::generators::jump_chaos::config::JumpChaosConfig,
+) -> Result<String, String> {
+    crate::generators::jump_chaos::preview_plugin_txt(settings).map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_spaceport_chaos_plugin_txt(
+    settings: &crate::generators::spaceport_chaos::config::SpaceportChaosConfig,
+) -> Result<String, String> {
+    crate::generators::spaceport_chaos::preview_plugin_txt(settings)
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_star_chaos_plugin_txt(
+    settings: &crate::generators::star_chaos::config::StarChaosConfig,
+) -> Result<String, String> {
+    crate::generators::star_chaos::preview_plugin_txt(settings).map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn preview_random_galaxy_plugin_txt(
+    settings: &crate::generators::random_galaxy::config::RandomGalaxyConfig,
+) -> Result<String, String> {
+    crate::generators::random_galaxy::preview_plugin_txt(settings)
+        .map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn generate_bundle(
+    paths: Vec<String>,
+    sources: Vec<String>,
+    shuffler_settings: crate::generators::system_shuffler::config::SystemShufflerConfig,
+    chaos_settings: &crate::generators::chaos::config::ChaosConfig,
+    include_full_map: bool,
+) -> Result<Vec<u8>, String> {
+    read_upload(paths, sources)
+        .and_then(|data_folder| {
+            crate::generators::bundle::process_data(
+                &data_folder,
+                shuffler_settings,
+                chaos_settings,
+                include_full_map,
+            )
+        })
+        .map_err(|error| error.to_string())
+}
+
+/// Turns the bytes of one uploaded file into its text, inflating it first if it's gzip-compressed
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn decode_source(bytes: Vec<u8>) -> Result<String, String> {
+    crate::generators::decode_source(bytes).map_err(|error| error.to_string())
+}
+
+#[wasm_bindgen]
+#[must_use]
+pub fn seed_from_f64(value: f64) -> u64 {
+    crate::wandom::seed_from_f64(value)
+}
+
+#[wasm_bindgen]
+#[must_use]
+#[allow(clippy::missing_const_for_fn)]
+pub fn seed_or_random(seed: u64, entropy: f64) -> u64 {
+    crate::wandom::seed_or_random(seed, entropy)
+}
+
+#[wasm_bindgen]
+#[must_use]
+#[allow(clippy::missing_const_for_fn)]
+pub fn seed_from_phrase(phrase: &str) -> u64 {
+    crate::wandom::seed_from_phrase(phrase)
+}
+
+#[wasm_bindgen]
+#[must_use]
+pub fn seed_from_date(year: i32, month: u32, day: u32) -> u64 {
+    crate::wandom::seed_from_date(year, month, day)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn list_planets(paths: Vec<String>, sources: Vec<String>) -> Result<JsValue, String> {
+    let data_folder = read_upload(paths, sources).map_err(|error| error.to_string())?;
+
+    let planet_systems = crate::generators::system_shuffler::planet_systems(data_folder.data())
+        .iter()
+        .map(|(planet_name, system_names)| {
+            format!(
+                "{}:[{}]",
+                json_string(planet_name),
+                system_names
+                    .iter()
+                    .map(|system_name| json_string(system_name))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    self::import_from_javascript::parse_json(format!("{{{planet_systems}}}").as_str())
+        .map_err(|error| format!("{error:?}"))
+}
+
+/// Lists the systems added, removed, or relinked between two uploads, so mod authors can tell
+/// whether a regenerated shuffler will differ after a game update
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn diff_systems(
+    paths_a: Vec<String>,
+    sources_a: Vec<String>,
+    paths_b: Vec<String>,
+    sources_b: Vec<String>,
+) -> Result<JsValue, String> {
+    let data_folder_a = read_upload(paths_a, sources_a).map_err(|error| error.to_string())?;
+    let data_folder_b = read_upload(paths_b, sources_b).map_err(|error| error.to_string())?;
+
+    let diff = crate::generators::system_shuffler::diff_systems(
+        data_folder_a.data(),
+        data_folder_b.data(),
+    );
+
+    let json_list = |system_names: &[&str]| {
+        system_names
+            .iter()
+            .map(|system_name| json_string(system_name))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    self::import_from_javascript::parse_json(
+        format!(
+            "{{\"added\":[{}],\"removed\":[{}],\"changed\":[{}]}}",
+            json_list(diff.added.as_slice()),
+            json_list(diff.removed.as_slice()),
+            json_list(diff.changed.as_slice()),
+        )
+        .as_str(),
+    )
+    .map_err(|error| format!("{error:?}"))
+}
+
+/// A debugging tool, not part of any generator: dumps every parsed root node (and all of its children) as JSON
+/// The output is several times bigger than the uploaded data, so expect it to be huge for the full game data
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn data_to_json(paths: Vec<String>, sources: Vec<String>) -> Result<String, String> {
+    let data_folder = read_upload(paths, sources).map_err(|error| error.to_string())?;
+
+    Ok(crate::generators::data_to_json(data_folder.data()))
+}
+
+/// Lists every uploaded file that had parse errors, along with those errors
+/// Each file is parsed on its own, so every error can be traced back to the file it came from
+#[wasm_bindgen]
+#[allow(clippy::missing_errors_doc)]
+pub fn upload_errors(paths: Vec<String>, sources: Vec<String>) -> Result<JsValue, String> {
+    let file_errors = paths
+        .into_iter()
+        .zip(sources)
+        .filter_map(|(path, source)| {
+            let (_, errors) = endless_sky_rw::read_upload(vec![path.clone()], vec![source])?;
+
+            (!errors.is_empty()).then(|| {
+                format!(
+                    "{{\"path\":{},\"error\":{}}}",
+                    json_string(path.as_str()),
+                    json_string(String::from_utf8_lossy(errors.as_slice()).trim())
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    self::import_from_javascript::parse_json(format!("[{file_errors}]").as_str())
+        .map_err(|error| format!("{error:?}"))
+}
+
 fn read_upload(paths: Vec<String>, sources: Vec<String>) -> Result<DataFolder, Box<dyn Error>> {
     match endless_sky_rw::read_upload(paths, sources) {
         Some((data_folder, errors)) => {
@@ -73,7 +505,7 @@ fn read_upload(paths: Vec<String>, sources: Vec<String>) -> Result<DataFolder, B
     }
 }
 
-mod import_from_javascript {
+pub mod import_from_javascript {
     use wasm_bindgen::prelude::*;
 
     #[wasm_bindgen]
@@ -86,5 +518,8 @@ mod import_from_javascript {
 
         #[wasm_bindgen(js_namespace = console)]
         pub fn error(text: &str);
+
+        #[wasm_bindgen(catch, js_namespace = JSON, js_name = parse)]
+        pub fn parse_json(text: &str) -> Result<JsValue, JsValue>;
     }
 }
@@ -9,23 +9,45 @@ use rawzip::{self, CompressionMethod, ZipArchiveWriter};
 
 type ZipBytes<'a> = io::Cursor<&'a mut Vec<u8>>;
 
+/// Makes a name (like a government's) safe to use as one part of a path in the archive
+pub fn sanitize_zip_path(name: &str) -> String {
+    name.replace(['/', '\\'], "_")
+}
+
 pub struct Zip<'a> {
     writer: ZipArchiveWriter<ZipBytes<'a>>,
+    data_dir: String,
 }
 
 impl<'a> Zip<'a> {
     pub fn new(output: &'a mut Vec<u8>) -> Self {
         Zip {
             writer: ZipArchiveWriter::new(io::Cursor::new(output)),
+            data_dir: "data/".to_owned(),
         }
     }
 
+    /// Redirects everything written under `data/` into another directory
+    /// This lets several generators share one archive without their data files colliding
+    pub fn set_data_dir<S: Into<String>>(&mut self, data_dir: S) {
+        self.data_dir = data_dir.into();
+    }
+
+    fn archive_path<P: Into<PathBuf>>(&self, path: P) -> String {
+        let path = P::into(path).display().to_string();
+
+        path.strip_prefix("data/").map_or_else(
+            || path.clone(),
+            |data_path| format!("{}{data_path}", self.data_dir),
+        )
+    }
+
     pub fn write_file<P: Into<PathBuf>>(
         &mut self,
         path: P,
         bytes: &[u8],
     ) -> Result<(), Box<dyn Error>> {
-        let path = P::into(path).display().to_string();
+        let path = self.archive_path(path);
 
         let (mut entry, config) = self
             .writer
@@ -39,15 +61,45 @@ impl<'a> Zip<'a> {
 
         writer.write_all(bytes)?;
 
-        let (_, descriptor) = writer.finish()?;
+        let (encoder, descriptor) = writer.finish()?;
+
+        // finish the deflate stream explicitly so the final block (the only block, for empty files)
+        // is written before the entry is closed, and any error writing it is returned here instead
+        // of being swallowed when the encoder is dropped
+        encoder.finish()?;
 
         let _compressed_len = entry.finish(descriptor)?;
 
         Ok(())
     }
 
+    /// Writes without compressing, for files like PNGs that are already compressed
+    pub fn write_file_stored<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        bytes: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        let path = self.archive_path(path);
+
+        let (mut entry, config) = self
+            .writer
+            .new_file(path.as_str())
+            .compression_method(CompressionMethod::STORE)
+            .start()?;
+
+        let mut writer = config.wrap(&mut entry);
+
+        writer.write_all(bytes)?;
+
+        let (_, descriptor) = writer.finish()?;
+
+        let _stored_len = entry.finish(descriptor)?;
+
+        Ok(())
+    }
+
     pub fn write_dir<P: Into<PathBuf>>(&mut self, path: P) -> Result<(), Box<dyn Error>> {
-        let path = P::into(path).display().to_string();
+        let path = self.archive_path(path);
         self.writer.new_dir(path.as_str()).create()?;
         Ok(())
     }
@@ -56,3 +108,61 @@ impl<'a> Zip<'a> {
         Ok(self.writer.finish()?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Zip;
+
+    use flate2::read::DeflateDecoder;
+
+    use std::io::Read;
+
+    #[test]
+    fn empty_file_is_extractable() {
+        let mut output = Vec::new();
+
+        let mut zip = Zip::new(&mut output);
+
+        zip.write_file("data/empty.txt", &[])
+            .expect("an empty file should be writable");
+
+        zip.finish().expect("the archive should finish");
+
+        let archive =
+            rawzip::ZipArchive::from_slice(output.as_slice()).expect("the output should be a zip");
+
+        let mut entries = archive.entries();
+
+        let entry = entries
+            .next_entry()
+            .expect("the central directory should be readable")
+            .expect("the file should be in the archive");
+
+        let path = entry
+            .file_path()
+            .try_normalize()
+            .expect("the path should be valid");
+
+        assert_eq!(AsRef::<str>::as_ref(&path), "data/empty.txt");
+
+        let local_entry = archive
+            .get_entry(entry.wayfinder())
+            .expect("the entry should be where the central directory says");
+
+        let mut contents = Vec::new();
+
+        local_entry
+            .verifying_reader(DeflateDecoder::new(local_entry.data()))
+            .read_to_end(&mut contents)
+            .expect("the empty deflate stream should decompress and match its checksum");
+
+        assert!(contents.is_empty());
+
+        assert!(
+            entries
+                .next_entry()
+                .expect("the central directory should be readable")
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Checks the outfits and ships chaos writes for the shared fixture universe
+
+mod common;
+
+use common::Tree;
+
+use std::collections::BTreeMap;
+
+use endless_sky_generator_web::generators::chaos::{
+    self,
+    config::{ChaosBuilder, ChaosConfig},
+};
+
+const UNIVERSE: &str = include_str!("fixtures/universe.txt");
+
+/// Only outfits and ships, with no outfit or ship ever left as itself
+fn settings() -> ChaosBuilder {
+    ChaosConfig::builder()
+        .seed(7)
+        .systems(false)
+        .planets(false)
+        .no_self_swap(true)
+}
+
+fn scramble(settings: &ChaosConfig) -> Vec<u8> {
+    scramble_universe(UNIVERSE, settings)
+}
+
+fn scramble_universe(universe: &str, settings: &ChaosConfig) -> Vec<u8> {
+    chaos::process_data(&common::parse(&[("data/universe.txt", universe)]), settings)
+        .expect("the fixture should scramble")
+}
+
+/// Which outfit or ship became which, from the `kind "original" -> "swap"` lines of `swaps.txt`
+fn swaps(output: &[u8], kind: &str) -> BTreeMap<String, String> {
+    common::unzip(output)
+        .get("swaps.txt")
+        .expect("every plugin should list its swaps")
+        .lines()
+        .filter_map(|line| line.strip_prefix(kind)?.strip_prefix(' '))
+        .map(|swap| {
+            let (original, replacement) = swap
+                .split_once(" -> ")
+                .expect("every swap should have an arrow");
+
+            (
+                original.trim_matches('"').to_string(),
+                replacement.trim_matches('"').to_string(),
+            )
+        })
+        .collect()
+}
+
+/// The children of an outfit's redefinition that start with `prefix`
+fn outfit_children<'a>(roots: &'a [Tree], outfit: &str, prefix: &[&str]) -> Vec<&'a Tree> {
+    common::root(roots, &["outfit", outfit])
+        .children_with(prefix)
+        .collect()
+}
+
+#[test]
+fn outfits_keep_their_own_footprint() {
+    let roots =
+        common::reparse(scramble(&settings().preserve_outfit_space(true).build()).as_slice());
+
+    for (outfit, mass, outfit_space) in [("Ion Engine", "20", "-20"), ("Laser Cannon", "10", "-10")]
+    {
+        let mass_nodes = outfit_children(&roots, outfit, &["mass"]);
+        let outfit_space_nodes = outfit_children(&roots, outfit, &["outfit space"]);
+
+        assert_eq!(mass_nodes.len(), 1, "{outfit}");
+        assert_eq!(mass_nodes[0].tokens, ["mass", mass], "{outfit}");
+
+        assert_eq!(outfit_space_nodes.len(), 1, "{outfit}");
+        assert_eq!(
+            outfit_space_nodes[0].tokens,
+            ["outfit space", outfit_space],
+            "{outfit}"
+        );
+    }
+
+    let roots = common::reparse(scramble(&settings().build()).as_slice());
+
+    for outfit in ["Ion Engine", "Laser Cannon"] {
+        assert!(outfit_children(&roots, outfit, &["mass"]).is_empty());
+        assert!(outfit_children(&roots, outfit, &["outfit space"]).is_empty());
+    }
+}
+
+#[test]
+fn same_seed_gives_the_same_plugin() {
+    let settings = |seed| {
+        ChaosConfig::builder()
+            .seed(seed)
+            .landscapes(true)
+            .by_category(true)
+            .weight_by_cost(true)
+            .build()
+    };
+
+    // each run parses the fixture again, so nothing carries over between them
+    let first = scramble(&settings(7));
+    let second = scramble(&settings(7));
+
+    assert!(
+        first == second,
+        "the same seed should give a byte-identical zip"
+    );
+
+    assert!(
+        first != scramble(&settings(8)),
+        "a different seed should give a different zip"
+    );
+}
+
+const OUTFITS: &str = "\
+outfit \"Alpha Engine\"
+\tthumbnail outfit/alpha
+\tcost 100
+
+outfit \"Beta Gun\"
+\tthumbnail outfit/beta
+\tcost 200
+
+outfit \"Gamma Shield\"
+\tthumbnail outfit/gamma
+\tcost 300
+
+outfit \"Delta Pod\"
+\tthumbnail outfit/delta
+\tcost 400
+
+outfit \"Epsilon Core\"
+\tthumbnail outfit/epsilon
+\tcost 500
+";
+
+#[test]
+fn excluding_an_outfit_leaves_the_other_swaps_alone() {
+    let settings = || {
+        ChaosConfig::builder()
+            .seed(7)
+            .ships(false)
+            .systems(false)
+            .planets(false)
+    };
+
+    let all = swaps(
+        scramble_universe(OUTFITS, &settings().build()).as_slice(),
+        "outfit",
+    );
+
+    let excluded = swaps(
+        scramble_universe(
+            OUTFITS,
+            &settings().exclude(vec!["Beta Gun".to_string()]).build(),
+        )
+        .as_slice(),
+        "outfit",
+    );
+
+    assert_eq!(all.len(), 5);
+    assert_eq!(excluded.len(), 4);
+    assert!(!excluded.contains_key("Beta Gun"));
+
+    // whatever would have become Beta Gun becomes what Beta Gun would have instead, and every
+    // other outfit is swapped exactly as before
+    for (original, replacement) in all.iter().filter(|(original, _)| *original != "Beta Gun") {
+        let expected = if replacement == "Beta Gun" {
+            &all["Beta Gun"]
+        } else {
+            replacement
+        };
+
+        assert_eq!(&excluded[original], expected, "{original}");
+    }
+}
+
+#[test]
+fn exclude_covers_outfits_and_ships() {
+    let output = scramble(
+        &settings()
+            .exclude(vec!["Ion Engine".to_string(), "Shuttle".to_string()])
+            .build(),
+    );
+
+    assert!(!swaps(output.as_slice(), "outfit").contains_key("Ion Engine"));
+    assert!(!swaps(output.as_slice(), "ship").contains_key("Shuttle"));
+
+    let roots = common::reparse(output.as_slice());
+
+    assert!(
+        !roots
+            .iter()
+            .any(|root| root.starts_with(&["outfit", "Ion Engine"]))
+    );
+    assert!(
+        !roots
+            .iter()
+            .any(|root| root.starts_with(&["ship", "Shuttle"]))
+    );
+}
@@ -0,0 +1,204 @@
+// every test file only uses some of these
+#![allow(dead_code)]
+
+use endless_sky_rw::{Data, DataFolder, NodeIndex, SourceIndex};
+
+use flate2::read::DeflateDecoder;
+
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+/// A node and everything nested in it, with its tokens as plain text so tests can compare them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree {
+    pub tokens: Vec<String>,
+    pub children: Vec<Self>,
+}
+
+impl Tree {
+    fn new(data: &Data, (source_index, node_index): (SourceIndex, NodeIndex)) -> Self {
+        Self {
+            tokens: data
+                .get_tokens(node_index)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|token| data.get_lexeme(source_index, token))
+                .map(ToString::to_string)
+                .collect(),
+            children: data
+                .get_children(node_index)
+                .unwrap_or_default()
+                .iter()
+                .map(|&child| Self::new(data, (source_index, child)))
+                .collect(),
+        }
+    }
+
+    /// Whether the tokens start with `prefix`, so `["system"]` matches every system
+    #[must_use]
+    pub fn starts_with(&self, prefix: &[&str]) -> bool {
+        self.tokens.len() >= prefix.len()
+            && self
+                .tokens
+                .iter()
+                .zip(prefix)
+                .all(|(token, prefix)| token == prefix)
+    }
+
+    /// Every child whose tokens start with `prefix`
+    pub fn children_with<'a>(&'a self, prefix: &[&str]) -> impl Iterator<Item = &'a Self> {
+        self.children
+            .iter()
+            .filter(move |child| child.starts_with(prefix))
+    }
+
+    /// The only child whose tokens start with `prefix`
+    #[must_use]
+    pub fn child(&self, prefix: &[&str]) -> &Self {
+        let children = self.children_with(prefix).collect::<Vec<_>>();
+
+        assert_eq!(
+            children.len(),
+            1,
+            "expected exactly one {prefix:?} in {:?}",
+            self.tokens
+        );
+
+        children[0]
+    }
+
+    /// The tokens of every child, which is all most nodes in an event need to be compared by
+    #[must_use]
+    pub fn child_tokens(&self) -> Vec<Vec<&str>> {
+        self.children
+            .iter()
+            .map(|child| child.tokens.iter().map(String::as_str).collect())
+            .collect()
+    }
+}
+
+/// Parses data files given as `(path, source)`, failing the test on any parse error
+#[must_use]
+pub fn parse(files: &[(&str, &str)]) -> DataFolder {
+    let (data_folder, errors) = endless_sky_rw::read_upload(
+        files.iter().map(|(path, _)| (*path).to_string()).collect(),
+        files
+            .iter()
+            .map(|(_, source)| (*source).to_string())
+            .collect(),
+    )
+    .expect("the files should be readable");
+
+    assert!(
+        errors.is_empty(),
+        "the files should parse cleanly:\n{}",
+        String::from_utf8_lossy(errors.as_slice())
+    );
+
+    data_folder
+}
+
+/// Every root node of some parsed data, in order
+#[must_use]
+pub fn roots(data: &Data) -> Vec<Tree> {
+    data.root_nodes()
+        .iter()
+        .map(|&root| Tree::new(data, root))
+        .collect()
+}
+
+/// Every file in a generated zip, by its path in the archive
+#[must_use]
+pub fn unzip_files(bytes: &[u8]) -> BTreeMap<String, Vec<u8>> {
+    let archive = rawzip::ZipArchive::from_slice(bytes).expect("the output should be a zip");
+
+    let mut entries = archive.entries();
+
+    let mut files = BTreeMap::new();
+
+    while let Some(entry) = entries
+        .next_entry()
+        .expect("every entry should be readable")
+    {
+        if entry.is_dir() {
+            continue;
+        }
+
+        let path = entry
+            .file_path()
+            .try_normalize()
+            .expect("every path should be valid");
+
+        let path: &str = path.as_ref();
+
+        let local_entry = archive
+            .get_entry(entry.wayfinder())
+            .expect("every entry should be where the central directory says");
+
+        let mut contents = vec![];
+
+        local_entry
+            .verifying_reader(DeflateDecoder::new(local_entry.data()))
+            .read_to_end(&mut contents)
+            .expect("every file should decompress to match its checksum");
+
+        files.insert(path.to_string(), contents);
+    }
+
+    files
+}
+
+/// Every text file in a generated zip, by its path in the archive
+#[must_use]
+pub fn unzip(bytes: &[u8]) -> BTreeMap<String, String> {
+    unzip_files(bytes)
+        .into_iter()
+        .filter(|(path, _)| {
+            Path::new(path)
+                .extension()
+                .is_some_and(|extension| extension == "txt")
+        })
+        .map(|(path, contents)| {
+            (
+                path,
+                String::from_utf8(contents).expect("every text file should be UTF-8"),
+            )
+        })
+        .collect()
+}
+
+/// Feeds the `plugin.txt` and every data file of a generated zip back through the parser, failing
+/// the test on any parse error, since the output of a generator should always be valid input
+/// Returns every root node, so tests can go on to check what was generated
+#[must_use]
+pub fn reparse(bytes: &[u8]) -> Vec<Tree> {
+    let files = unzip(bytes)
+        .into_iter()
+        .filter(|(path, _)| path == "plugin.txt" || path.starts_with("data/"))
+        .collect::<Vec<_>>();
+
+    assert!(!files.is_empty(), "the zip should have a plugin in it");
+
+    roots(
+        parse(
+            files
+                .iter()
+                .map(|(path, source)| (path.as_str(), source.as_str()))
+                .collect::<Vec<_>>()
+                .as_slice(),
+        )
+        .data(),
+    )
+}
+
+/// The root node that starts with `prefix`, like `["event", "Some Event"]`
+#[must_use]
+pub fn root<'a>(roots: &'a [Tree], prefix: &[&str]) -> &'a Tree {
+    let matching = roots
+        .iter()
+        .filter(|root| root.starts_with(prefix))
+        .collect::<Vec<_>>();
+
+    assert_eq!(matching.len(), 1, "expected exactly one {prefix:?}");
+
+    matching[0]
+}
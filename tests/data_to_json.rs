@@ -0,0 +1,32 @@
+//! The debugging export should keep every node of the upload, tokens and nesting included
+
+mod common;
+
+use endless_sky_generator_web::generators;
+
+const UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tlink \"Alpha Centauri\"
+
+system \"Alpha Centauri\"
+\tpos -100 50
+\tlink Sol
+";
+
+#[test]
+fn data_to_json_keeps_systems_and_links() {
+    let json = generators::data_to_json(common::parse(&[("data/universe.txt", UNIVERSE)]).data());
+
+    assert_eq!(
+        json,
+        concat!(
+            r#"[{"tokens":["system","Sol"],"children":["#,
+            r#"{"tokens":["pos","0","0"],"children":[]},"#,
+            r#"{"tokens":["link","Alpha Centauri"],"children":[]}]},"#,
+            r#"{"tokens":["system","Alpha Centauri"],"children":["#,
+            r#"{"tokens":["pos","-100","50"],"children":[]},"#,
+            r#"{"tokens":["link","Sol"],"children":[]}]}]"#,
+        )
+    );
+}
@@ -0,0 +1,75 @@
+//! Checks that jump chaos hands out every system's jump range, the game's default included, through
+//! an event
+
+mod common;
+
+use endless_sky_generator_web::generators::jump_chaos::{self, config::JumpChaosConfig};
+
+const EVENT_NAME: &str = "Jump Chaos: Shuffle Jump Ranges";
+
+const UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\t\"jump range\" 50
+
+system Vega
+\tpos 100 0
+\t\"jump range\" 150
+
+system Deneb
+\tpos 0 100
+
+system Rigel
+\tpos 100 100
+\t\"jump range\" 300
+";
+
+#[test]
+fn jump_ranges_are_shuffled_through_an_event() {
+    let output = jump_chaos::process_data(
+        &common::parse(&[("data/universe.txt", UNIVERSE)]),
+        &JumpChaosConfig::new(7, vec![]),
+    )
+    .expect("the fixture should scramble");
+
+    let roots = common::reparse(output.as_slice());
+
+    let event = common::root(&roots, &["event", EVENT_NAME]);
+
+    let systems = event
+        .children
+        .iter()
+        .map(|system| {
+            assert_eq!(system.tokens[0], "system");
+
+            (
+                system.tokens[1].as_str(),
+                system.child(&["jump range"]).tokens[1].as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut names = systems.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+    let mut jump_ranges = systems
+        .iter()
+        .map(|&(_, jump_range)| jump_range)
+        .collect::<Vec<_>>();
+
+    names.sort_unstable();
+    jump_ranges.sort_unstable();
+
+    assert_eq!(names, ["Deneb", "Rigel", "Sol", "Vega"]);
+
+    // Deneb never sets a jump range, so the game's default is one of the ranges handed out
+    assert_eq!(jump_ranges, ["100", "150", "300", "50"]);
+
+    // nothing redefines the systems directly, the event is the only way the ranges change
+    assert!(!roots.iter().any(|root| root.starts_with(&["system"])));
+
+    let mission = common::root(&roots, &["mission", EVENT_NAME]);
+
+    assert_eq!(
+        mission.child(&["on", "offer"]).child_tokens(),
+        [["event", EVENT_NAME, "0"]]
+    );
+}
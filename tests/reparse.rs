@@ -0,0 +1,169 @@
+//! Every generator's output should be valid input, so each one is run on the same fixture and its
+//! plugin is parsed again, which catches any quoting, precision, or comment corruption
+
+mod common;
+
+use endless_sky_generator_web::generators::{
+    self, bundle, chaos, full_map, jump_chaos, random_galaxy, spaceport_chaos, star_chaos,
+    system_shuffler,
+};
+
+use endless_sky_rw::DataFolder;
+
+use flate2::{Compression, write::GzEncoder};
+
+use std::io::Write;
+
+const UNIVERSE: &str = include_str!("fixtures/universe.txt");
+
+const RANDOM_GALAXY_CONFIG: &str = include_str!("../config/random_galaxy/default.txt");
+
+fn universe() -> DataFolder {
+    common::parse(&[("data/universe.txt", UNIVERSE)])
+}
+
+fn shuffler_settings() -> system_shuffler::config::SystemShufflerConfig {
+    system_shuffler::config::SystemShufflerConfig::builder()
+        .seed(7)
+        .max_presets(3)
+        .shuffle_chance(25)
+        .fixed_shuffle_days(30)
+        .include_inverse_presets(true)
+        .shuffle_minables(true)
+        .shuffle_hazards(true)
+        .shuffle_radii(true)
+        .normalize_music(true)
+        .include_full_map(true)
+        .shuffle_message(Some("The stars \"moved\".\n# not a comment".to_string()))
+        .build()
+}
+
+#[test]
+fn system_shuffler_output_reparses() {
+    let output = system_shuffler::process_data(&universe(), shuffler_settings())
+        .expect("the fixture should shuffle");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn system_shuffler_geometry_output_reparses() {
+    for geometry_mode in [
+        system_shuffler::config::GeometryMode::Rotate,
+        system_shuffler::config::GeometryMode::Mirror,
+    ] {
+        let settings = system_shuffler::config::SystemShufflerConfig::builder()
+            .seed(7)
+            .max_presets(3)
+            .geometry_mode(geometry_mode)
+            .build();
+
+        let output = system_shuffler::process_data(&universe(), settings)
+            .expect("the fixture should rotate and mirror");
+
+        let _ = common::reparse(output.as_slice());
+    }
+}
+
+#[test]
+fn chaos_output_reparses() {
+    let settings = chaos::config::ChaosConfig::builder()
+        .seed(7)
+        .landscapes(true)
+        .build();
+
+    let output = chaos::process_data(&universe(), &settings).expect("the fixture should scramble");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn full_map_output_reparses() {
+    let output = full_map::process_data(&universe()).expect("the fixture should be revealed");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn jump_chaos_output_reparses() {
+    let settings = jump_chaos::config::JumpChaosConfig::new(7, vec![]);
+
+    let output =
+        jump_chaos::process_data(&universe(), &settings).expect("the fixture should scramble");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn spaceport_chaos_output_reparses() {
+    let settings = spaceport_chaos::config::SpaceportChaosConfig::new(7, vec![]);
+
+    let output =
+        spaceport_chaos::process_data(&universe(), &settings).expect("the fixture should scramble");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn star_chaos_output_reparses() {
+    let settings = star_chaos::config::StarChaosConfig::new(7, vec![]);
+
+    let output =
+        star_chaos::process_data(&universe(), &settings).expect("the fixture should scramble");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn random_galaxy_output_reparses() {
+    let settings = random_galaxy::config::from_file::parse(RANDOM_GALAXY_CONFIG, true)
+        .expect("the default config should parse");
+
+    let output =
+        random_galaxy::process_data(&universe(), settings).expect("the galaxy should generate");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn bundle_output_reparses() {
+    let output = bundle::process_data(
+        &universe(),
+        shuffler_settings(),
+        &chaos::config::ChaosConfig::builder().seed(7).build(),
+        true,
+    )
+    .expect("the fixture should bundle");
+
+    let _ = common::reparse(output.as_slice());
+}
+
+#[test]
+fn gzipped_uploads_parse_like_plain_text() {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+
+    encoder
+        .write_all(UNIVERSE.as_bytes())
+        .expect("the fixture should compress");
+
+    let compressed = encoder.finish().expect("the fixture should compress");
+
+    assert_ne!(compressed.as_slice(), UNIVERSE.as_bytes());
+
+    let decoded = generators::decode_source(compressed).expect("the fixture should inflate");
+    let plain = generators::decode_source(UNIVERSE.as_bytes().to_vec())
+        .expect("plain text should pass through");
+
+    assert_eq!(decoded, plain);
+
+    assert_eq!(
+        common::roots(common::parse(&[("data/universe.txt", &decoded)]).data()),
+        common::roots(universe().data())
+    );
+}
+
+#[test]
+fn uploads_that_are_not_text_are_an_error() {
+    assert!(generators::decode_source(vec![0xff, 0xfe, 0x00]).is_err());
+    assert!(generators::decode_source(vec![0x1f, 0x8b, 0x00]).is_err());
+}
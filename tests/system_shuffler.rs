@@ -0,0 +1,1657 @@
+//! Checks what the system shuffler writes into its events for small universes where the swaps are
+//! known ahead of time, since two unpinned systems with `no_fixed_points` can only trade places
+
+mod common;
+
+use common::Tree;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use endless_sky_generator_web::generators::{
+    self,
+    system_shuffler::{self, config::SystemShufflerBuilder, config::SystemShufflerConfig},
+};
+
+/// Every setting but the ones a test cares about, with Vega and Deneb always trading places in
+/// preset 1 and Sol pinned wherever it's in the fixture
+fn settings() -> SystemShufflerBuilder {
+    SystemShufflerConfig::builder()
+        .seed(7)
+        .max_presets(1)
+        .no_fixed_points(true)
+        .pinned_systems(vec!["Sol".to_string()])
+}
+
+/// Shuffles `universe` and parses the plugin back in
+fn shuffle(universe: &str, settings: SystemShufflerConfig) -> Vec<Tree> {
+    let output =
+        system_shuffler::process_data(&common::parse(&[("data/universe.txt", universe)]), settings)
+            .expect("the fixture should shuffle");
+
+    common::reparse(output.as_slice())
+}
+
+/// The restore and activate events of a preset, or of its copy of an uploaded event
+fn preset_events<'a>(
+    roots: &'a [Tree],
+    preset_index: usize,
+    event: Option<&str>,
+) -> (&'a Tree, &'a Tree) {
+    let event_name = |action: &str| {
+        event.map_or_else(
+            || format!("System Shuffler: {action} Preset {preset_index}"),
+            |event| format!("System Shuffler: {action} Preset {preset_index}: {event}"),
+        )
+    };
+
+    (
+        common::root(roots, &["event", event_name("Restore").as_str()]),
+        common::root(roots, &["event", event_name("Activate").as_str()]),
+    )
+}
+
+/// The tokens of every child, sorted, for nodes whose order within an event doesn't matter
+fn sorted_child_tokens(tree: &Tree) -> Vec<Vec<&str>> {
+    let mut child_tokens = tree.child_tokens();
+
+    child_tokens.sort_unstable();
+
+    child_tokens
+}
+
+/// Like `sorted_child_tokens`, but only for the children whose tokens start with `prefix`
+fn sorted_child_tokens_with<'a>(tree: &'a Tree, prefix: &[&str]) -> Vec<Vec<&'a str>> {
+    sorted_child_tokens(tree)
+        .into_iter()
+        .filter(|tokens| tokens.starts_with(prefix))
+        .collect()
+}
+
+const WORMHOLE_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tlink Vega
+\tlink Deneb
+
+system Vega
+\tpos 100 0
+\tlink Sol
+\tobject Gateway
+\t\tsprite planet/wormhole
+\t\tdistance 500
+\t\tperiod 100
+
+system Deneb
+\tpos 0 100
+\tlink Sol
+\tobject Gateway
+\t\tsprite planet/wormhole
+\t\tdistance 500
+\t\tperiod 100
+
+planet Gateway
+\twormhole Gateway
+
+wormhole Gateway
+\tlink Vega Deneb
+\tlink Deneb Vega
+";
+
+#[test]
+fn wormhole_links_are_removed_once_and_added_back_one_by_one() {
+    let roots = shuffle(WORMHOLE_UNIVERSE, settings().build());
+
+    let (restore, activate) = preset_events(&roots, 1, None);
+
+    assert_eq!(
+        restore.child(&["wormhole", "Gateway"]).child_tokens(),
+        [["remove", "link"]],
+        "a bare `remove link` already drops every link of the wormhole"
+    );
+
+    assert_eq!(
+        sorted_child_tokens(activate.child(&["wormhole", "Gateway"])),
+        [
+            ["add", "link", "Deneb", "Vega"],
+            ["add", "link", "Vega", "Deneb"]
+        ],
+    );
+}
+
+#[test]
+fn every_link_of_a_system_is_removed_and_added_back() {
+    let roots = shuffle(WORMHOLE_UNIVERSE, settings().build());
+
+    let (restore, activate) = preset_events(&roots, 1, None);
+
+    assert_eq!(
+        sorted_child_tokens_with(restore.child(&["system", "Sol"]), &["remove", "link"]),
+        [["remove", "link", "Deneb"], ["remove", "link", "Vega"]]
+    );
+
+    assert_eq!(
+        sorted_child_tokens_with(activate.child(&["system", "Sol"]), &["add", "link"]),
+        [["add", "link", "Deneb"], ["add", "link", "Vega"]]
+    );
+}
+
+const EVENT_LINK_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+
+system Vega
+\tpos 100 0
+
+system Deneb
+\tpos 0 100
+
+event bridge
+\tlink Sol Vega
+\tlink Sol Deneb
+\tunlink Vega Deneb
+";
+
+#[test]
+fn bare_event_links_are_swapped_and_inverted_on_restore() {
+    let roots = shuffle(EVENT_LINK_UNIVERSE, settings().build());
+
+    let (restore, activate) = preset_events(&roots, 1, Some("bridge"));
+
+    assert_eq!(
+        sorted_child_tokens(activate),
+        [
+            ["link", "Sol", "Deneb"],
+            ["link", "Sol", "Vega"],
+            ["unlink", "Deneb", "Vega"]
+        ]
+    );
+
+    // restoring undoes exactly what activating did, so every link is unlinked again and the other
+    // way around
+    let mut inverted = sorted_child_tokens(activate)
+        .into_iter()
+        .map(|mut tokens| {
+            tokens[0] = if tokens[0] == "link" {
+                "unlink"
+            } else {
+                "link"
+            };
+
+            tokens
+        })
+        .collect::<Vec<_>>();
+
+    inverted.sort_unstable();
+
+    assert_eq!(sorted_child_tokens(restore), inverted);
+
+    assert_eq!(
+        sorted_child_tokens(restore),
+        [
+            ["link", "Deneb", "Vega"],
+            ["unlink", "Sol", "Deneb"],
+            ["unlink", "Sol", "Vega"]
+        ]
+    );
+}
+
+const HIDDEN_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tlink Vega
+\tlink Deneb
+
+system Vega
+\tpos 100 0
+\thidden
+\tlink Sol
+
+system Deneb
+\tpos 0 100
+\tlink Sol
+";
+
+#[test]
+fn hidden_flag_moves_with_the_system() {
+    let roots = shuffle(HIDDEN_UNIVERSE, settings().build());
+
+    let (restore, activate) = preset_events(&roots, 1, None);
+
+    // Deneb is where Vega went, so it's hidden only while the preset is active
+    assert!(
+        activate
+            .child(&["system", "Deneb"])
+            .child_tokens()
+            .contains(&vec!["hidden"])
+    );
+
+    assert!(
+        restore
+            .child(&["system", "Deneb"])
+            .child_tokens()
+            .contains(&vec!["remove", "hidden"])
+    );
+
+    assert!(
+        !activate
+            .child(&["system", "Vega"])
+            .child_tokens()
+            .iter()
+            .any(|tokens| tokens.contains(&"hidden"))
+    );
+
+    // and the original universe hides Vega again
+    let (restore, activate) = preset_events(&roots, 0, None);
+
+    assert!(
+        activate
+            .child(&["system", "Vega"])
+            .child_tokens()
+            .contains(&vec!["hidden"])
+    );
+
+    assert!(
+        restore
+            .child(&["system", "Vega"])
+            .child_tokens()
+            .contains(&vec!["remove", "hidden"])
+    );
+}
+
+const MOON_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tlink Vega
+\tlink Deneb
+
+system Vega
+\tpos 100 0
+\tlink Sol
+\tobject
+\t\tsprite star/g0
+\t\tperiod 10
+\tobject Earth
+\t\tsprite planet/earth
+\t\tdistance 300
+\t\tperiod 365.25
+\t\tobject Luna
+\t\t\tsprite planet/luna
+\t\t\tdistance 40
+\t\t\tperiod 27.3
+
+system Deneb
+\tpos 0 100
+\tlink Sol
+\tobject
+\t\tsprite star/b0
+\t\tperiod 9
+\tobject Station
+\t\tsprite planet/station1
+\t\tdistance 200
+\t\tperiod 120
+
+planet Earth
+\tlandscape land/earth
+
+planet Luna
+\tlandscape land/moon
+
+planet Station
+\tlandscape land/station
+";
+
+/// Whether anything nested below `tree`'s own children starts with `add` or `remove`
+fn has_nested_modifier(tree: &Tree) -> bool {
+    tree.children.iter().any(|child| {
+        child.children.iter().any(|grandchild| {
+            grandchild.starts_with(&["add"])
+                || grandchild.starts_with(&["remove"])
+                || has_nested_modifier(grandchild)
+        })
+    })
+}
+
+#[test]
+fn only_top_level_objects_are_added_and_removed() {
+    let roots = shuffle(MOON_UNIVERSE, settings().shuffle_objects(true).build());
+
+    let (restore, activate) = preset_events(&roots, 1, None);
+
+    // Deneb is where Vega went, so Earth moves in with its moon as a plain nested object
+    let activated = activate.child(&["system", "Deneb"]);
+
+    assert!(
+        activated
+            .child(&["add", "object", "Earth"])
+            .child_tokens()
+            .contains(&vec!["object", "Luna"])
+    );
+
+    assert!(
+        activated
+            .children_with(&["remove", "object", "Station"])
+            .next()
+            .is_some()
+    );
+
+    assert!(!has_nested_modifier(activated));
+
+    // the moon goes with its parent, so removing Earth is enough to take both out
+    let restored = restore.child(&["system", "Deneb"]);
+
+    assert!(
+        restored
+            .child(&["remove", "object", "Earth"])
+            .children_with(&["object"])
+            .next()
+            .is_none()
+    );
+
+    assert!(
+        restored
+            .children_with(&["add", "object", "Station"])
+            .next()
+            .is_some()
+    );
+
+    assert!(!has_nested_modifier(restored));
+}
+
+const POS_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tlink Vega
+\tlink Deneb
+
+system Vega
+\tpos 100.25 -3.5 extra
+\tlink Sol
+
+system Deneb
+\tpos 0 100
+\tlink Sol
+";
+
+#[test]
+fn pos_is_copied_with_every_token() {
+    let roots = shuffle(POS_UNIVERSE, settings().build());
+
+    let (_, activate) = preset_events(&roots, 1, None);
+
+    assert_eq!(
+        activate.child(&["system", "Deneb"]).child(&["pos"]).tokens,
+        ["pos", "100.25", "-3.5", "extra"]
+    );
+
+    assert_eq!(
+        activate.child(&["system", "Vega"]).child(&["pos"]).tokens,
+        ["pos", "0", "100"]
+    );
+}
+
+#[test]
+fn shuffle_chance_is_compared_as_a_percentage() {
+    for (shuffle_chance, expected) in [(0, None), (50, Some("50")), (100, Some("100"))] {
+        let roots = shuffle(
+            HIDDEN_UNIVERSE,
+            settings().shuffle_chance(shuffle_chance).build(),
+        );
+
+        let chances = common::root(&roots, &["mission", "AAAAA System Shuffler: Select Preset"])
+            .child(&["to", "offer"])
+            .child(&["or"])
+            .children_with(&["random"])
+            .map(|chance| chance.tokens.clone())
+            .collect::<Vec<_>>();
+
+        // `random` is 0 to 99, so `random < 100` always passes and a chance of 0 needs no check
+        assert_eq!(
+            chances,
+            expected
+                .map(|expected| vec!["random".to_string(), "<".to_string(), expected.to_string()])
+                .into_iter()
+                .collect::<Vec<_>>(),
+            "shuffle chance {shuffle_chance}"
+        );
+    }
+}
+
+/// Whether `tree` or anything nested in it is an `object`, with or without a modifier
+fn has_object(tree: &Tree) -> bool {
+    tree.tokens.iter().take(2).any(|token| token == "object")
+        || tree.children.iter().any(has_object)
+}
+
+#[test]
+fn planets_stay_put_without_shuffle_objects() {
+    let roots = shuffle(MOON_UNIVERSE, settings().build());
+
+    for preset_index in [0, 1] {
+        let (restore, activate) = preset_events(&roots, preset_index, None);
+
+        // the positions still trade places, the planets just stay with their system's name
+        assert!(
+            activate
+                .child(&["system", "Deneb"])
+                .children_with(&["pos"])
+                .next()
+                .is_some()
+        );
+
+        assert!(!has_object(restore), "preset {preset_index} restore");
+        assert!(!has_object(activate), "preset {preset_index} activate");
+    }
+}
+
+const SORT_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tlink Deneb
+\tlink Vega
+
+system Vega
+\tpos 100 0
+\tlink Sol
+
+system Deneb
+\tpos 0 100
+\tlink Sol
+
+event bridge
+\tlink Sol Vega
+";
+
+/// The systems linked by every `link`, `unlink` or `add link` among `tree`'s children, each sorted
+fn linked_systems(tree: &Tree) -> Vec<Vec<&str>> {
+    let mut linked_systems = tree
+        .child_tokens()
+        .into_iter()
+        .filter_map(|tokens| {
+            let position = tokens
+                .iter()
+                .position(|token| *token == "link" || *token == "unlink")?;
+
+            let mut systems = tokens[position + 1..].to_vec();
+
+            systems.sort_unstable();
+
+            Some(systems)
+        })
+        .collect::<Vec<_>>();
+
+    linked_systems.sort_unstable();
+
+    linked_systems
+}
+
+#[test]
+fn sorted_links_link_the_same_systems() {
+    let default = shuffle(SORT_UNIVERSE, settings().build());
+    let sorted = shuffle(SORT_UNIVERSE, settings().sort_links(true).build());
+
+    let (_, default_bridge) = preset_events(&default, 1, Some("bridge"));
+    let (_, sorted_bridge) = preset_events(&sorted, 1, Some("bridge"));
+
+    // Vega becomes Deneb, which the default keeps in place while sorting puts it first
+    assert_eq!(default_bridge.child_tokens(), [["link", "Sol", "Deneb"]]);
+    assert_eq!(sorted_bridge.child_tokens(), [["link", "Deneb", "Sol"]]);
+
+    let (_, default_activate) = preset_events(&default, 1, None);
+    let (_, sorted_activate) = preset_events(&sorted, 1, None);
+
+    let sorted_sol = sorted_activate.child(&["system", "Sol"]);
+
+    let sol_links = sorted_sol
+        .children_with(&["add", "link"])
+        .map(|link| link.tokens[2].as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(sol_links, ["Deneb", "Vega"]);
+
+    for (default_tree, sorted_tree) in [
+        (default_bridge, sorted_bridge),
+        (default_activate.child(&["system", "Sol"]), sorted_sol),
+    ] {
+        assert_eq!(linked_systems(default_tree), linked_systems(sorted_tree));
+    }
+}
+
+/// Three unpinned systems, so `no_fixed_points` leaves a cycle that isn't its own inverse
+const CYCLE_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+
+system Vega
+\tpos 100 0
+
+system Deneb
+\tpos 0 100
+
+system Rigel
+\tpos 100 100
+";
+
+/// Which system's original position every system that moves takes while a preset is active
+fn moved_positions(activate: &Tree) -> BTreeMap<String, String> {
+    let original_positions = BTreeMap::from([
+        ("0 0", "Sol"),
+        ("100 0", "Vega"),
+        ("0 100", "Deneb"),
+        ("100 100", "Rigel"),
+    ]);
+
+    activate
+        .children_with(&["system"])
+        .filter_map(|system| {
+            let pos = system.children_with(&["pos"]).next()?;
+
+            Some((
+                system.tokens[1].clone(),
+                original_positions[pos.tokens[1..].join(" ").as_str()].to_string(),
+            ))
+        })
+        .filter(|(system, moved_to)| system != moved_to)
+        .collect()
+}
+
+#[test]
+fn inverse_presets_undo_their_preset() {
+    let roots = shuffle(
+        CYCLE_UNIVERSE,
+        settings().include_inverse_presets(true).build(),
+    );
+
+    // with one preset, its inverse is preset 2
+    let (_, activate) = preset_events(&roots, 1, None);
+    let (_, inverse_activate) = preset_events(&roots, 2, None);
+
+    let forward = moved_positions(activate);
+    let inverse = moved_positions(inverse_activate);
+
+    assert_eq!(
+        forward.keys().collect::<Vec<_>>(),
+        ["Deneb", "Rigel", "Vega"]
+    );
+    assert_eq!(
+        inverse.keys().collect::<Vec<_>>(),
+        forward.keys().collect::<Vec<_>>()
+    );
+
+    // a three system cycle is never its own inverse, so this can't pass by the inverse doing the same
+    assert_ne!(forward, inverse);
+
+    for (system, moved_to) in &forward {
+        assert_eq!(&inverse[moved_to], system, "{system} moves to {moved_to}");
+    }
+}
+
+/// The condition a `has`/`not` or `== 0`/`!= 0` guard checks, and whether it holds for `value`
+fn evaluate_flag(tokens: &[String], value: i64) -> Option<(&str, bool)> {
+    match tokens {
+        [has, condition] if has == "has" => Some((condition.as_str(), value != 0)),
+        [not, condition] if not == "not" => Some((condition.as_str(), value == 0)),
+        [condition, comparison, zero] if zero == "0" && comparison == "!=" => {
+            Some((condition.as_str(), value != 0))
+        }
+        [condition, comparison, zero] if zero == "0" && comparison == "==" => {
+            Some((condition.as_str(), value == 0))
+        }
+        _ => None,
+    }
+}
+
+/// Walks both plugins side by side, checking that wherever they differ it's the same flag checked
+/// both ways, and that both agree over a few condition values, returning how many guards differed
+fn assert_same_guards(flags: &Tree, numeric: &Tree) -> usize {
+    let mut differences = 0;
+
+    if flags.tokens != numeric.tokens {
+        for value in [-1, 0, 1, 2, 1000] {
+            let flag = evaluate_flag(&flags.tokens, value);
+            let comparison = evaluate_flag(&numeric.tokens, value);
+
+            assert!(
+                flag.is_some() && flag == comparison,
+                "{:?} and {:?} disagree when the condition is {value}",
+                flags.tokens,
+                numeric.tokens
+            );
+        }
+
+        differences += 1;
+    }
+
+    assert_eq!(
+        flags.children.len(),
+        numeric.children.len(),
+        "{:?}",
+        flags.tokens
+    );
+
+    differences
+        + flags
+            .children
+            .iter()
+            .zip(&numeric.children)
+            .map(|(flags, numeric)| assert_same_guards(flags, numeric))
+            .sum::<usize>()
+}
+
+/// Whether `tree` or anything nested in it is a `has` or `not` condition
+fn has_flag(tree: &Tree) -> bool {
+    tree.starts_with(&["has"]) || tree.starts_with(&["not"]) || tree.children.iter().any(has_flag)
+}
+
+#[test]
+fn numeric_conditions_have_the_same_truth_table() {
+    let settings = || {
+        settings()
+            .max_presets(2)
+            .reconcile_external_changes(true)
+            .shuffle_once_on_install(true)
+    };
+
+    let flags = shuffle(EVENT_LINK_UNIVERSE, settings().build());
+    let numeric = shuffle(
+        EVENT_LINK_UNIVERSE,
+        settings().numeric_conditions(true).build(),
+    );
+
+    assert_eq!(flags.len(), numeric.len());
+
+    let differences = flags
+        .iter()
+        .zip(&numeric)
+        .map(|(flags, numeric)| assert_same_guards(flags, numeric))
+        .sum::<usize>();
+
+    // the install check, the backpatch missions and the conditional events all have guards
+    assert!(differences > 3, "only {differences} guards were rewritten");
+
+    // and none of the numeric plugin's guards are left as `has` or `not`
+    assert!(!numeric.iter().any(has_flag));
+}
+
+const CURRENT_PRESET: &str = "System Shuffler: Current Preset";
+
+/// Whether `tree` is the action that sets the current preset to itself
+fn is_noop_action(tree: &Tree) -> bool {
+    tree.tokens == ["action"] && tree.child_tokens() == [[CURRENT_PRESET, "=", CURRENT_PRESET]]
+}
+
+#[test]
+fn only_the_last_preset_ends_with_a_noop_action() {
+    let roots = shuffle(HIDDEN_UNIVERSE, settings().max_presets(3).build());
+
+    let conversation = common::root(&roots, &["mission", "AAAAA System Shuffler: Select Preset"])
+        .child(&["on", "offer"])
+        .child(&["conversation"]);
+
+    let noop_positions = conversation
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| is_noop_action(child))
+        .map(|(position, _)| position)
+        .collect::<Vec<_>>();
+
+    // once after restoring and once after activating, each right after the last preset's skip
+    // label and after no other preset's
+    assert_eq!(noop_positions.len(), 2);
+
+    for (position, suffix) in noop_positions.iter().zip(["restore", "activate"]) {
+        assert_eq!(
+            conversation.children[position - 1].tokens,
+            ["label", format!("not 3 {suffix}").as_str()]
+        );
+    }
+
+    // the activate pass ends the conversation, so without the no-op it would end on a label that
+    // the last preset's skip branch still jumps to, with nothing after it to land on
+    let (last, rest) = conversation
+        .children
+        .split_last()
+        .expect("the conversation should not be empty");
+
+    assert!(is_noop_action(last));
+
+    let dangling_label = rest.last().expect("the no-op should follow a label");
+
+    assert_eq!(dangling_label.tokens, ["label", "not 3 activate"]);
+    assert!(
+        rest.iter()
+            .any(|child| child.tokens == ["branch", "not 3 activate"])
+    );
+}
+
+#[test]
+fn preset_indices_from_js_are_checked() {
+    for (settings, last_preset) in [
+        (settings().max_presets(3).build(), 3_u32),
+        // inverse presets come after the others, so they double the range
+        (
+            settings()
+                .max_presets(3)
+                .include_inverse_presets(true)
+                .build(),
+            6,
+        ),
+    ] {
+        for preset_index in 0..=last_preset {
+            assert_eq!(
+                system_shuffler::preset_index(&settings, f64::from(preset_index))
+                    .expect("every preset up to the last should be accepted"),
+                usize::try_from(preset_index).expect("a preset index fits in a usize")
+            );
+        }
+
+        for value in [
+            f64::from(last_preset + 1),
+            f64::from(u32::MAX),
+            -1.0,
+            -0.5,
+            0.5,
+            f64::from(last_preset) - 0.25,
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MAX,
+            f64::MIN,
+        ] {
+            let error = system_shuffler::preset_index(&settings, value)
+                .expect_err("anything but a whole number in range should be rejected")
+                .to_string();
+
+            assert!(
+                error.contains(format!("from 0 to {last_preset}").as_str()),
+                "{value} gave {error}"
+            );
+        }
+    }
+}
+
+/// Conditions the way the game keeps them, where anything that was never set is 0
+type Conditions = BTreeMap<String, i64>;
+
+fn condition_value(conditions: &Conditions, token: &str) -> i64 {
+    token
+        .parse()
+        .unwrap_or_else(|_| conditions.get(token).copied().unwrap_or_default())
+}
+
+/// Whether a condition the shuffler writes holds, for the few forms it uses
+fn condition_holds(conditions: &Conditions, condition: &Tree) -> bool {
+    let tokens = condition
+        .tokens
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    match tokens.as_slice() {
+        ["and"] => condition
+            .children
+            .iter()
+            .all(|condition| condition_holds(conditions, condition)),
+        ["or"] => condition
+            .children
+            .iter()
+            .any(|condition| condition_holds(conditions, condition)),
+        ["has", condition] => condition_value(conditions, condition) != 0,
+        ["not", condition] => condition_value(conditions, condition) == 0,
+        [left, comparison, right] => {
+            let (left, right) = (
+                condition_value(conditions, left),
+                condition_value(conditions, right),
+            );
+
+            match *comparison {
+                "==" => left == right,
+                "!=" => left != right,
+                "<" => left < right,
+                ">" => left > right,
+                "<=" => left <= right,
+                ">=" => left >= right,
+                _ => panic!("unknown comparison in {tokens:?}"),
+            }
+        }
+        _ => panic!("unknown condition {tokens:?}"),
+    }
+}
+
+/// Steps through a conversation like the game would, with every `roll` coming out as `roll`,
+/// returning the events its actions fire in order
+fn run_conversation(conversation: &Tree, conditions: &mut Conditions, roll: i64) -> Vec<String> {
+    let nodes = &conversation.children;
+    let label = |name: &str| {
+        nodes
+            .iter()
+            .position(|node| node.tokens == ["label", name])
+            .unwrap_or_else(|| panic!("no label {name}"))
+    };
+
+    let mut events = vec![];
+    let mut position = 0;
+
+    while let Some(node) = nodes.get(position) {
+        position += 1;
+
+        match node.tokens.first().map(String::as_str) {
+            Some("branch") => {
+                if node
+                    .children
+                    .iter()
+                    .all(|condition| condition_holds(conditions, condition))
+                {
+                    position = label(&node.tokens[1]);
+                } else if let Some(otherwise) = node.tokens.get(2) {
+                    position = label(otherwise);
+                }
+            }
+            Some("action") => {
+                for action in &node.children {
+                    let tokens = action.tokens.iter().map(String::as_str).collect::<Vec<_>>();
+
+                    match tokens.as_slice() {
+                        ["event", event, ..] => events.push((*event).to_string()),
+                        [condition, "=", "(", roll_limit, "+", "1", ")"]
+                            if roll_limit.starts_with("roll: ") =>
+                        {
+                            conditions.insert((*condition).to_string(), roll + 1);
+                        }
+                        [condition, "=", "days since epoch"] => {
+                            conditions.insert((*condition).to_string(), 0);
+                        }
+                        [condition, "=", value] => {
+                            let value = condition_value(conditions, value);
+
+                            conditions.insert((*condition).to_string(), value);
+                        }
+                        _ => panic!("unknown action {tokens:?}"),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Whether every `to offer` condition of a mission holds
+fn offered(mission: &Tree, conditions: &Conditions) -> bool {
+    mission
+        .child(&["to", "offer"])
+        .children
+        .iter()
+        .all(|condition| condition_holds(conditions, condition))
+}
+
+#[test]
+fn an_outside_preset_change_is_reconciled() {
+    let roots = shuffle(
+        HIDDEN_UNIVERSE,
+        settings()
+            .max_presets(2)
+            .reconcile_external_changes(true)
+            .build(),
+    );
+
+    let conversation = |mission: &str| {
+        common::root(&roots, &["mission", mission])
+            .child(&["on", "offer"])
+            .child(&["conversation"])
+    };
+
+    let reconcile = common::root(
+        &roots,
+        &["mission", "AAAAA System Shuffler: Reconcile Preset"],
+    );
+
+    let mut conditions = Conditions::new();
+
+    // installing rolls preset 1, which is also remembered as the one in the universe
+    let events = run_conversation(
+        conversation("AAAAA System Shuffler: Select Preset"),
+        &mut conditions,
+        0,
+    );
+
+    assert!(events.contains(&"System Shuffler: Activate Preset 1".to_string()));
+    assert_eq!(conditions[CURRENT_PRESET], 1);
+    assert_eq!(conditions["System Shuffler: Applied Preset"], 1);
+    assert!(!offered(reconcile, &conditions));
+
+    // then something else sets the current preset, like another plugin or the console
+    conditions.insert(CURRENT_PRESET.to_string(), 2);
+
+    assert!(offered(reconcile, &conditions));
+
+    let events = run_conversation(
+        conversation("AAAAA System Shuffler: Reconcile Preset"),
+        &mut conditions,
+        0,
+    );
+
+    // the preset really in the universe is undone before the new one goes in, and nothing else
+    // is touched
+    let preset_events = events
+        .iter()
+        .filter(|event| event.contains("Preset 1") || event.contains("Preset 2"))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        preset_events,
+        [
+            "System Shuffler: Restore Preset 1",
+            "System Shuffler: Activate Preset 2"
+        ]
+    );
+
+    assert_eq!(conditions[CURRENT_PRESET], 2);
+    assert_eq!(conditions["System Shuffler: Applied Preset"], 2);
+    assert!(!offered(reconcile, &conditions));
+
+    // setting it back to the original universe only restores
+    conditions.insert(CURRENT_PRESET.to_string(), 0);
+
+    assert!(offered(reconcile, &conditions));
+
+    let events = run_conversation(
+        conversation("AAAAA System Shuffler: Reconcile Preset"),
+        &mut conditions,
+        0,
+    );
+
+    assert!(events.contains(&"System Shuffler: Restore Preset 2".to_string()));
+    assert!(!events.iter().any(|event| event.contains("Activate Preset 1")
+        || event.contains("Activate Preset 2")));
+    assert_eq!(conditions["System Shuffler: Applied Preset"], 0);
+}
+
+#[test]
+fn one_way_shuffles_never_restore() {
+    let roots = shuffle(
+        EVENT_LINK_UNIVERSE,
+        settings()
+            .max_presets(2)
+            .one_way(true)
+            .manual_job(false)
+            .build(),
+    );
+
+    assert!(
+        !roots
+            .iter()
+            .any(|root| root.starts_with(&["mission", "System Shuffler: Restore Universe"]))
+    );
+
+    // only the original universe's copy of the uploaded event is left, for the initial backpatch
+    let restore_events = roots
+        .iter()
+        .filter(|root| {
+            root.starts_with(&["event"])
+                && root.tokens[1].starts_with("System Shuffler: Restore Preset")
+        })
+        .map(|root| root.tokens[1].as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        restore_events,
+        ["System Shuffler: Restore Preset 0: bridge"]
+    );
+
+    assert!(!roots.iter().any(|root| {
+        root.starts_with(&["mission"]) && root.tokens[1].contains("Restore Preset")
+    }));
+
+    // installing still activates whichever preset is rolled, without restoring anything first
+    for roll in [0, 1] {
+        let mut conditions = Conditions::new();
+
+        let events = run_conversation(
+            common::root(&roots, &["mission", "AAAAA System Shuffler: Select Preset"])
+                .child(&["on", "offer"])
+                .child(&["conversation"]),
+            &mut conditions,
+            roll,
+        );
+
+        let activate_name = format!("System Shuffler: Activate Preset {}", roll + 1);
+
+        assert_eq!(events, [activate_name.as_str()]);
+        assert_eq!(conditions[CURRENT_PRESET], roll + 1);
+
+        assert!(
+            !common::root(&roots, &["event", activate_name.as_str()])
+                .children
+                .is_empty()
+        );
+    }
+}
+
+#[test]
+fn verbose_logs_progress_under_the_plugin_name() {
+    let (_, quiet) = generators::capture_log(|| shuffle(HIDDEN_UNIVERSE, settings().build()));
+
+    let (_, verbose) = generators::capture_log(|| {
+        shuffle(
+            HIDDEN_UNIVERSE,
+            settings()
+                .verbose(true)
+                .plugin_name(Some("Tiny Shuffle".to_string()))
+                .build(),
+        )
+    });
+
+    // warnings are logged either way, so only the progress lines are compared
+    let progress = verbose
+        .iter()
+        .filter(|line| !quiet.contains(line))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        progress,
+        [
+            "Tiny Shuffle: Detecting shuffled data",
+            "Tiny Shuffle: Found 3 systems and 0 events to shuffle",
+            "Tiny Shuffle: Wrote preset 0 of 1",
+            "Tiny Shuffle: Wrote preset 1 of 1",
+        ]
+    );
+
+    assert!(
+        !quiet
+            .iter()
+            .any(|line| line.starts_with("System Shuffler: ")),
+        "{quiet:?}"
+    );
+}
+
+#[test]
+fn same_seed_gives_the_same_plugin() {
+    let settings = [
+        settings().max_presets(4).build(),
+        settings()
+            .max_presets(4)
+            .shuffle_objects(true)
+            .scope_events_by_kind(true)
+            .include_inverse_presets(true)
+            .build(),
+    ];
+
+    for universe in [WORMHOLE_UNIVERSE, EVENT_LINK_UNIVERSE, MOON_UNIVERSE] {
+        for settings in &settings {
+            // each run parses the fixture again, so nothing carries over between them
+            let plugin = || {
+                system_shuffler::process_data(
+                    &common::parse(&[("data/universe.txt", universe)]),
+                    settings.clone(),
+                )
+                .expect("the fixture should shuffle")
+            };
+
+            assert!(
+                plugin() == plugin(),
+                "the same seed should give a byte-identical zip"
+            );
+        }
+    }
+}
+
+/// The events and missions a plugin defines and every condition it sets, which are what two
+/// plugins installed together must not share
+fn owned_names(roots: &[Tree]) -> BTreeSet<String> {
+    fn assigned(tree: &Tree, names: &mut BTreeSet<String>) {
+        if tree.tokens.get(1).is_some_and(|token| token == "=") {
+            names.insert(tree.tokens[0].clone());
+        }
+
+        for child in &tree.children {
+            assigned(child, names);
+        }
+    }
+
+    let mut names = BTreeSet::new();
+
+    for root in roots {
+        if root.starts_with(&["event"]) || root.starts_with(&["mission"]) {
+            names.insert(root.tokens.join(" "));
+        }
+
+        assigned(root, &mut names);
+    }
+
+    names
+}
+
+#[test]
+fn namespaces_keep_plugins_apart() {
+    let shuffler = |namespace: &str| {
+        shuffle(
+            EVENT_LINK_UNIVERSE,
+            settings()
+                .max_presets(2)
+                .namespace(Some(namespace.to_string()))
+                .reconcile_external_changes(true)
+                .build(),
+        )
+    };
+
+    let alpha = owned_names(&shuffler("Alpha"));
+    let beta = owned_names(&shuffler("Beta"));
+
+    // the install flag, the current preset, every preset event and every mission
+    assert!(alpha.len() > 10, "{alpha:?}");
+    assert_eq!(alpha.len(), beta.len());
+
+    assert!(
+        alpha.is_disjoint(&beta),
+        "{:?}",
+        alpha.intersection(&beta).collect::<Vec<_>>()
+    );
+
+    assert!(alpha.contains("Alpha: Current Preset"));
+    assert!(alpha.iter().all(|name| !name.contains("System Shuffler")));
+}
+
+/// Three systems and `event_count` events that each link two of them, so every event is copied
+/// into every preset
+fn many_events_universe(event_count: usize) -> String {
+    let mut universe = "\
+system Sol
+\tpos 0 0
+
+system Vega
+\tpos 100 0
+
+system Deneb
+\tpos 0 100
+"
+    .to_string();
+
+    for event_index in 0..event_count {
+        universe.push_str(format!("\nevent \"bridge {event_index}\"\n\tlink Sol Vega\n").as_str());
+    }
+
+    universe
+}
+
+#[test]
+fn too_many_branches_are_warned_about() {
+    // picking a preset takes 2 * (presets + 1) * (events + 1) branches, so 49 presets cross the
+    // limit of 10000 between 99 and 100 events
+    let warnings = |event_count: usize| {
+        let universe = many_events_universe(event_count);
+
+        let (output, logged) = generators::capture_log(|| {
+            system_shuffler::process_parsed_with_report(
+                common::parse(&[("data/universe.txt", universe.as_str())]).data(),
+                settings().max_presets(49).build(),
+            )
+            .expect("the fixture should shuffle")
+        });
+
+        (output.1.to_json(), logged)
+    };
+
+    let (report, logged) = warnings(99);
+
+    assert!(!report.contains("Picking a preset takes"), "{report}");
+    assert!(
+        logged
+            .iter()
+            .all(|line| !line.starts_with("Picking a preset takes")),
+        "{logged:?}"
+    );
+
+    let (report, logged) = warnings(100);
+
+    assert!(
+        report.contains("Picking a preset takes 10100 branches"),
+        "{report}"
+    );
+    assert!(
+        logged
+            .iter()
+            .any(|line| line.starts_with("Picking a preset takes 10100 branches")),
+        "{logged:?}"
+    );
+}
+
+#[test]
+fn duplicate_presets_are_counted_from_the_real_swaps() {
+    // with Sol pinned, the three other systems can only be deranged two ways
+    let settings = settings().max_presets(12).build();
+
+    let counted = system_shuffler::count_duplicate_presets(
+        common::parse(&[("data/universe.txt", CYCLE_UNIVERSE)]).data(),
+        &settings,
+    )
+    .expect("the fixture should shuffle");
+
+    let roots = shuffle(CYCLE_UNIVERSE, settings);
+
+    // the original universe moves nothing
+    let moves = std::iter::once(BTreeMap::new())
+        .chain(
+            (1..=12)
+                .map(|preset_index| moved_positions(preset_events(&roots, preset_index, None).1)),
+        )
+        .collect::<Vec<_>>();
+
+    let collisions = moves
+        .iter()
+        .enumerate()
+        .filter(|&(preset_index, preset_moves)| moves[..preset_index].contains(preset_moves))
+        .count();
+
+    assert!(collisions >= 10, "{moves:?}");
+    assert_eq!(counted, collisions);
+}
+
+#[test]
+fn the_report_lists_the_swaps_the_events_make() {
+    let settings = settings()
+        .max_presets(3)
+        .include_inverse_presets(true)
+        .build();
+
+    let (output, report) = system_shuffler::process_parsed_with_report(
+        common::parse(&[("data/universe.txt", CYCLE_UNIVERSE)]).data(),
+        settings,
+    )
+    .expect("the fixture should shuffle");
+
+    let roots = common::reparse(output.as_slice());
+
+    // the report swaps `original` for `swap`, which the events write as `swap` taking the
+    // position `original` had
+    let preset_swaps = (1..=6)
+        .map(|preset_index| {
+            let swaps = moved_positions(preset_events(&roots, preset_index, None).1)
+                .into_iter()
+                .map(|(swap, original)| {
+                    let pair = format!("\"{original}\":\"{swap}\"");
+
+                    (original, pair)
+                })
+                .collect::<BTreeMap<_, _>>()
+                .into_values()
+                .collect::<Vec<_>>()
+                .join(",");
+
+            format!("{{{swaps}}}")
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let report = report.to_json();
+
+    assert!(
+        report.contains(format!("\"preset_swaps\":[{{}},{preset_swaps}]").as_str()),
+        "{report}"
+    );
+    assert!(report.contains("\"system_count\":4"), "{report}");
+}
+
+const GOVERNMENT_UNIVERSE: &str = "\
+system Sol
+\tpos 0 0
+\tgovernment Republic
+
+system Vega
+\tpos 100 0
+\tgovernment Republic
+
+system Altair
+\tpos 100 100
+\tgovernment Republic
+
+system Deneb
+\tpos 0 100
+\tgovernment Pirate
+
+system Rigel
+\tpos -100 0
+\tgovernment Pirate
+";
+
+/// The systems an event modifies
+fn event_systems(event: &Tree) -> BTreeSet<&str> {
+    event
+        .children_with(&["system"])
+        .map(|system| system.tokens[1].as_str())
+        .collect()
+}
+
+#[test]
+fn every_region_gets_its_own_plugin() {
+    let output = system_shuffler::process_data_per_region(
+        &common::parse(&[("data/universe.txt", GOVERNMENT_UNIVERSE)]),
+        &settings().build(),
+    )
+    .expect("the fixture should shuffle");
+
+    let plugins = common::unzip_files(output.as_slice());
+
+    assert_eq!(
+        plugins.keys().collect::<Vec<_>>(),
+        ["Pirate.zip", "Republic.zip"]
+    );
+
+    for (plugin, region, systems) in [
+        ("Pirate.zip", "Pirate", &["Deneb", "Rigel"]),
+        ("Republic.zip", "Republic", &["Altair", "Vega"]),
+    ] {
+        let roots = common::reparse(plugins[plugin].as_slice());
+
+        // each region's plugin only moves its own systems, under its own names
+        let activate = common::root(
+            &roots,
+            &[
+                "event",
+                format!("System Shuffler: {region}: Activate Preset 1").as_str(),
+            ],
+        );
+
+        assert_eq!(event_systems(activate), BTreeSet::from(*systems));
+    }
+}
+
+/// Shuffles `files` and returns every text file of the plugin by its path
+fn shuffled_files(
+    files: &[(&str, &str)],
+    settings: SystemShufflerConfig,
+) -> BTreeMap<String, String> {
+    let output = system_shuffler::process_data(&common::parse(files), settings)
+        .expect("the fixture should shuffle");
+
+    common::unzip(output.as_slice())
+}
+
+/// The files directly in `folder`, by their name
+fn folder_files<'a>(
+    files: &'a BTreeMap<String, String>,
+    folder: &str,
+) -> BTreeMap<&'a str, &'a str> {
+    files
+        .iter()
+        .filter_map(|(path, text)| {
+            let name = path.strip_prefix(folder)?.strip_prefix('/')?;
+
+            (!name.contains('/')).then_some((name, text.as_str()))
+        })
+        .collect()
+}
+
+/// Every root node of one generated file
+fn file_roots(text: &str) -> Vec<Tree> {
+    common::roots(common::parse(&[("data/file.txt", text)]).data())
+}
+
+#[test]
+fn mirror_layout_writes_a_file_per_uploaded_file() {
+    let first = "\
+system Sol
+\tpos 0 0
+
+system Vega
+\tpos 100 0
+";
+    let second = "\
+system Deneb
+\tpos 0 100
+
+system Rigel
+\tpos 100 100
+";
+
+    let files = shuffled_files(
+        &[("data/first.txt", first), ("data/second.txt", second)],
+        settings().mirror_layout(true).build(),
+    );
+
+    let preset_files = folder_files(&files, "data/presets/universe_preset_1");
+
+    let source_files = preset_files
+        .iter()
+        .filter(|(name, _)| name.starts_with("source_"))
+        .collect::<Vec<_>>();
+
+    assert_eq!(source_files.len(), 2, "{:?}", preset_files.keys());
+    assert_eq!(preset_files.len(), 4, "{:?}", preset_files.keys());
+    assert!(preset_files.contains_key("events.txt"));
+    assert!(preset_files.contains_key("missions.txt"));
+
+    // while a preset is active, a system's node is named after the system taking its place but
+    // keeps the position it had, which tells which uploaded file it came from
+    let original_sources = BTreeMap::from([
+        ("0 0", "first"),
+        ("100 0", "first"),
+        ("0 100", "second"),
+        ("100 100", "second"),
+    ]);
+
+    let mut seen_sources = BTreeSet::new();
+
+    for (_, text) in source_files {
+        let sources = file_roots(text)
+            .iter()
+            .filter(|event| event.tokens[1].starts_with("System Shuffler: Activate Preset 1: "))
+            .flat_map(|event| event.children_with(&["system"]))
+            .flat_map(|system| system.children_with(&["pos"]))
+            .map(|pos| original_sources[pos.tokens[1..].join(" ").as_str()])
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(sources.len(), 1, "{text}");
+
+        seen_sources.extend(sources);
+    }
+
+    assert_eq!(seen_sources, BTreeSet::from(["first", "second"]));
+}
+
+#[test]
+fn combined_preset_files_hold_everything_the_separate_ones_do() {
+    let separate = shuffled_files(
+        &[("data/universe.txt", EVENT_LINK_UNIVERSE)],
+        settings().build(),
+    );
+    let combined = shuffled_files(
+        &[("data/universe.txt", EVENT_LINK_UNIVERSE)],
+        settings().combine_preset_files(true).build(),
+    );
+
+    for preset_index in 0..=1 {
+        let folder = format!("data/presets/universe_preset_{preset_index}");
+
+        let separate_files = folder_files(&separate, folder.as_str());
+        let combined_files = folder_files(&combined, folder.as_str());
+
+        assert_eq!(
+            separate_files.keys().copied().collect::<Vec<_>>(),
+            ["events.txt", "main.txt", "missions.txt"]
+        );
+
+        let combined_name = format!("preset_{preset_index}.txt");
+
+        assert_eq!(
+            combined_files.keys().copied().collect::<Vec<_>>(),
+            [combined_name.as_str()]
+        );
+
+        // the same nodes in the same order, main.txt then events.txt then missions.txt
+        assert_eq!(
+            file_roots(combined_files[combined_name.as_str()]),
+            ["main.txt", "events.txt", "missions.txt"]
+                .into_iter()
+                .flat_map(|name| file_roots(separate_files[name]))
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn split_by_government_gives_every_government_a_folder() {
+    let files = shuffled_files(
+        &[("data/universe.txt", GOVERNMENT_UNIVERSE)],
+        settings().split_by_government(true).build(),
+    );
+
+    // the uploaded events and the missions stay in the preset's own folder
+    assert_eq!(
+        folder_files(&files, "data/presets/universe_preset_1")
+            .keys()
+            .copied()
+            .collect::<Vec<_>>(),
+        ["events.txt", "missions.txt"]
+    );
+
+    for (government, positions) in [
+        ("Pirate", ["0 100", "-100 0"].as_slice()),
+        ("Republic", ["0 0", "100 0", "100 100"].as_slice()),
+    ] {
+        let government_files = folder_files(
+            &files,
+            format!("data/presets/{government}/universe_preset_1").as_str(),
+        );
+
+        assert_eq!(
+            government_files.keys().copied().collect::<Vec<_>>(),
+            ["main.txt"]
+        );
+
+        let roots = file_roots(government_files["main.txt"]);
+
+        let activate = common::root(
+            &roots,
+            &[
+                "event",
+                format!("System Shuffler: Activate Preset 1: {government}").as_str(),
+            ],
+        );
+
+        // the systems can swap across governments, but each node keeps the position of a system
+        // from the government it's filed under
+        let moved_from = activate
+            .children_with(&["system"])
+            .flat_map(|system| system.children_with(&["pos"]))
+            .map(|pos| pos.tokens[1..].join(" "))
+            .collect::<Vec<_>>();
+
+        assert!(!moved_from.is_empty(), "{activate:?}");
+        assert!(
+            moved_from
+                .iter()
+                .all(|position| positions.contains(&position.as_str())),
+            "{activate:?}"
+        );
+    }
+}
+
+#[test]
+fn named_presets_get_their_own_folders() {
+    let files = shuffled_files(
+        &[("data/universe.txt", CYCLE_UNIVERSE)],
+        settings()
+            .max_presets(2)
+            .preset_names(vec!["Home".to_string(), "Either/Or".to_string()])
+            .build(),
+    );
+
+    let preset_folders = files
+        .keys()
+        .filter_map(|path| path.strip_prefix("data/presets/")?.split_once('/'))
+        .map(|(folder, _)| folder)
+        .collect::<BTreeSet<_>>();
+
+    // the names go to presets 0 and 1 in order, made safe for a path, and the presets past the
+    // end of the names fall back to their number
+    assert_eq!(
+        preset_folders,
+        BTreeSet::from(["Either_Or", "Home", "universe_preset_2"])
+    );
+
+    for folder in preset_folders {
+        assert_eq!(
+            folder_files(&files, format!("data/presets/{folder}").as_str())
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            ["events.txt", "main.txt", "missions.txt"]
+        );
+    }
+}
+
+#[test]
+fn the_full_map_gets_its_own_data_folder() {
+    // everything but the presets and their maps
+    let plugin_paths = |include_full_map: bool| {
+        shuffled_files(
+            &[("data/universe.txt", CYCLE_UNIVERSE)],
+            settings().include_full_map(include_full_map).build(),
+        )
+        .into_keys()
+        .filter(|path| {
+            path == "plugin.txt"
+                || (path.starts_with("data/") && !path.starts_with("data/presets/"))
+        })
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        plugin_paths(false),
+        [
+            "data/initial_backpatch_missions.txt",
+            "data/main.txt",
+            "plugin.txt"
+        ]
+    );
+
+    // the full map shares the plugin.txt but none of the data files
+    assert_eq!(
+        plugin_paths(true),
+        [
+            "data/full_map/full_map_event.txt",
+            "data/full_map/full_map_mission.txt",
+            "data/initial_backpatch_missions.txt",
+            "data/main.txt",
+            "plugin.txt"
+        ]
+    );
+}
+
+#[test]
+fn tiny_uploads_shuffle_or_say_why_not() {
+    let result = |universe: &str, settings: SystemShufflerBuilder| {
+        system_shuffler::process_data(
+            &common::parse(&[("data/universe.txt", universe)]),
+            settings.build(),
+        )
+        .map_err(|error| error.to_string())
+    };
+
+    assert_eq!(
+        result("government Republic\n", settings()),
+        Err("The uploaded data doesn't define any systems to shuffle :(".to_string())
+    );
+
+    let lonely_universe = "system Sol\n\tpos 0 0\n";
+
+    assert_eq!(
+        result(lonely_universe, settings()),
+        Err("Every uploaded system is pinned, so there is nothing left to shuffle :(".to_string())
+    );
+
+    // a single system can only swap with itself, so every preset leaves it where it is
+    let output = result(lonely_universe, settings().pinned_systems(vec![]))
+        .expect("a single system should still shuffle");
+
+    let roots = common::reparse(output.as_slice());
+
+    let (_, activate) = preset_events(&roots, 1, None);
+
+    assert!(moved_positions(activate).is_empty(), "{activate:?}");
+}
@@ -1,3 +1,5 @@
+import { seed_from_f64, seed_or_random, decode_source } from "./endless_sky_generator_web.js";
+
 export const readFileAsText = async (file) => {
   return await new Promise((resolve) => {
     const reader = new FileReader();
@@ -10,6 +12,18 @@ export const readFileAsText = async (file) => {
   });
 };
 
+export const readFileAsBytes = async (file) => {
+  return await new Promise((resolve) => {
+    const reader = new FileReader();
+
+    reader.onload = () => {
+      resolve(new Uint8Array(reader.result));
+    };
+
+    reader.readAsArrayBuffer(file);
+  });
+};
+
 const input = document.getElementById("input");
 
 let defaults_checked = false;
@@ -25,6 +39,15 @@ input.addEventListener("change", async () => {
     if (file.type.startsWith("text")) {
       loaded_paths.push(file.name);
       loaded_sources.push(await readFileAsText(file));
+    } else if (file.name.endsWith(".txt.gz")) {
+      try {
+        const source = decode_source(await readFileAsBytes(file));
+
+        loaded_paths.push(file.name.slice(0, -".gz".length));
+        loaded_sources.push(source);
+      } catch(error) {
+        console.error(error);
+      }
     }
   }
 });
@@ -114,6 +137,21 @@ export const generateAndDownload = (fileName, rustFn) => {
   }
 };
 
+// a seed of 0 means "pick one for me", and the picked seed is written into the plugin.txt
+export const seedFromInput = (value) => {
+  try {
+    const seed = BigInt(value);
+
+    if (seed >= 0n && seed < (1n << 64n)) {
+      return seed_or_random(seed, Math.random());
+    }
+  } catch {
+    // not a whole number, so fall through to the double's bit pattern
+  }
+
+  return seed_or_random(seed_from_f64(Number(value)), Math.random());
+};
+
 export const iterateElements = (node, modify) => {
   const elements = [node];
 
@@ -229,3 +267,48 @@ export const deepClone = (node) => {
 
   return the_clone;
 };
+
+// the generators whose only settings are a seed and the plugins they require all share one form, with
+// every element's class starting with `classPrefix`
+export const seedOnlyPreparation = (classPrefix, fileName, rustFn, Config) => {
+  const form = document.getElementById(classPrefix + "-form");
+
+  iterateElements(form, (node) => {
+    defaultEventListeners(node);
+  });
+
+  const seed = Array.from(form.getElementsByClassName(classPrefix + "-seed"))[0];
+
+  const requires = Array.from(form.getElementsByClassName(classPrefix + "-requires"))[0];
+
+  form.addEventListener("submit", async (event) => {
+    event.preventDefault();
+
+    if (!form.checkValidity()) {
+      form.reportValidity();
+      return;
+    }
+
+    const paths_and_sources = await getPathsAndSources();
+
+    let result;
+
+    try {
+      result = new Uint8Array(
+        rustFn(
+          paths_and_sources.paths,
+          paths_and_sources.sources,
+          new Config(
+            seedFromInput(seed.value),
+            requires.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0),
+          )
+        )
+      );
+    } catch(error) {
+      console.error(error);
+      return;
+    }
+
+    downloadZip(fileName, result);
+  });
+};
@@ -3,12 +3,13 @@ import {
   downloadZip,
   generateAndDownload,
   iterateElements,
-  defaultEventListeners
+  defaultEventListeners,
+  seedFromInput
 } from "../export_to_rust.js";
 
 import {
   generate_chaos,
-  ChaosConfig
+  ChaosBuilder
 } from "../endless_sky_generator_web.js";
 
 export const preparation = () => {
@@ -20,6 +21,10 @@ export const preparation = () => {
 
   const seed = Array.from(chaos_form.getElementsByClassName("chaos-seed"))[0];
 
+  const outfit_seed = Array.from(chaos_form.getElementsByClassName("chaos-outfit-seed"))[0];
+
+  const ship_seed = Array.from(chaos_form.getElementsByClassName("chaos-ship-seed"))[0];
+
   const outfits = Array.from(chaos_form.getElementsByClassName("chaos-outfits"))[0];
 
   const ships = Array.from(chaos_form.getElementsByClassName("chaos-ships"))[0];
@@ -28,21 +33,43 @@ export const preparation = () => {
 
   const planets = Array.from(chaos_form.getElementsByClassName("chaos-planets"))[0];
 
+  const landscapes = Array.from(chaos_form.getElementsByClassName("chaos-landscapes"))[0];
+
+  const swap_names = Array.from(chaos_form.getElementsByClassName("chaos-swap-names"))[0];
+
+  const swap_assets = Array.from(chaos_form.getElementsByClassName("chaos-swap-assets"))[0];
+
+  const by_category = Array.from(chaos_form.getElementsByClassName("chaos-by-category"))[0];
+
+  const weight_by_cost = Array.from(chaos_form.getElementsByClassName("chaos-weight-by-cost"))[0];
+
+  const no_self_swap = Array.from(chaos_form.getElementsByClassName("chaos-no-self-swap"))[0];
+
+  const preserve_outfit_space = Array.from(chaos_form.getElementsByClassName("chaos-preserve-outfit-space"))[0];
+
+  const exclude = Array.from(chaos_form.getElementsByClassName("chaos-exclude"))[0];
+
+
+
+  const requires = Array.from(chaos_form.getElementsByClassName("chaos-requires"))[0];
+
   chaos_form.addEventListener("submit", async (event) => {
     event.preventDefault();
 
-    if (!outfits.checked && !ships.checked && !systems.checked && !planets.checked) {
+    if (!outfits.checked && !ships.checked && !systems.checked && !planets.checked && !landscapes.checked) {
       const invalid = "You should enable at least one of these, otherwise the generator serves no purpose";
 
       outfits.setCustomValidity(invalid);
       ships.setCustomValidity(invalid);
       systems.setCustomValidity(invalid);
       planets.setCustomValidity(invalid);
+      landscapes.setCustomValidity(invalid);
     } else {
       outfits.setCustomValidity("");
       ships.setCustomValidity("");
       systems.setCustomValidity("");
       planets.setCustomValidity("");
+      landscapes.setCustomValidity("");
     }
 
     if (!chaos_form.checkValidity()) {
@@ -59,13 +86,24 @@ export const preparation = () => {
         generate_chaos(
           paths_and_sources.paths,
           paths_and_sources.sources,
-          new ChaosConfig(
-            seed.value,
-            outfits.checked,
-            ships.checked,
-            systems.checked,
-            planets.checked,
-          )
+          new ChaosBuilder()
+            .seed(seedFromInput(seed.value))
+            .outfit_seed(BigInt(outfit_seed.value || 0))
+            .ship_seed(BigInt(ship_seed.value || 0))
+            .outfits(outfits.checked)
+            .ships(ships.checked)
+            .systems(systems.checked)
+            .planets(planets.checked)
+            .landscapes(landscapes.checked)
+            .swap_names(swap_names.checked)
+            .swap_assets(swap_assets.checked)
+            .by_category(by_category.checked)
+            .weight_by_cost(weight_by_cost.checked)
+            .no_self_swap(no_self_swap.checked)
+            .preserve_outfit_space(preserve_outfit_space.checked)
+            .exclude(exclude.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0))
+            .requires(requires.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0))
+            .build()
         )
       );
     } catch(error) {
@@ -0,0 +1,10 @@
+import { seedOnlyPreparation } from "../export_to_rust.js";
+
+import {
+  generate_jump_chaos,
+  JumpChaosConfig
+} from "../endless_sky_generator_web.js";
+
+export const preparation = () => {
+  seedOnlyPreparation("jump-chaos", "jump_chaos.zip", generate_jump_chaos, JumpChaosConfig);
+};
@@ -3,7 +3,8 @@ import {
   downloadZip,
   generateAndDownload,
   iterateElements,
-  defaultEventListeners
+  defaultEventListeners,
+  seedFromInput
 } from "../export_to_rust.js";
 
 import {
@@ -181,6 +182,8 @@ export const preparation = () => {
 
   const reveal_all = Array.from(random_galaxy_form.getElementsByClassName("random-galaxy-reveal-all"))[0];
 
+  const requires = Array.from(random_galaxy_form.getElementsByClassName("random-galaxy-requires"))[0];
+
   random_galaxy_form.addEventListener("submit", async (event) => {
     event.preventDefault();
 
@@ -276,8 +279,9 @@ export const preparation = () => {
           paths_and_sources.sources,
           new RandomGalaxyConfig(
             name.value,
-            seed.value,
+            seedFromInput(seed.value),
             reveal_all.checked,
+            requires.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0),
             clusters
               .map((cluster) => new Cluster(
                 new SystemCapacity(
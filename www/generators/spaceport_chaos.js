@@ -0,0 +1,10 @@
+import { seedOnlyPreparation } from "../export_to_rust.js";
+
+import {
+  generate_spaceport_chaos,
+  SpaceportChaosConfig
+} from "../endless_sky_generator_web.js";
+
+export const preparation = () => {
+  seedOnlyPreparation("spaceport-chaos", "spaceport_chaos.zip", generate_spaceport_chaos, SpaceportChaosConfig);
+};
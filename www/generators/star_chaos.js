@@ -0,0 +1,10 @@
+import { seedOnlyPreparation } from "../export_to_rust.js";
+
+import {
+  generate_star_chaos,
+  StarChaosConfig
+} from "../endless_sky_generator_web.js";
+
+export const preparation = () => {
+  seedOnlyPreparation("star-chaos", "star_chaos.zip", generate_star_chaos, StarChaosConfig);
+};
@@ -3,12 +3,15 @@ import {
   downloadZip,
   generateAndDownload,
   iterateElements,
-  defaultEventListeners
+  defaultEventListeners,
+  seedFromInput
 } from "../export_to_rust.js";
 
 import {
   generate_system_shuffler,
-  SystemShufflerConfig
+  seed_from_date,
+  GeometryMode,
+  SystemShufflerBuilder
 } from "../endless_sky_generator_web.js";
 
 export const preparation = () => {
@@ -20,14 +23,101 @@ export const preparation = () => {
 
   const seed = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-seed"))[0];
 
+  const daily_seed = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-daily-seed"))[0];
+
+  // everyone generating on the same day gets the same seed, and so the same universe
+  daily_seed.addEventListener("click", () => {
+    const today = new Date();
+
+    seed.value = seed_from_date(today.getFullYear(), today.getMonth() + 1, today.getDate()).toString();
+  });
+
   const max_presets = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-max-presets"))[0];
 
   const shuffle_once_on_install = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-once-on-install"))[0];
 
+  const include_inverse_presets = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-include-inverse-presets"))[0];
+
   const shuffle_chance = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-chance"))[0];
 
   const fixed_shuffle_days = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-fixed-shuffle-days"))[0];
 
+  const mirror_layout = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-mirror-layout"))[0];
+
+  const scope_events_by_kind = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-scope-events-by-kind"))[0];
+
+  const detect_wormholes = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-detect-wormholes"))[0];
+
+  const numeric_conditions = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-numeric-conditions"))[0];
+
+  const shuffle_minables = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-minables"))[0];
+
+  const shuffle_hazards = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-hazards"))[0];
+
+  const shuffle_radii = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-radii"))[0];
+
+  const combine_preset_files = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-combine-preset-files"))[0];
+
+  const reconcile_external_changes = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-reconcile-external-changes"))[0];
+
+  const normalize_music = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-normalize-music"))[0];
+
+  const verbose = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-verbose"))[0];
+
+  const include_full_map = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-include-full-map"))[0];
+
+  const sort_links = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-sort-links"))[0];
+
+  const one_way = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-one-way"))[0];
+
+  const split_by_government = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-split-by-government"))[0];
+
+  const no_fixed_points = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-no-fixed-points"))[0];
+
+  const shuffle_links = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-links"))[0];
+
+  const shuffle_positions = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-positions"))[0];
+
+  const shuffle_objects = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-objects"))[0];
+
+  const geometry_mode = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-geometry-mode"))[0];
+
+  const geometry_modes = {
+    swap: GeometryMode.Swap,
+    rotate: GeometryMode.Rotate,
+    mirror: GeometryMode.Mirror
+  };
+
+  const plugin_name = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-plugin-name"))[0];
+
+  const plugin_version = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-plugin-version"))[0];
+
+  const namespace = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-namespace"))[0];
+
+  const region = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-region"))[0];
+
+  const restore_job_name = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-restore-job-name"))[0];
+
+  const restore_job_description = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-restore-job-description"))[0];
+
+  const manual_job = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-manual-job"))[0];
+
+  const manual_job_name = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-manual-job-name"))[0];
+
+  const manual_job_description = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-manual-job-description"))[0];
+
+  const shuffle_message = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-shuffle-message"))[0];
+
+  const restore_message = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-restore-message"))[0];
+
+  const manual_message = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-manual-message"))[0];
+
+  const requires = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-requires"))[0];
+
+  const preset_names = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-preset-names"))[0];
+
+  const pinned_systems = Array.from(system_shuffler_form.getElementsByClassName("system-shuffler-pinned-systems"))[0];
+
   system_shuffler_form.addEventListener("submit", async (event) => {
     event.preventDefault();
 
@@ -45,13 +135,49 @@ export const preparation = () => {
         generate_system_shuffler(
           paths_and_sources.paths,
           paths_and_sources.sources,
-          new SystemShufflerConfig(
-            seed.value,
-            max_presets.value,
-            shuffle_chance.value,
-            fixed_shuffle_days.value,
-            shuffle_once_on_install.checked,
-          )
+          new SystemShufflerBuilder()
+            .seed(seedFromInput(seed.value))
+            .max_presets(max_presets.value)
+            .shuffle_chance(shuffle_chance.value)
+            .fixed_shuffle_days(fixed_shuffle_days.value)
+            .shuffle_once_on_install(shuffle_once_on_install.checked)
+            .include_inverse_presets(include_inverse_presets.checked)
+            .mirror_layout(mirror_layout.checked)
+            .scope_events_by_kind(scope_events_by_kind.checked)
+            .detect_wormholes(detect_wormholes.checked)
+            .numeric_conditions(numeric_conditions.checked)
+            .shuffle_minables(shuffle_minables.checked)
+            .shuffle_hazards(shuffle_hazards.checked)
+            .shuffle_radii(shuffle_radii.checked)
+            .combine_preset_files(combine_preset_files.checked)
+            .reconcile_external_changes(reconcile_external_changes.checked)
+            .normalize_music(normalize_music.checked)
+            .verbose(verbose.checked)
+            .include_full_map(include_full_map.checked)
+            .sort_links(sort_links.checked)
+            .one_way(one_way.checked)
+            .split_by_government(split_by_government.checked)
+            .no_fixed_points(no_fixed_points.checked)
+            .shuffle_links(shuffle_links.checked)
+            .shuffle_positions(shuffle_positions.checked)
+            .shuffle_objects(shuffle_objects.checked)
+            .geometry_mode(geometry_modes[geometry_mode.value])
+            .plugin_name(plugin_name.value.trim() || undefined)
+            .plugin_version(plugin_version.value.trim() || undefined)
+            .namespace(namespace.value.trim() || undefined)
+            .region(region.value.trim() || undefined)
+            .restore_job_name(restore_job_name.value.trim() || undefined)
+            .restore_job_description(restore_job_description.value.trim() || undefined)
+            .manual_job(manual_job.checked)
+            .manual_job_name(manual_job_name.value.trim() || undefined)
+            .manual_job_description(manual_job_description.value.trim() || undefined)
+            .shuffle_message(shuffle_message.value.trim() || undefined)
+            .restore_message(restore_message.value.trim() || undefined)
+            .manual_message(manual_message.value.trim() || undefined)
+            .requires(requires.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0))
+            .preset_names(preset_names.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0))
+            .pinned_systems(pinned_systems.value.split(",").map((name) => name.trim()).filter((name) => name.length > 0))
+            .build()
         )
       );
     } catch(error) {
@@ -14,6 +14,18 @@ import {
   preparation as chaos_preparation
 } from "./generators/chaos.js";
 
+import {
+  preparation as jump_chaos_preparation
+} from "./generators/jump_chaos.js";
+
+import {
+  preparation as spaceport_chaos_preparation
+} from "./generators/spaceport_chaos.js";
+
+import {
+  preparation as star_chaos_preparation
+} from "./generators/star_chaos.js";
+
 import {
   preparation as random_galaxy_preparation
 } from "./generators/random_galaxy.js";
@@ -21,5 +33,8 @@ import {
 full_map_preparation();
 system_shuffler_preparation();
 chaos_preparation();
+jump_chaos_preparation();
+spaceport_chaos_preparation();
+star_chaos_preparation();
 random_galaxy_preparation();
 